@@ -1,34 +1,677 @@
 pub mod app_command_errors;
+pub mod config;
+#[cfg(unix)]
+pub mod daemon;
+pub mod live;
+pub mod pomodoro;
+pub mod profile;
+pub mod serve;
+pub mod tui;
+
+use std::{
+  convert::Infallible,
+  fmt::Display,
+  io::{self, IsTerminal},
+  path::PathBuf,
+  sync::Arc,
+};
+
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
 
 use crate::{
   app_data_access,
-  time_entities::time_entities_controller::{DuplicateTitleError, TimeEntitiesController},
+  args_parser::{
+    AllCliArgs, CompleteTitlesCliArgs, DisplayCliArgs, RangeCliArgs, RateCliArgs, ReportCliArgs,
+    ShowAllCliArgs, StatusCliArgs, SwitchCliArgs, TimeStampCliArgs, TimesheetCliArgs, Title,
+  },
+  chrono_utility::{
+    clock::Clock,
+    date_time_display::{DateTimeDisplay, DateTimeFormat, DisplayZone},
+    parsing::{self, TimeParseError},
+    DifferenceFormat,
+  },
+  completion,
+  config::{Config, ConfigError, ConfigSource},
+  daemon::{DaemonRequest, DaemonResponse},
+  data_access,
+  format_utils::{self, Overflow, TableStyle},
+  hooks::{HookConfigError, Hooks},
+  profile::Profile,
+  report::{
+    chart::ChartStyle,
+    timesheet::{Rounding, Timesheet},
+    ReportRange, ReportRangeError,
+  },
+  status::{Status, StatusOutput, StatusTemplate, DEFAULT_TEMPLATE},
+  theme::{self, ColorChoice, Theme, ThemeConfigError},
+  time_entities::{
+    listing::{Filter, FilterParseError, ListOptions, ListOutput},
+    running_policy::{PolicyConfigError, RunningPolicy},
+    time_entities_controller::{
+      EntityKind, EntityOperationError, SwitchOutcome, SwitchTarget, TimeEntitiesController,
+    },
+    time_stamp::TimeStamp,
+  },
 };
 
 use self::app_command_errors::{AppDataIoOrJsonError, AppDataOperationError};
 
-pub fn show_all_items() -> Result<String, AppDataIoOrJsonError> {
-  let data = app_data_access::read_app_data()?;
-  let entities = TimeEntitiesController::from_json(&data)?;
-  Ok(format!("{entities}"))
+/// Reads all time stamps and stop watches which take the current moment from param clock and
+/// follow the running policy of param config. Stop watches which expired since the last
+/// invocation are noted and their hooks run.
+fn load_entities(
+  config: &Config,
+  clock: &Arc<dyn Clock>,
+) -> Result<TimeEntitiesController, AppDataIoOrJsonError> {
+  load_entities_noting_expiries(config, clock).map(|(entities, _)| entities)
 }
 
-pub fn add_time_stamp_by_title(
-  new_title: &str,
-) -> Result<(), AppDataOperationError<DuplicateTitleError>> {
+/// Like [`load_entities`], but also returns the titles of the stop watches which were noted as
+/// expired.
+fn load_entities_noting_expiries(
+  config: &Config,
+  clock: &Arc<dyn Clock>,
+) -> Result<(TimeEntitiesController, Vec<String>), AppDataIoOrJsonError> {
+  let policy = load_policy_or_invalid_data(config)?;
   let data = app_data_access::read_app_data()?;
-  let mut entities = TimeEntitiesController::from_json(&data)?;
-  let result = entities.add_new_time_stamp(new_title);
-
-  match result {
-    Ok(_) => {
-      let json = entities.to_json()?;
-      app_data_access::save_app_data(&json)?;
-      Ok(())
+  let mut entities = TimeEntitiesController::from_json(&data)?
+    .with_clock(Arc::clone(clock))
+    .with_policy(policy);
+  let expired = entities.record_expiries();
+  if expired.is_empty() {
+    return Ok((entities, expired));
+  }
+  // Noted again under the lock, so no change of another invocation is overwritten.
+  let (entities, _) = change_entities(config, clock, |_| Ok::<(), Infallible>(()))?;
+  Ok((entities, expired))
+}
+
+/// Loads all time stamps and stop watches while no other invocation can change them, notes
+/// expired stop watches, applies the operation and saves them unless it fails. Hooks run after
+/// the lock is released. Returns the entities after the operation with its outcome.
+fn change_entities<T, E>(
+  config: &Config,
+  clock: &Arc<dyn Clock>,
+  operation: impl FnOnce(&mut TimeEntitiesController) -> Result<T, E>,
+) -> Result<(TimeEntitiesController, Result<T, E>), AppDataIoOrJsonError> {
+  let policy = load_policy_or_invalid_data(config)?;
+  let locked = app_data_access::lock_app_data()?;
+  let mut entities = TimeEntitiesController::from_json(locked.data())?
+    .with_clock(Arc::clone(clock))
+    .with_policy(policy);
+  let expired = entities.record_expiries();
+  let outcome = operation(&mut entities);
+  if outcome.is_ok() || !expired.is_empty() {
+    locked.save(&entities.to_json()?)?;
+    run_hooks(&mut entities, config);
+  } else {
+    // Nothing was saved, so there is nothing for hooks to react to.
+    entities.take_events();
+  }
+  Ok((entities, outcome))
+}
+
+/// Like [`change_entities`], but only returns the outcome of the operation.
+fn update_entities<T, E: Display>(
+  config: &Config,
+  clock: &Arc<dyn Clock>,
+  operation: impl FnOnce(&mut TimeEntitiesController) -> Result<T, E>,
+) -> Result<T, AppDataOperationError<E>> {
+  let (_, outcome) =
+    change_entities(config, clock, operation).map_err(AppDataOperationError::IoOrJsonError)?;
+  outcome.map_err(AppDataOperationError::OperationErrorOnEntity)
+}
+
+/// Applies the request of the daemon protocol to the time stamps and stop watches: by the
+/// daemon if it runs and otherwise under the lock of the data file. Returns the response, which
+/// tells why a refused request was refused.
+/// # Errors
+/// If the data can not be read or saved.
+pub fn apply_request(
+  request: DaemonRequest,
+  config: &Config,
+  clock: &Arc<dyn Clock>,
+) -> Result<DaemonResponse, AppDataIoOrJsonError> {
+  #[cfg(unix)]
+  if let Some(response) = crate::daemon::send(&request)? {
+    return Ok(response);
+  }
+  // A response without a change is passed as error, so nothing is saved.
+  let (_, outcome) = change_entities(
+    config,
+    clock,
+    |entities| match crate::daemon::handle_request(entities, request) {
+      (response, true) => Ok(response),
+      (response, false) => Err(response),
+    },
+  )?;
+  Ok(outcome.unwrap_or_else(|response| response))
+}
+
+/// Runs the hooks of param config for the changes of the entities which are not handled yet.
+/// An invalid hooks section is reported on stderr.
+fn run_hooks(entities: &mut TimeEntitiesController, config: &Config) {
+  let events = entities.take_events();
+  if events.is_empty() {
+    return;
+  }
+  match load_hooks(config) {
+    Ok(hooks) => hooks.run(&events, entities),
+    Err(error) => eprintln!("Hooks were not run: {error}"),
+  }
+}
+
+/// Returns how moments and durations are shown as chosen via flags or else via the settings.
+pub fn date_time_display(args: &DisplayCliArgs, config: &Config) -> DateTimeDisplay {
+  let zone = args
+    .time_zone
+    .or_else(|| config.parsed("display.time_zone", DisplayZone::from_name));
+  let format = args
+    .time_format
+    .clone()
+    .or_else(|| config.parsed("display.time_format", DateTimeFormat::from_pattern));
+  let duration_format = args
+    .duration_format
+    .or_else(|| config.parsed("display.duration_format", DifferenceFormat::from_name));
+  DateTimeDisplay::new(zone.unwrap_or_default(), format.unwrap_or_default())
+    .with_duration_format(duration_format.unwrap_or_default())
+}
+
+/// Returns whether to colour output as chosen via flag or else via the settings.
+pub fn color_choice(args: &DisplayCliArgs, config: &Config) -> ColorChoice {
+  args
+    .color
+    .or_else(|| config.parsed("display.color", ColorChoice::from_name))
+    .unwrap_or_default()
+}
+
+/// Returns the settings of the user config file, the config file of the selected profile, the
+/// project config file and the environment. The profile is selected via the environment or
+/// else via the other files.
+pub fn load_config() -> Result<Config, AppDataOperationError<ConfigError>> {
+  let mut files = Vec::new();
+  if let Some(content) = app_data_access::read_app_config()? {
+    files.push((data_access::paths::get_config_path()?, content));
+  }
+  let project_file = app_data_access::read_project_config()?;
+  let config = merge_config(&files, project_file.as_ref())?;
+
+  let profile = config
+    .parsed("profile.name", Profile::parse)
+    .unwrap_or_default();
+  if profile.is_default() {
+    return Ok(config);
+  }
+  let path = data_access::paths::get_profile_config_path(&profile)?;
+  let Some(content) = app_data_access::read_config_file(&path)? else {
+    return Ok(config);
+  };
+  let mut profile_config = Config::default();
+  profile_config
+    .merge_file(&content, &path)
+    .map_err(AppDataOperationError::OperationErrorOnEntity)?;
+  if profile_config.get("profile.name").is_some() {
+    return Err(AppDataOperationError::OperationErrorOnEntity(
+      ConfigError::InvalidValue {
+        source: Some(ConfigSource::File(path)),
+        key: "profile.name",
+        error: "The config file of a profile can not select a profile".to_string(),
+      },
+    ));
+  }
+  files.push((path, content));
+  merge_config(&files, project_file.as_ref())
+}
+
+/// Returns the settings of the config files, later ones winning, then of the project config
+/// file and then of the environment.
+fn merge_config(
+  files: &[(PathBuf, String)],
+  project_file: Option<&(PathBuf, String)>,
+) -> Result<Config, AppDataOperationError<ConfigError>> {
+  let mut config = Config::default();
+  for (path, content) in files {
+    config
+      .merge_file(content, path)
+      .map_err(AppDataOperationError::OperationErrorOnEntity)?;
+  }
+  if let Some((path, content)) = project_file {
+    config
+      .merge_project_file(content, path)
+      .map_err(AppDataOperationError::OperationErrorOnEntity)?;
+  }
+  config
+    .merge_env(|name| std::env::var(name).ok())
+    .map_err(AppDataOperationError::OperationErrorOnEntity)?;
+  Ok(config)
+}
+
+/// Returns the running policy of the settings. Concurrent if none is set.
+pub fn load_policy(config: &Config) -> Result<RunningPolicy, PolicyConfigError> {
+  RunningPolicy::from_config(&config.to_toml())
+}
+
+/// Like [`load_policy`] with an invalid policy as invalid data, so no time stamp starts against
+/// the policy the user meant to set.
+fn load_policy_or_invalid_data(config: &Config) -> io::Result<RunningPolicy> {
+  load_policy(config).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error.to_string()))
+}
+
+/// Returns the hooks of the settings. No hooks if none are set.
+pub fn load_hooks(config: &Config) -> Result<Hooks, HookConfigError> {
+  Hooks::from_config(&config.to_toml())
+}
+
+/// Returns the theme of the settings if output is coloured as chosen via param choice, the
+/// environment variable NO_COLOR and whether stdout is a terminal. Returns a plain theme
+/// otherwise. The theme section of the settings is validated either way.
+pub fn load_theme(choice: ColorChoice, config: &Config) -> Result<Theme, ThemeConfigError> {
+  let theme = Theme::from_config(&config.to_toml())?;
+
+  let no_color = std::env::var(theme::NO_COLOR_ENV_VAR).ok();
+  if choice.should_color(io::stdout().is_terminal(), no_color.as_deref()) {
+    Ok(theme)
+  } else {
+    Ok(Theme::plain())
+  }
+}
+
+/// Lists the time stamps and stop watches chosen by the filters, sorting, limit and columns of
+/// the user as a table or as JSON. Tables are only fitted into the width of a terminal, so piped
+/// output keeps whole titles.
+pub fn show_all_items(
+  args: &ShowAllCliArgs,
+  config: &Config,
+  display: &DateTimeDisplay,
+  theme: &Theme,
+  clock: &Arc<dyn Clock>,
+) -> Result<String, AppDataOperationError<FilterParseError>> {
+  let options = list_options_from_args(
+    &args.list,
+    display,
+    theme,
+    clock.as_ref(),
+    io::stdout()
+      .is_terminal()
+      .then(format_utils::terminal_width),
+  )
+  .map_err(AppDataOperationError::OperationErrorOnEntity)?;
+
+  let entities = load_entities(config, clock).map_err(AppDataOperationError::IoOrJsonError)?;
+  match args.output {
+    ListOutput::Table => Ok(entities.to_listed_text_table(display, &options)),
+    ListOutput::Json => Ok(entities.to_listed_json(&options)?),
+  }
+}
+
+/// Returns the options of a list of time stamps and stop watches which fits into param width if
+/// given.
+/// Moments of filters are relative to the current moment in the time zone of param display.
+fn list_options_from_args(
+  args: &AllCliArgs,
+  display: &DateTimeDisplay,
+  theme: &Theme,
+  clock: &dyn Clock,
+  width: Option<usize>,
+) -> Result<ListOptions, FilterParseError> {
+  let filters = args
+    .filters
+    .iter()
+    .map(|expression| parse_filter(expression, display, clock))
+    .collect::<Result<Vec<Filter>, FilterParseError>>()?;
+  let overflow = if args.wrap {
+    Overflow::Wrap
+  } else {
+    Overflow::Truncate
+  };
+
+  Ok(
+    ListOptions::new()
+      .with_filters(filters)
+      .with_sort(args.sort)
+      .with_limit(args.limit)
+      .with_columns(args.columns.clone())
+      .with_table_style(
+        TableStyle::default()
+          .with_max_table_width(width)
+          .with_overflow(overflow)
+          .with_borders(args.borders),
+      )
+      .with_theme(theme.clone()),
+  )
+}
+
+/// Parses a filter expression with moments relative to now in the time zone of param display.
+fn parse_filter(
+  expression: &str,
+  display: &DateTimeDisplay,
+  clock: &dyn Clock,
+) -> Result<Filter, FilterParseError> {
+  let now = clock.now();
+  match display.zone() {
+    DisplayZone::Local => Filter::parse(expression, &now.with_timezone(&Local)),
+    DisplayZone::Named(named) => Filter::parse(expression, &now.with_timezone(&named)),
+  }
+}
+
+/// Returns the moment at which a new time stamp starts. It is now if neither the option at nor
+/// ago is given by the user.
+/// # Errors
+/// If the given moment or duration can not be parsed or lies in the future.
+pub fn started_moment_from_args(
+  args: &TimeStampCliArgs,
+  clock: &dyn Clock,
+) -> Result<DateTime<Utc>, TimeParseError> {
+  let now = clock.now().with_timezone(&Local);
+  match (&args.at, &args.ago) {
+    (Some(at), _) => parsing::parse_past_moment(at, &now),
+    (None, Some(ago)) => parsing::parse_moment_ago(ago, &now),
+    (None, None) => Ok(now.with_timezone(&Utc)),
+  }
+}
+
+/// Returns the time stamp described by the user with its start, project and tags.
+/// # Errors
+/// If the given moment or duration can not be parsed or lies in the future.
+pub fn new_time_stamp_from_args(
+  args: &TimeStampCliArgs,
+  clock: &dyn Clock,
+) -> Result<TimeStamp, TimeParseError> {
+  let started = started_moment_from_args(args, clock)?;
+  Ok(
+    TimeStamp::with_started(&args.name, started)
+      .with_project(args.project.as_deref())
+      .with_tags(&args.tags)
+      .with_rate(args.rate.clone())
+      .with_billable(!args.non_billable),
+  )
+}
+
+pub fn add_time_stamp(
+  new_time_stamp: TimeStamp,
+  config: &Config,
+  clock: &Arc<dyn Clock>,
+) -> Result<(), AppDataOperationError<EntityOperationError>> {
+  update_entities(config, clock, |entities| {
+    entities.add_time_stamp(new_time_stamp)
+  })
+}
+
+/// Pauses or finishes every running time stamp and starts or resumes the chosen one in one
+/// step. A running daemon does it under its lock, otherwise the data file is locked while it is
+/// read and written once.
+/// Returns both changes as text.
+/// # Errors
+/// If the data can not be read or saved, the title is blank or its time stamp is finished.
+pub fn switch_to(
+  args: &SwitchCliArgs,
+  config: &Config,
+  clock: &Arc<dyn Clock>,
+) -> Result<String, AppDataOperationError<EntityOperationError>> {
+  #[cfg(unix)]
+  if let Some(outcome) = crate::daemon::switch(&args.name, args.finish)? {
+    return Ok(switch_message(&outcome));
+  }
+
+  let outcome = update_entities(config, clock, |entities| {
+    entities.switch_to(&args.name, args.finish)
+  })?;
+  Ok(switch_message(&outcome))
+}
+
+fn switch_message(outcome: &SwitchOutcome) -> String {
+  let title = &outcome.title;
+  let target = match outcome.target {
+    SwitchTarget::Started => format!("Started \"{title}\""),
+    SwitchTarget::Resumed => format!("Resumed \"{title}\""),
+    SwitchTarget::AlreadyRunning => format!("\"{title}\" is running already"),
+  };
+  if outcome.stopped.is_empty() {
+    return target;
+  }
+  let stopped: Vec<String> = outcome
+    .stopped
+    .iter()
+    .map(|title| format!("\"{title}\""))
+    .collect();
+  let verb = if outcome.finished {
+    "Finished"
+  } else {
+    "Paused"
+  };
+  format!("{verb} {}\n{target}", stopped.join(", "))
+}
+
+/// Pauses the time stamp with the title or else the stop watch with it. Returns the text to show.
+/// # Errors
+/// If the data can not be read or saved or there is no such entry or it is paused or finished.
+pub fn stop(
+  args: &Title,
+  config: &Config,
+  clock: &Arc<dyn Clock>,
+) -> Result<String, AppDataOperationError<EntityOperationError>> {
+  let title = args.name.trim();
+  update_entities(config, clock, |entities| {
+    entities.pause(kind_of(entities, title), title)
+  })?;
+  Ok(format!("Paused \"{title}\""))
+}
+
+/// Resumes the time stamp with the title or else the stop watch with it. Returns the text to
+/// show.
+/// # Errors
+/// If the data can not be read or saved, there is no such entry, it is not paused or finished
+/// or the running policy refuses it.
+pub fn resume(
+  args: &Title,
+  config: &Config,
+  clock: &Arc<dyn Clock>,
+) -> Result<String, AppDataOperationError<EntityOperationError>> {
+  let title = args.name.trim();
+  update_entities(config, clock, |entities| {
+    entities.resume(kind_of(entities, title), title)
+  })?;
+  Ok(format!("Resumed \"{title}\""))
+}
+
+/// Returns the kind of the entry with the title. Time stamps come first, so it is a time stamp
+/// if there is neither.
+fn kind_of(entities: &TimeEntitiesController, title: &str) -> EntityKind {
+  let is_stop_watch = entities
+    .time_stamp_of(EntityKind::TimeStamp, title)
+    .is_none()
+    && entities
+      .time_stamp_of(EntityKind::StopWatch, title)
+      .is_some();
+  if is_stop_watch {
+    EntityKind::StopWatch
+  } else {
+    EntityKind::TimeStamp
+  }
+}
+
+/// Returns the range of days chosen by the user. It is today if no range is given.
+/// Days are taken from param zone.
+/// # Errors
+/// If a day can not be parsed or the first day lies after the last day.
+pub fn report_range_from_args(
+  args: &RangeCliArgs,
+  zone: DisplayZone,
+  clock: &dyn Clock,
+) -> Result<ReportRange, ReportRangeError> {
+  let now = clock.now();
+  let today = zone.date_of(&now);
+  if args.week {
+    return Ok(ReportRange::week_of(today));
+  }
+
+  let last_day = match &args.to {
+    Some(to) => parse_day_in_zone(to, zone, now)?,
+    None => today,
+  };
+  let first_day = match &args.from {
+    Some(from) => parse_day_in_zone(from, zone, now)?,
+    None => last_day,
+  };
+  ReportRange::new(first_day, last_day)
+}
+
+fn parse_day_in_zone(
+  input: &str,
+  zone: DisplayZone,
+  now: DateTime<Utc>,
+) -> Result<NaiveDate, TimeParseError> {
+  match zone {
+    DisplayZone::Local => parsing::parse_day(input, &now.with_timezone(&Local)),
+    DisplayZone::Named(named) => parsing::parse_day(input, &now.with_timezone(&named)),
+  }
+}
+
+/// Returns the report over the range of days chosen by the user in the chosen output.
+pub fn create_report(
+  args: &ReportCliArgs,
+  config: &Config,
+  display: &DateTimeDisplay,
+  theme: &Theme,
+  clock: &Arc<dyn Clock>,
+) -> Result<String, AppDataOperationError<ReportRangeError>> {
+  let range = report_range_from_args(&args.range, display.zone(), clock.as_ref())
+    .map_err(AppDataOperationError::OperationErrorOnEntity)?;
+  let entities = load_entities(config, clock).map_err(AppDataOperationError::IoOrJsonError)?;
+  let report = entities.create_report(range, display.zone());
+
+  if args.chart {
+    let style = ChartStyle::new(format_utils::terminal_width()).with_color(theme.is_colored());
+    return Ok(report.to_chart(args.by, display, &style));
+  }
+  Ok(report.render(args.by, args.output, display)?)
+}
+
+/// Returns the titles which fit the command, one per line. Only reads the data like
+/// [`show_status`].
+/// # Errors
+/// If the data can not be read.
+pub fn complete_titles(
+  args: &CompleteTitlesCliArgs,
+  clock: &Arc<dyn Clock>,
+) -> Result<String, AppDataIoOrJsonError> {
+  let entities = read_entities_if_any(clock)?;
+  Ok(completion::titles_for(&args.command, &entities).join("\n"))
+}
+
+/// Reads all time stamps and stop watches without noting expiries, so nothing is written.
+/// Without a data file there are none.
+fn read_entities_if_any(
+  clock: &Arc<dyn Clock>,
+) -> Result<TimeEntitiesController, AppDataIoOrJsonError> {
+  match app_data_access::read_app_data() {
+    Ok(data) => Ok(TimeEntitiesController::from_json(&data)?.with_clock(Arc::clone(clock))),
+    Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(TimeEntitiesController::empty()),
+    Err(error) => Err(error.into()),
+  }
+}
+
+/// Returns the status line about the running entry or the snippet chosen by the user. Only
+/// reads the data, so it stays fast and never runs hooks. Without a data file nothing runs.
+/// # Errors
+/// If the data can not be read.
+pub fn show_status(
+  args: &StatusCliArgs,
+  config: &Config,
+  display: &DateTimeDisplay,
+  clock: &Arc<dyn Clock>,
+) -> Result<String, AppDataIoOrJsonError> {
+  if let Some(snippet) = args.snippet {
+    return Ok(snippet.text().to_string());
+  }
+  let template = args
+    .template
+    .clone()
+    .or_else(|| config.parsed("status.template", StatusTemplate::parse))
+    .unwrap_or_else(|| StatusTemplate::parse(DEFAULT_TEMPLATE).expect("Default template is valid"));
+  let idle = args
+    .idle
+    .clone()
+    .or_else(|| config.parsed("status.idle", |idle| Ok::<_, ()>(idle.to_string())))
+    .unwrap_or_default();
+  let entities = read_entities_if_any(clock)?;
+  let status = Status::of(&entities);
+
+  match args.output {
+    StatusOutput::Text => Ok(template.render(&status, display).unwrap_or(idle)),
+    StatusOutput::Json => Ok(status.to_json(&template, &idle, display)?),
+  }
+}
+
+/// Returns the timesheet over the range of days chosen by the user with the chosen rounding.
+pub fn create_timesheet(
+  args: &TimesheetCliArgs,
+  config: &Config,
+  display: &DateTimeDisplay,
+  clock: &Arc<dyn Clock>,
+) -> Result<String, AppDataOperationError<ReportRangeError>> {
+  let range = report_range_from_args(&args.range, display.zone(), clock.as_ref())
+    .map_err(AppDataOperationError::OperationErrorOnEntity)?;
+  let entities = load_entities(config, clock).map_err(AppDataOperationError::IoOrJsonError)?;
+  let report = entities.create_report(range, display.zone());
+
+  let mut rounding = Rounding::new(args.round.unwrap_or_else(Duration::zero), args.rounding);
+  if let Some(minimum) = args.minimum {
+    rounding = rounding.with_minimum(minimum);
+  }
+  let mut timesheet = Timesheet::from_report(&report, &rounding);
+  if let Some(project) = &args.project {
+    timesheet = timesheet.for_project(project);
+  }
+
+  Ok(timesheet.render(args.output, display))
+}
+
+/// Lists the rates of all projects, shows the rate of one project or sets or removes it.
+/// Returns the text to show to the user.
+pub fn manage_project_rate(
+  args: &RateCliArgs,
+  config: &Config,
+  clock: &Arc<dyn Clock>,
+) -> Result<String, AppDataIoOrJsonError> {
+  let entities = load_entities(config, clock)?;
+  let project = match &args.project {
+    Some(project) => project.trim(),
+    None => {
+      let rates = entities.project_rates();
+      if rates.is_empty() {
+        return Ok("No project has an hourly rate".to_string());
+      }
+      let rows: Vec<Vec<String>> = std::iter::once(vec!["Project".to_string(), "Rate".to_string()])
+        .chain(
+          rates
+            .iter()
+            .map(|(project, rate)| vec![project.clone(), rate.to_string()]),
+        )
+        .collect();
+      return Ok(format_utils::format_to_text_table(&rows, 2));
+    }
+  };
+
+  match (&args.rate, args.remove) {
+    (None, false) => Ok(match entities.project_rates().get(project) {
+      Some(rate) => format!("{project}: {rate}"),
+      None => format!("{project} has no hourly rate"),
+    }),
+    (Some(rate), _) => {
+      change_entities(config, clock, |entities| {
+        entities.set_project_rate(project, Some(rate.clone()));
+        Ok::<(), Infallible>(())
+      })?;
+      Ok(format!("Hourly rate of {project} set to {rate}"))
     }
-    Err(_) => Err(AppDataOperationError::OperationErrorOnEntity(
-      DuplicateTitleError,
-    )),
+    // Nothing is saved if there is no rate to remove.
+    (None, true) => match change_entities(config, clock, |entities| {
+      entities.set_project_rate(project, None).ok_or(())
+    })? {
+      (_, Ok(_)) => Ok(format!("Hourly rate of {project} removed")),
+      (_, Err(())) => Ok(format!("{project} has no hourly rate")),
+    },
   }
 }
 
@@ -0,0 +1,127 @@
+//! Shows and changes settings. Get and list show the settings which apply in the working
+//! directory with all layers merged, set and unset change one config file.
+use std::path::PathBuf;
+
+use crate::{
+  app_data_access,
+  args_parser::{ConfigAction, ConfigCliArgs},
+  config::{self, ConfigError, ConfigSource, Setting, SETTINGS},
+  data_access::paths,
+  profile::Profile,
+};
+
+use super::app_command_errors::AppDataOperationError;
+
+/// Runs the config action of the user. Returns the text to show.
+/// # Errors
+/// If a config file can not be read or written, has invalid settings or the key or value given
+/// is invalid.
+pub fn manage_config(args: &ConfigCliArgs) -> Result<String, AppDataOperationError<ConfigError>> {
+  match &args.action {
+    ConfigAction::Get { key } => {
+      let setting = Setting::of(key).map_err(AppDataOperationError::OperationErrorOnEntity)?;
+      let config = super::load_config()?;
+      match config.get(setting.key) {
+        Some(toml::Value::String(text)) => Ok(text.clone()),
+        Some(value) => Ok(value.to_string()),
+        None => Err(AppDataOperationError::OperationErrorOnEntity(
+          ConfigError::NotSet(setting.key),
+        )),
+      }
+    }
+    ConfigAction::Set {
+      key,
+      value,
+      project,
+      for_profile,
+    } => {
+      if *for_profile && key == "profile.name" {
+        return Err(AppDataOperationError::OperationErrorOnEntity(
+          ConfigError::InvalidValue {
+            source: None,
+            key: "profile.name",
+            error: "The config file of a profile can not select a profile".to_string(),
+          },
+        ));
+      }
+      let setting = Setting::of(key).map_err(AppDataOperationError::OperationErrorOnEntity)?;
+      if *project {
+        setting
+          .check_in_project(None)
+          .map_err(AppDataOperationError::OperationErrorOnEntity)?;
+      }
+      let path = target_path(*project, *for_profile)?;
+      let content = app_data_access::read_config_file(&path)?.unwrap_or_default();
+      let content = config::set_in_file(&content, &path, key, value)
+        .map_err(AppDataOperationError::OperationErrorOnEntity)?;
+      app_data_access::save_config_file(&path, &content)?;
+
+      let message = format!("Set {key} in {}", path.display());
+      // Other files may still have invalid settings, which get and list point at.
+      let overriding = super::load_config()
+        .ok()
+        .and_then(|config| config.source(key).cloned())
+        .filter(|source| *source != ConfigSource::File(path.clone()));
+      match overriding {
+        Some(source) => Ok(format!("{message}, but {source} overrides it here")),
+        None => Ok(message),
+      }
+    }
+    ConfigAction::Unset {
+      key,
+      project,
+      for_profile,
+    } => {
+      let path = target_path(*project, *for_profile)?;
+      let content = app_data_access::read_config_file(&path)?.unwrap_or_default();
+      let (content, removed) = config::unset_in_file(&content, &path, key)
+        .map_err(AppDataOperationError::OperationErrorOnEntity)?;
+      if !removed {
+        return Ok(format!("{key} is not set in {}", path.display()));
+      }
+      app_data_access::save_config_file(&path, &content)?;
+      Ok(format!("Removed {key} from {}", path.display()))
+    }
+    ConfigAction::List { all } => {
+      let config = super::load_config()?;
+      let lines: Vec<String> = SETTINGS
+        .iter()
+        .filter_map(
+          |setting| match (config.get(setting.key), config.source(setting.key)) {
+            (Some(value), Some(source)) => Some(format!("{} = {value}  # {source}", setting.key)),
+            _ if *all => Some(format!("# {}: {}", setting.key, setting.description)),
+            _ => None,
+          },
+        )
+        .collect();
+      if lines.is_empty() {
+        Ok("No settings are set, see \"config list --all\" for all keys".to_string())
+      } else {
+        Ok(lines.join("\n"))
+      }
+    }
+  }
+}
+
+/// Returns the path of the project config file which applies to the working directory or a
+/// new one in it if param project is true and the one of the selected profile if param
+/// for_profile is true. Returns the path of the user config file otherwise.
+fn target_path(project: bool, for_profile: bool) -> std::io::Result<PathBuf> {
+  if for_profile {
+    let profile = match super::load_config() {
+      Ok(config) => config
+        .parsed("profile.name", Profile::parse)
+        .unwrap_or_default(),
+      Err(_) => paths::selected_profile()?,
+    };
+    return paths::get_profile_config_path(&profile);
+  }
+  if !project {
+    return paths::get_config_path();
+  }
+  let directory = std::env::current_dir()?;
+  Ok(
+    paths::find_project_config_path(&directory)
+      .unwrap_or_else(|| directory.join(config::PROJECT_CONFIG_FILE_NAME)),
+  )
+}
@@ -0,0 +1,221 @@
+//! Daemon which owns all time stamps and stop watches while it runs. Every connection is served
+//! on its own thread, but requests are applied one after another, so mutations never overlap,
+//! and the data file is written after every change. The daemon holds the lock of the data file
+//! while it runs, so no invocation changes the file behind its back. Stop watches which expire
+//! while it runs are announced on its output and their hooks run.
+use std::{
+  io::{self, BufRead, BufReader},
+  os::unix::net::{UnixListener, UnixStream},
+  path::PathBuf,
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex, PoisonError,
+  },
+  thread,
+  time::Duration as StdDuration,
+};
+
+use crate::{
+  app_data_access,
+  args_parser::DaemonCliArgs,
+  chrono_utility::clock::Clock,
+  config::Config,
+  daemon::{self, DaemonRequest, DaemonResponse},
+  data_access::paths,
+  time_entities::time_entities_controller::TimeEntitiesController,
+};
+
+use super::app_command_errors::AppDataIoOrJsonError;
+
+const EXPIRY_CHECK_INTERVAL: StdDuration = StdDuration::from_secs(1);
+const NOT_RUNNING: &str = "No daemon is running";
+
+/// Removes the socket file when the daemon stops.
+struct SocketFile(PathBuf);
+
+impl Drop for SocketFile {
+  fn drop(&mut self) {
+    let _ = std::fs::remove_file(&self.0);
+  }
+}
+
+/// Runs the daemon until it is asked to shut down or stops it or tells whether it runs as chosen
+/// by the user. Returns the text to show to the user.
+/// # Errors
+/// If the data can not be read or the socket can not be used.
+pub fn manage_daemon(
+  args: &DaemonCliArgs,
+  config: &Config,
+  clock: &Arc<dyn Clock>,
+) -> Result<String, AppDataIoOrJsonError> {
+  let socket_path = paths::get_socket_path()?;
+  if args.stop {
+    return Ok(match daemon::send(&DaemonRequest::Shutdown)? {
+      Some(_) => "Daemon stopped".to_string(),
+      None => NOT_RUNNING.to_string(),
+    });
+  }
+  if args.status {
+    return Ok(match daemon::send(&DaemonRequest::Ping)? {
+      Some(_) => format!("Daemon is running on {}", socket_path.display()),
+      None => NOT_RUNNING.to_string(),
+    });
+  }
+
+  if daemon::send(&DaemonRequest::Ping)?.is_some() {
+    return Err(
+      io::Error::new(
+        io::ErrorKind::AddrInUse,
+        format!("Daemon is already running on {}", socket_path.display()),
+      )
+      .into(),
+    );
+  }
+  // Left over by a daemon which was killed.
+  if socket_path.exists() {
+    std::fs::remove_file(&socket_path)?;
+  }
+
+  let policy = super::load_policy_or_invalid_data(config)?;
+  let _data_lock = app_data_access::lock_data_file(&paths::selected_profile()?)?;
+  let data = app_data_access::read_app_data_file()?;
+  let entities = TimeEntitiesController::from_json(&data)?
+    .with_clock(Arc::clone(clock))
+    .with_policy(policy);
+  let entities = Arc::new(Mutex::new(entities));
+  let listener = UnixListener::bind(&socket_path)?;
+  let _socket_file = SocketFile(socket_path.clone());
+  println!("Daemon listening on {}", socket_path.display());
+
+  // Hooks use the settings at the start like the running policy.
+  let config = Arc::new(config.clone());
+  let watched = Arc::clone(&entities);
+  let watched_config = Arc::clone(&config);
+  thread::spawn(move || announce_expiries(&watched, &watched_config));
+
+  let shutdown = Arc::new(AtomicBool::new(false));
+  for stream in listener.incoming() {
+    if shutdown.load(Ordering::SeqCst) {
+      break;
+    }
+    match stream {
+      Ok(stream) => {
+        let entities = Arc::clone(&entities);
+        let config = Arc::clone(&config);
+        let shutdown = Arc::clone(&shutdown);
+        let socket_path = socket_path.clone();
+        thread::spawn(move || {
+          match serve_connection(stream, &entities, &config) {
+            Ok(true) => {
+              shutdown.store(true, Ordering::SeqCst);
+              // Wakes up the loop waiting for the next connection, so it sees the shutdown.
+              let _ = UnixStream::connect(&socket_path);
+            }
+            Ok(false) => (),
+            Err(error) => eprintln!("Request failed: {error}"),
+          }
+        });
+      }
+      Err(error) => eprintln!("Connection failed: {error}"),
+    }
+  }
+  // Waits for a change in progress to be saved.
+  drop(entities.lock().unwrap_or_else(PoisonError::into_inner));
+
+  Ok("Daemon stopped".to_string())
+}
+
+/// Answers the request of a connection, which waits at most [`daemon::CLIENT_TIMEOUT`] for it.
+/// A lock request keeps the entities locked until the client saves them or closes the
+/// connection. Returns true if the daemon is asked to shut down.
+fn serve_connection(
+  stream: UnixStream,
+  entities: &Mutex<TimeEntitiesController>,
+  config: &Config,
+) -> io::Result<bool> {
+  stream.set_read_timeout(Some(daemon::CLIENT_TIMEOUT))?;
+  stream.set_write_timeout(Some(daemon::CLIENT_TIMEOUT))?;
+  let mut reader = BufReader::new(&stream);
+  let request = match read_request(&mut reader)? {
+    Ok(request) => request,
+    Err(response) => {
+      daemon::write_message(&mut &stream, &response)?;
+      return Ok(false);
+    }
+  };
+
+  let shutdown = request == DaemonRequest::Shutdown;
+  let is_lock = request == DaemonRequest::Lock;
+  let mut entities = entities.lock().unwrap_or_else(PoisonError::into_inner);
+  let response = apply(&mut entities, request, config);
+  daemon::write_message(&mut &stream, &response)?;
+  if is_lock {
+    // Only a save ends the transaction with a change, anything else leaves the data as it was.
+    if let Ok(save @ DaemonRequest::Save { .. }) = read_request(&mut reader)? {
+      let response = apply(&mut entities, save, config);
+      daemon::write_message(&mut &stream, &response)?;
+    }
+  }
+  Ok(shutdown)
+}
+
+/// Reads the next request of the connection. Returns the error response to send if it is
+/// invalid.
+fn read_request(
+  reader: &mut BufReader<&UnixStream>,
+) -> io::Result<Result<DaemonRequest, DaemonResponse>> {
+  let mut line = String::new();
+  reader.read_line(&mut line)?;
+  Ok(
+    serde_json::from_str::<DaemonRequest>(&line).map_err(|error| DaemonResponse::Error {
+      message: format!("Invalid request: {error}"),
+    }),
+  )
+}
+
+/// Applies the request, saves the entities if they changed and runs the hooks.
+fn apply(
+  entities: &mut TimeEntitiesController,
+  request: DaemonRequest,
+  config: &Config,
+) -> DaemonResponse {
+  let (response, changed) = daemon::handle_request(entities, request);
+  let persisted = persist_if(changed, entities);
+  super::run_hooks(entities, config);
+  match persisted {
+    Ok(()) => response,
+    Err(error) => DaemonResponse::Error {
+      message: format!("Saving failed: {error}"),
+    },
+  }
+}
+
+fn persist_if(
+  changed: bool,
+  entities: &TimeEntitiesController,
+) -> Result<(), AppDataIoOrJsonError> {
+  if !changed {
+    return Ok(());
+  }
+  let json = entities.to_json()?;
+  app_data_access::save_app_data_file(&json)?;
+  Ok(())
+}
+
+/// Prints a line and runs the hooks for every stop watch which expires while the daemon runs.
+fn announce_expiries(entities: &Mutex<TimeEntitiesController>, config: &Config) {
+  loop {
+    {
+      let mut entities = entities.lock().unwrap_or_else(PoisonError::into_inner);
+      let expired = entities.record_expiries();
+      for title in &expired {
+        println!("Stop watch \"{title}\" expired");
+      }
+      if let Err(error) = persist_if(!expired.is_empty(), &entities) {
+        eprintln!("Saving failed: {error}");
+      }
+      super::run_hooks(&mut entities, config);
+    }
+    thread::sleep(EXPIRY_CHECK_INTERVAL);
+  }
+}
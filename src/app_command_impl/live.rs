@@ -0,0 +1,239 @@
+//! Live view which redraws the table of time stamps and stop watches in place every interval
+//! until the user quits with q, Esc or Ctrl-C. Stop watches which expire while watching flash.
+use std::{
+  collections::{BTreeMap, BTreeSet},
+  io::{self, Write},
+  sync::Arc,
+  time::{Duration as StdDuration, Instant},
+};
+
+use crossterm::{
+  cursor,
+  event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+  execute, queue,
+  terminal::{self, ClearType},
+};
+
+use crate::{
+  args_parser::LiveCliArgs,
+  chrono_utility::{clock::Clock, date_time_display::DateTimeDisplay},
+  config::Config,
+  format_utils,
+  theme::Theme,
+  time_entities::{
+    listing::{EntityState, FilterParseError, ListOptions},
+    stop_watch::StopWatch,
+    time_entities_controller::TimeEntitiesController,
+    TimeEntity,
+  },
+};
+
+use super::app_command_errors::AppDataOperationError;
+
+/// Number of redraws a stop watch flashes after it expired.
+const FLASH_TICKS: u32 = 10;
+const MIN_INTERVAL: StdDuration = StdDuration::from_millis(100);
+const QUIT_HINT: &str = "Press q, Esc or Ctrl-C to quit.";
+const BELL: &str = "\x07";
+
+/// Remembers which stop watches were expired at the last redraw to flash those which expire
+/// while watching.
+#[derive(Debug, Default)]
+pub struct ExpiryWatch {
+  /// Titles of expired stop watches. None before the first redraw.
+  expired: Option<BTreeSet<String>>,
+  /// Redraws left to flash by title.
+  flashing: BTreeMap<String, u32>,
+}
+
+impl ExpiryWatch {
+  /// Notes which stop watches are expired now and counts down the flashing of the others.
+  /// Stop watches which are already expired at the first call are not flashed.
+  /// Returns the titles of the stop watches which expired since the last call.
+  pub fn tick(&mut self, stop_watches: &[StopWatch], clock: &dyn Clock) -> Vec<String> {
+    let now_expired: BTreeSet<String> = stop_watches
+      .iter()
+      .filter(|stop_watch| EntityState::of(*stop_watch, clock) == EntityState::Expired)
+      .map(|stop_watch| stop_watch.get_title().to_string())
+      .collect();
+    let newly_expired: Vec<String> = match &self.expired {
+      Some(before) => now_expired.difference(before).cloned().collect(),
+      None => Vec::new(),
+    };
+
+    for ticks_left in self.flashing.values_mut() {
+      *ticks_left -= 1;
+    }
+    self.flashing.retain(|_, ticks_left| *ticks_left > 0);
+    for title in &newly_expired {
+      self.flashing.insert(title.clone(), FLASH_TICKS);
+    }
+    self.expired = Some(now_expired);
+
+    newly_expired
+  }
+
+  /// Returns the titles of the stop watches which expired during the last redraws.
+  pub fn flashing(&self) -> impl Iterator<Item = &str> {
+    self.flashing.keys().map(String::as_str)
+  }
+}
+
+/// Shows the view on the alternate screen in raw mode. The terminal is restored when dropped,
+/// also after an error.
+pub(super) struct AlternateScreen;
+
+impl AlternateScreen {
+  pub(super) fn enter() -> io::Result<Self> {
+    terminal::enable_raw_mode()?;
+    execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+    Ok(AlternateScreen)
+  }
+}
+
+impl Drop for AlternateScreen {
+  fn drop(&mut self) {
+    let _ = execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+    let _ = terminal::disable_raw_mode();
+  }
+}
+
+/// Redraws the time stamps and stop watches chosen by the user every interval. Data is read
+/// again on every redraw, so changes of other invocations show up.
+/// # Errors
+/// If a filter is invalid, the data can not be read or the terminal fails.
+pub fn show_live(
+  args: &LiveCliArgs,
+  config: &Config,
+  display: &DateTimeDisplay,
+  theme: &Theme,
+  clock: &Arc<dyn Clock>,
+) -> Result<(), AppDataOperationError<FilterParseError>> {
+  let interval = args
+    .interval
+    .to_std()
+    .unwrap_or(MIN_INTERVAL)
+    .max(MIN_INTERVAL);
+  let mut stdout = io::stdout();
+  let _screen = AlternateScreen::enter()?;
+  let mut expiry_watch = ExpiryWatch::default();
+
+  for tick in 0u64.. {
+    // Terminals which do not know their size report a width of 0.
+    let width = match terminal::size()? {
+      (0, _) => format_utils::terminal_width(),
+      (width, _) => width as usize,
+    };
+    let options =
+      super::list_options_from_args(&args.list, display, theme, clock.as_ref(), Some(width))
+        .map_err(AppDataOperationError::OperationErrorOnEntity)?;
+    let entities =
+      super::load_entities(config, clock).map_err(AppDataOperationError::IoOrJsonError)?;
+    let newly_expired = expiry_watch.tick(entities.stop_watches(), entities.clock());
+
+    let frame = render_frame(&entities, &options, display, theme, &expiry_watch, tick);
+    draw(&mut stdout, &frame, !newly_expired.is_empty())?;
+    if wait_for_quit(interval)? {
+      break;
+    }
+  }
+
+  Ok(())
+}
+
+/// Returns a banner for every flashing stop watch followed by the table. Banners are shown on
+/// every other redraw.
+fn render_frame(
+  entities: &TimeEntitiesController,
+  options: &ListOptions,
+  display: &DateTimeDisplay,
+  theme: &Theme,
+  expiry_watch: &ExpiryWatch,
+  tick: u64,
+) -> String {
+  let mut frame = String::new();
+  for title in expiry_watch.flashing() {
+    if tick.is_multiple_of(2) {
+      let banner = format!(">>> Stop watch \"{title}\" expired <<<");
+      frame.push_str(&theme.state(EntityState::Expired).paint(&banner));
+    }
+    frame.push('\n');
+  }
+  frame.push_str(&entities.to_listed_text_table(display, options));
+  frame.push_str(QUIT_HINT);
+  frame
+}
+
+/// Replaces the screen with the frame. Rings the bell of the terminal if param ring is true.
+fn draw(stdout: &mut io::Stdout, frame: &str, ring: bool) -> io::Result<()> {
+  queue!(
+    stdout,
+    cursor::MoveTo(0, 0),
+    terminal::Clear(ClearType::All)
+  )?;
+  // Raw mode does not return to the start of the line on a line break.
+  stdout.write_all(frame.replace('\n', "\r\n").as_bytes())?;
+  if ring {
+    stdout.write_all(BELL.as_bytes())?;
+  }
+  stdout.flush()
+}
+
+/// Waits up to param timeout for the user to quit. Returns early without quitting if the
+/// terminal is resized, so the table is fitted to the new width at once.
+fn wait_for_quit(timeout: StdDuration) -> io::Result<bool> {
+  let deadline = Instant::now() + timeout;
+  loop {
+    let remaining = deadline.saturating_duration_since(Instant::now());
+    if remaining.is_zero() || !event::poll(remaining)? {
+      return Ok(false);
+    }
+    match event::read()? {
+      Event::Key(key) if key.kind == KeyEventKind::Press => {
+        let is_ctrl_c =
+          key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+        if is_ctrl_c || matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) {
+          return Ok(true);
+        }
+      }
+      Event::Resize(_, _) => return Ok(false),
+      _ => (),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono::{Duration, TimeZone, Utc};
+
+  use super::*;
+  use crate::{chrono_utility::clock::ManualClock, time_entities::time_stamp::TimeStamp};
+
+  #[test]
+  fn should_flash_only_stop_watches_expiring_while_watching() {
+    let started = Utc.ymd(2022, 10, 20).and_hms(9, 0, 0);
+    let stop_watches = [
+      StopWatch::new(
+        TimeStamp::with_started("Tea", started),
+        Duration::minutes(4),
+      ),
+      StopWatch::new(
+        TimeStamp::with_started("Old", started),
+        Duration::minutes(1),
+      ),
+    ];
+    let clock = ManualClock::new(started + Duration::minutes(2));
+    let mut expiry_watch = ExpiryWatch::default();
+
+    assert!(expiry_watch.tick(&stop_watches, &clock).is_empty());
+    clock.advance(Duration::minutes(3));
+    assert_eq!(vec!["Tea"], expiry_watch.tick(&stop_watches, &clock));
+    assert!(expiry_watch.tick(&stop_watches, &clock).is_empty());
+    assert_eq!(vec!["Tea"], expiry_watch.flashing().collect::<Vec<&str>>());
+
+    for _ in 0..FLASH_TICKS {
+      expiry_watch.tick(&stop_watches, &clock);
+    }
+    assert_eq!(0, expiry_watch.flashing().count());
+  }
+}
@@ -0,0 +1,146 @@
+//! Pomodoro on a task followed in the foreground. Every second the data is read again, which
+//! moves the pomodoro from work to break and back, and the time left is shown. Pressing Ctrl-C
+//! only stops following, the pomodoro goes on with the next invocation.
+use std::{
+  io::{self, IsTerminal, Write},
+  sync::Arc,
+  thread,
+  time::Duration as StdDuration,
+};
+
+use chrono::Duration;
+
+use crate::{
+  args_parser::PomodoroCliArgs,
+  chrono_utility::{clock::Clock, date_time_display::DateTimeDisplay, parsing, DateDifference},
+  config::Config,
+  time_entities::{
+    pomodoro::{PomodoroSettings, PomodoroState, PomodoroStatus},
+    time_entities_controller::EntityOperationError,
+  },
+};
+
+use super::app_command_errors::AppDataOperationError;
+
+const FOLLOW_INTERVAL: StdDuration = StdDuration::from_secs(1);
+
+/// Starts the pomodoro on the task if it has none running and follows it unless the user
+/// detaches. Stops it instead if the user asks so. Returns the text to show at the end.
+/// # Errors
+/// If the data can not be read or saved, a stop watch of another kind has the title or there
+/// is no pomodoro to stop.
+pub fn run_pomodoro(
+  args: &PomodoroCliArgs,
+  config: &Config,
+  display: &DateTimeDisplay,
+  clock: &Arc<dyn Clock>,
+) -> Result<String, AppDataOperationError<EntityOperationError>> {
+  let title = args.title.trim();
+
+  if args.stop {
+    let (entities, stopped) =
+      super::change_entities(config, clock, |entities| entities.stop_pomodoro(title))
+        .map_err(AppDataOperationError::IoOrJsonError)?;
+    stopped.map_err(AppDataOperationError::OperationErrorOnEntity)?;
+    return Ok(stopped_message(title, entities.pomodoro_status(title)));
+  }
+
+  let settings = settings_from(args, config);
+  super::update_entities(config, clock, |entities| {
+    entities.start_pomodoro(title, settings)
+  })?;
+  if args.detach {
+    return Ok(format!("Pomodoro on \"{title}\" is running"));
+  }
+
+  follow(title, config, display, clock).map_err(AppDataOperationError::IoOrJsonError)
+}
+
+/// Returns the lengths chosen via flags or else via the settings or else the defaults.
+fn settings_from(args: &PomodoroCliArgs, config: &Config) -> PomodoroSettings {
+  let default = PomodoroSettings::default();
+  let length = |flag: Option<Duration>, key: &str, default: Duration| {
+    flag
+      .or_else(|| config.parsed(key, parsing::parse_duration))
+      .unwrap_or(default)
+  };
+  PomodoroSettings::new(
+    length(args.work, "pomodoro.work", default.work()),
+    length(
+      args.short_break,
+      "pomodoro.short_break",
+      default.short_break(),
+    ),
+    length(args.long_break, "pomodoro.long_break", default.long_break()),
+    args
+      .long_break_every
+      .or_else(|| {
+        config
+          .integer("pomodoro.long_break_every")
+          .and_then(|every| every.try_into().ok())
+      })
+      .unwrap_or(default.long_break_every()),
+  )
+}
+
+/// Shows a line whenever the pomodoro moves on and the time left of the current phase until it
+/// is over. On a terminal the time left is updated in place.
+fn follow(
+  title: &str,
+  config: &Config,
+  display: &DateTimeDisplay,
+  clock: &Arc<dyn Clock>,
+) -> Result<String, super::AppDataIoOrJsonError> {
+  let in_place = io::stdout().is_terminal();
+  let mut stdout = io::stdout();
+  let mut last_state = None;
+
+  loop {
+    let entities = super::load_entities(config, clock)?;
+    let status = match entities.pomodoro_status(title) {
+      Some(status) if status.state != PomodoroState::Over => status,
+      status => return Ok(stopped_message(title, status)),
+    };
+    let remaining = display.format_duration(&DateDifference::from(status.remaining));
+
+    if last_state != Some((status.state, status.completed)) {
+      if in_place && last_state.is_some() {
+        writeln!(stdout)?;
+      }
+      writeln!(stdout, "{}", phase_message(title, &status))?;
+      last_state = Some((status.state, status.completed));
+    }
+    if in_place {
+      write!(stdout, "\r{remaining} left ")?;
+      stdout.flush()?;
+    }
+    thread::sleep(FOLLOW_INTERVAL);
+  }
+}
+
+fn phase_message(title: &str, status: &PomodoroStatus) -> String {
+  match status.state {
+    PomodoroState::Work => format!("Work cycle {} on \"{title}\"", status.completed + 1),
+    PomodoroState::Break(length) => format!(
+      "{} after {} of \"{title}\"",
+      length.name(),
+      cycles(status.completed)
+    ),
+    PomodoroState::Over => format!("Pomodoro on \"{title}\" is over"),
+  }
+}
+
+fn stopped_message(title: &str, status: Option<PomodoroStatus>) -> String {
+  let completed = status.map_or(0, |status| status.completed);
+  format!(
+    "Pomodoro on \"{title}\" stopped after {}",
+    cycles(completed)
+  )
+}
+
+fn cycles(count: usize) -> String {
+  match count {
+    1 => "1 work cycle".to_string(),
+    count => format!("{count} work cycles"),
+  }
+}
@@ -0,0 +1,154 @@
+//! Lists, creates, copies and deletes profiles and moves entries between them. Data of another
+//! profile is read and saved via its daemon if it runs, like the data of the selected one.
+use std::{io, sync::Arc};
+
+use crate::{
+  app_data_access,
+  args_parser::{ProfileAction, ProfileCliArgs},
+  chrono_utility::clock::Clock,
+  config::Config,
+  data_access::paths,
+  profile::{Profile, ProfileError},
+  time_entities::time_entities_controller::TimeEntitiesController,
+};
+
+use super::app_command_errors::{AppDataIoOrJsonError, AppDataOperationError};
+
+/// Runs the profile action of the user. Returns the text to show.
+/// # Errors
+/// If data can not be read or written or the profiles do not fit the action, like a profile to
+/// create which exists already.
+pub fn manage_profile(
+  args: &ProfileCliArgs,
+  config: &Config,
+  clock: &Arc<dyn Clock>,
+) -> Result<String, AppDataOperationError<ProfileError>> {
+  match &args.action {
+    ProfileAction::List => list(),
+    ProfileAction::Create { name } => {
+      ensure_exists(name, false)?;
+      app_data_access::save_profile_data(name, &TimeEntitiesController::empty().to_json()?)?;
+      Ok(format!("Created profile \"{name}\""))
+    }
+    ProfileAction::Copy { from, to } => {
+      if from == to {
+        return Err(AppDataOperationError::OperationErrorOnEntity(
+          ProfileError::SameProfile,
+        ));
+      }
+      ensure_exists(from, true)?;
+      ensure_exists(to, false)?;
+      let data = app_data_access::read_profile_data(from)?;
+      app_data_access::save_profile_data(to, &data)?;
+      // The user config file of the default profile applies to every profile anyway.
+      if !from.is_default() {
+        let from_config = paths::get_profile_config_path(from)?;
+        if let Some(content) = app_data_access::read_config_file(&from_config)? {
+          app_data_access::save_config_file(&paths::get_profile_config_path(to)?, &content)?;
+        }
+      }
+      Ok(format!("Copied profile \"{from}\" to \"{to}\""))
+    }
+    ProfileAction::Delete { name, force } => {
+      if name.is_default() {
+        return Err(AppDataOperationError::OperationErrorOnEntity(
+          ProfileError::DeleteDefault,
+        ));
+      }
+      ensure_exists(name, true)?;
+      #[cfg(unix)]
+      if crate::daemon::send_to(name, &crate::daemon::DaemonRequest::Ping)?.is_some() {
+        return Err(AppDataOperationError::OperationErrorOnEntity(
+          ProfileError::DaemonRunning(name.to_string()),
+        ));
+      }
+      let entities = entities_of(name)?;
+      let count = entities.time_stamps().len() + entities.stop_watches().len();
+      if count > 0 && !force {
+        return Err(AppDataOperationError::OperationErrorOnEntity(
+          ProfileError::NotEmpty(name.to_string(), count),
+        ));
+      }
+      app_data_access::delete_profile_files(name)?;
+      Ok(format!("Deleted profile \"{name}\""))
+    }
+    ProfileAction::Move { title, to } => {
+      let from = paths::selected_profile()?;
+      if from == *to {
+        return Err(AppDataOperationError::OperationErrorOnEntity(
+          ProfileError::SameProfile,
+        ));
+      }
+      ensure_exists(to, true)?;
+      let title = title.trim();
+      // Both profiles stay locked until the entries are moved.
+      let (_, moved) = super::change_entities(config, clock, |entities| {
+        let locked_target = app_data_access::lock_profile_data(to)?;
+        let mut target = TimeEntitiesController::from_json(locked_target.data())?;
+        let moved = entities.move_to(title, &mut target).map_err(|error| {
+          AppDataOperationError::OperationErrorOnEntity(ProfileError::Move(error))
+        })?;
+        // The target is saved first, so a failure leaves the entries in both profiles, not in
+        // none.
+        locked_target.save(&target.to_json()?)?;
+        Ok::<_, AppDataOperationError<ProfileError>>(moved)
+      })
+      .map_err(AppDataOperationError::IoOrJsonError)?;
+      let moved = moved?;
+      let entries = if moved == 1 { "entry" } else { "entries" };
+      Ok(format!(
+        "Moved {moved} {entries} titled \"{title}\" from profile \"{from}\" to \"{to}\""
+      ))
+    }
+  }
+}
+
+/// Lists every profile with its number of time stamps and stop watches and marks the selected
+/// one.
+fn list() -> Result<String, AppDataOperationError<ProfileError>> {
+  let selected = paths::selected_profile()?;
+  let mut lines = Vec::new();
+  for profile in app_data_access::list_profiles()? {
+    let marker = if profile == selected { "*" } else { " " };
+    let line = match entities_of(&profile) {
+      Ok(entities) => format!(
+        "{marker} {profile} ({} time stamps, {} stop watches)",
+        entities.time_stamps().len(),
+        entities.stop_watches().len()
+      ),
+      // The default profile has no data file before the first entry is saved.
+      Err(AppDataOperationError::IoOrJsonError(AppDataIoOrJsonError::IoError(error)))
+        if error.kind() == io::ErrorKind::NotFound =>
+      {
+        format!("{marker} {profile} (no data yet)")
+      }
+      Err(error) => return Err(error),
+    };
+    lines.push(line);
+  }
+  Ok(lines.join("\n"))
+}
+
+fn entities_of(
+  profile: &Profile,
+) -> Result<TimeEntitiesController, AppDataOperationError<ProfileError>> {
+  let data = app_data_access::read_profile_data(profile)?;
+  Ok(TimeEntitiesController::from_json(&data)?)
+}
+
+/// # Errors
+/// If the profile does not exist though param expected is true or the other way around.
+fn ensure_exists(
+  profile: &Profile,
+  expected: bool,
+) -> Result<(), AppDataOperationError<ProfileError>> {
+  match (app_data_access::profile_exists(profile)?, expected) {
+    (true, false) => Err(AppDataOperationError::OperationErrorOnEntity(
+      ProfileError::AlreadyExists(profile.to_string()),
+    )),
+    (false, true) => Err(AppDataOperationError::OperationErrorOnEntity(
+      ProfileError::NotFound(profile.to_string()),
+    )),
+    _ => Ok(()),
+  }
+}
@@ -0,0 +1,492 @@
+//! HTTP server on localhost with a JSON API for time stamps and stop watches:
+//! ```text
+//! GET    /time-stamps                  lists all time stamps
+//! POST   /time-stamps                  creates one from {"title": "…", "project": "…", "tags": […], "note": "…"}
+//! GET    /time-stamps/<title>          shows one
+//! POST   /time-stamps/<title>/pause    pauses one, likewise resume and finish
+//! DELETE /time-stamps/<title>          deletes one
+//! ```
+//! The same routes exist under /stop-watches, which are created with "count_down_seconds".
+//! Errors are answered as {"error": "…"}. Requests are handled one after another and every
+//! change is saved at once.
+//!
+//! Web pages can send requests to localhost too. So the host has to be localhost or 127.0.0.1,
+//! which a page of another domain can not claim, and every change has to be sent with
+//! "Content-Type: application/json", which a page can not send without asking first.
+use std::{io, sync::Arc};
+
+use chrono::Duration;
+use percent_encoding::percent_decode_str;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::{
+  args_parser::ServeCliArgs,
+  chrono_utility::{clock::Clock, parsing::MAX_DURATION_SECS},
+  config::Config,
+  time_entities::{
+    listing::{JsonEntity, ListOptions},
+    stop_watch::StopWatch,
+    time_entities_controller::{EntityKind, EntityOperationError, TimeEntitiesController},
+    time_stamp::TimeStamp,
+    TimeEntity,
+  },
+};
+
+use super::app_command_errors::AppDataIoOrJsonError;
+
+const LOCALHOST: &str = "127.0.0.1";
+const ALLOWED_HOSTS: [&str; 2] = [LOCALHOST, "localhost"];
+const JSON_CONTENT_TYPE: &str = "application/json";
+const DEFAULT_PORT: u16 = 7878;
+/// Time without requests after which stop watches are checked for expiry.
+const EXPIRY_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Status code and JSON body of an answer. No body means 204 No Content.
+#[derive(Debug, PartialEq)]
+pub struct ApiResponse {
+  status: u16,
+  body: Option<Value>,
+  /// Whether the entities changed and need to be saved.
+  changed: bool,
+}
+
+/// Body to create a time stamp or stop watch.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CreateBody {
+  title: String,
+  project: Option<String>,
+  #[serde(default)]
+  tags: Vec<String>,
+  note: Option<String>,
+  /// Required for stop watches and not allowed for time stamps.
+  count_down_seconds: Option<i64>,
+}
+
+/// Serves the API on localhost at the port of the user or the settings until the process is
+/// stopped. Stop watches are checked for expiry also between requests.
+/// # Errors
+/// If the port can not be bound or the data can not be read or saved.
+pub fn serve(
+  args: &ServeCliArgs,
+  config: &Config,
+  clock: &Arc<dyn Clock>,
+) -> Result<(), AppDataIoOrJsonError> {
+  let port = args
+    .port
+    .or_else(|| {
+      config
+        .integer("serve.port")
+        .and_then(|port| port.try_into().ok())
+    })
+    .unwrap_or(DEFAULT_PORT);
+  let address = format!("{LOCALHOST}:{port}");
+  let server = Server::http(&address).map_err(io::Error::other)?;
+  println!("Serving on http://{address}");
+
+  loop {
+    let outcome = match server.recv_timeout(EXPIRY_CHECK_INTERVAL)? {
+      Some(request) => answer(request, port, args.token.as_deref(), config, clock),
+      // Loading notes expired stop watches and runs their hooks.
+      None => super::load_entities(config, clock).map(|_| ()),
+    };
+    if let Err(error) = outcome {
+      eprintln!("Request failed: {error}");
+    }
+  }
+}
+
+fn answer(
+  mut request: Request,
+  port: u16,
+  token: Option<&str>,
+  config: &Config,
+  clock: &Arc<dyn Clock>,
+) -> Result<(), AppDataIoOrJsonError> {
+  let is_get = *request.method() == Method::Get;
+  let response = if !is_allowed_host(request.headers(), port) {
+    error(403, "Host has to be localhost or 127.0.0.1")
+  } else if !is_authorized(request.headers(), token) {
+    error(401, "Missing or wrong bearer token")
+  } else if !is_get && !is_json(request.headers()) {
+    error(
+      415,
+      "Changes have to be sent as Content-Type: application/json",
+    )
+  } else {
+    let mut body = String::new();
+    request.as_reader().read_to_string(&mut body)?;
+    if is_get {
+      let mut entities = super::load_entities(config, clock)?;
+      route(&mut entities, request.method(), request.url(), &body)
+    } else {
+      // Changes are made under the lock of the data, unchanged entities are not saved.
+      let (_, response) = super::change_entities(config, clock, |entities| {
+        let response = route(entities, request.method(), request.url(), &body);
+        if response.changed {
+          Ok(response)
+        } else {
+          Err(response)
+        }
+      })?;
+      response.unwrap_or_else(|response| response)
+    }
+  };
+
+  let http_response = match response.body {
+    Some(body) => Response::from_string(body.to_string())
+      .with_header(Header::from_bytes("Content-Type", JSON_CONTENT_TYPE).expect("Valid header"))
+      .with_status_code(response.status),
+    None => Response::from_string(String::new()).with_status_code(response.status),
+  };
+  request.respond(http_response)?;
+  Ok(())
+}
+
+/// Returns whether the host header names localhost or 127.0.0.1 with param port or no port.
+/// Other names could point at localhost via the DNS of a web page.
+fn is_allowed_host(headers: &[Header], port: u16) -> bool {
+  let Some(host) = header_value(headers, "Host") else {
+    return false;
+  };
+  let (name, host_port) = match host.rsplit_once(':') {
+    Some((name, host_port)) => (name, Some(host_port)),
+    None => (host, None),
+  };
+  ALLOWED_HOSTS
+    .iter()
+    .any(|allowed| allowed.eq_ignore_ascii_case(name))
+    && host_port.is_none_or(|host_port| host_port == port.to_string())
+}
+
+/// Without a token every request is allowed. Otherwise it has to be sent as
+/// "Authorization: Bearer <token>".
+fn is_authorized(headers: &[Header], token: Option<&str>) -> bool {
+  let Some(token) = token else {
+    return true;
+  };
+  headers.iter().any(|header| {
+    header.field.equiv("Authorization")
+      && header
+        .value
+        .as_str()
+        .strip_prefix("Bearer ")
+        .is_some_and(|sent| is_same_in_constant_time(sent.as_bytes(), token.as_bytes()))
+  })
+}
+
+/// Compares every byte, so the time taken does not tell how much of a guessed token is right.
+fn is_same_in_constant_time(left: &[u8], right: &[u8]) -> bool {
+  left.len() == right.len()
+    && left
+      .iter()
+      .zip(right)
+      .fold(0, |difference, (left, right)| difference | (left ^ right))
+      == 0
+}
+
+/// Returns whether the body is declared as JSON, also with parameters like a charset.
+fn is_json(headers: &[Header]) -> bool {
+  header_value(headers, "Content-Type").is_some_and(|content_type| {
+    content_type
+      .split(';')
+      .next()
+      .is_some_and(|media_type| media_type.trim().eq_ignore_ascii_case(JSON_CONTENT_TYPE))
+  })
+}
+
+fn header_value<'a>(headers: &'a [Header], field: &'static str) -> Option<&'a str> {
+  headers
+    .iter()
+    .find(|header| header.field.equiv(field))
+    .map(|header| header.value.as_str().trim())
+}
+
+/// Answers the request with param method to param url with the given body.
+pub fn route(
+  entities: &mut TimeEntitiesController,
+  method: &Method,
+  url: &str,
+  body: &str,
+) -> ApiResponse {
+  let path = url.split('?').next().unwrap_or_default();
+  let segments: Vec<String> = path
+    .split('/')
+    .filter(|segment| !segment.is_empty())
+    .map(|segment| percent_decode_str(segment).decode_utf8_lossy().to_string())
+    .collect();
+  let kind = match segments.first().map(String::as_str) {
+    Some("time-stamps") => EntityKind::TimeStamp,
+    Some("stop-watches") => EntityKind::StopWatch,
+    _ => return error(404, "Unknown path"),
+  };
+
+  match (method, &segments[1..]) {
+    (Method::Get, []) => ok(200, list(entities, kind)),
+    (Method::Post, []) => create(entities, kind, body),
+    (Method::Get, [title]) => match show(entities, kind, title) {
+      Some(entity) => ok(200, entity),
+      None => operation_error(EntityOperationError::NotFound(kind, title.clone())),
+    },
+    (Method::Delete, [title]) => match entities.delete(kind, title) {
+      Ok(()) => ApiResponse {
+        status: 204,
+        body: None,
+        changed: true,
+      },
+      Err(error) => operation_error(error),
+    },
+    (Method::Post, [title, action]) => {
+      let outcome = match action.as_str() {
+        "pause" => entities.pause(kind, title),
+        "resume" => entities.resume(kind, title),
+        "finish" => entities.finish(kind, title),
+        _ => return error(404, "Unknown action, expected pause, resume or finish"),
+      };
+      match outcome {
+        Ok(_) => changed(200, show(entities, kind, title).unwrap_or_default()),
+        Err(error) => operation_error(error),
+      }
+    }
+    (_, [] | [_] | [_, _]) => error(405, "Method not allowed"),
+    _ => error(404, "Unknown path"),
+  }
+}
+
+fn list(entities: &TimeEntitiesController, kind: EntityKind) -> Value {
+  json!(entities.to_json_entities(kind, &ListOptions::new()))
+}
+
+fn show(entities: &TimeEntitiesController, kind: EntityKind, title: &str) -> Option<Value> {
+  let clock = entities.clock();
+  let entity = match kind {
+    EntityKind::TimeStamp => entities
+      .time_stamps()
+      .iter()
+      .find(|time_stamp| time_stamp.get_title() == title)
+      .map(|time_stamp| JsonEntity::of(time_stamp, kind, clock)),
+    EntityKind::StopWatch => entities
+      .stop_watches()
+      .iter()
+      .find(|stop_watch| stop_watch.get_title() == title)
+      .map(|stop_watch| JsonEntity::of(stop_watch, kind, clock)),
+  }?;
+  Some(json!(entity))
+}
+
+fn create(entities: &mut TimeEntitiesController, kind: EntityKind, body: &str) -> ApiResponse {
+  let body: CreateBody = match serde_json::from_str(body) {
+    Ok(body) => body,
+    Err(parse_error) => return error(400, &format!("Invalid body: {parse_error}")),
+  };
+  let title = body.title.trim();
+  if title.is_empty() {
+    return operation_error(EntityOperationError::BlankTitle);
+  }
+  let time_stamp = TimeStamp::with_started(title, entities.clock().now())
+    .with_project(body.project.as_deref())
+    .with_tags(&body.tags)
+    .with_note(body.note.as_deref());
+
+  let added = match (kind, body.count_down_seconds) {
+    (EntityKind::TimeStamp, None) => entities.add_time_stamp(time_stamp),
+    (EntityKind::StopWatch, Some(seconds)) if (1..=MAX_DURATION_SECS).contains(&seconds) => {
+      entities
+        .add_stop_watch(StopWatch::new(time_stamp, Duration::seconds(seconds)))
+        .map_err(|_| EntityOperationError::DuplicateTitle(title.to_string()))
+    }
+    (EntityKind::StopWatch, _) => {
+      return error(
+        400,
+        &format!("count_down_seconds must be between 1 and {MAX_DURATION_SECS}"),
+      )
+    }
+    (EntityKind::TimeStamp, Some(_)) => {
+      return error(400, "count_down_seconds is only allowed for stop watches")
+    }
+  };
+  match added {
+    Ok(()) => changed(201, show(entities, kind, title).unwrap_or_default()),
+    Err(error) => operation_error(error),
+  }
+}
+
+fn ok(status: u16, body: Value) -> ApiResponse {
+  ApiResponse {
+    status,
+    body: Some(body),
+    changed: false,
+  }
+}
+
+fn changed(status: u16, body: Value) -> ApiResponse {
+  ApiResponse {
+    status,
+    body: Some(body),
+    changed: true,
+  }
+}
+
+fn error(status: u16, message: &str) -> ApiResponse {
+  ok(status, json!({ "error": message }))
+}
+
+/// Not found is 404, a blank title 400 and a taken title, an illegal transition like pausing
+/// twice or a start refused by the running policy 409.
+fn operation_error(operation_error: EntityOperationError) -> ApiResponse {
+  let status = match operation_error {
+    EntityOperationError::NotFound(_, _) => 404,
+    EntityOperationError::BlankTitle => 400,
+    EntityOperationError::DuplicateTitle(_)
+    | EntityOperationError::AlreadyPaused
+    | EntityOperationError::NotPaused
+    | EntityOperationError::AlreadyFinished
+    | EntityOperationError::OtherRunning(_)
+    | EntityOperationError::NoPomodoro(_) => 409,
+  };
+  error(status, &operation_error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono::{TimeZone, Utc};
+
+  use super::*;
+  use crate::chrono_utility::clock::FixedClock;
+
+  fn entities() -> TimeEntitiesController {
+    let started = Utc.ymd(2022, 10, 20).and_hms(9, 0, 0);
+    TimeEntitiesController::new(
+      vec![TimeStamp::with_started("Code review", started)],
+      Vec::new(),
+    )
+    .with_clock(Arc::new(FixedClock::new(started + Duration::minutes(30))))
+  }
+
+  #[test]
+  fn should_answer_with_status_codes_of_outcome() {
+    let mut entities = entities();
+
+    let paused = route(
+      &mut entities,
+      &Method::Post,
+      "/time-stamps/Code%20review/pause",
+      "",
+    );
+    assert_eq!(200, paused.status);
+    assert!(paused.changed);
+    assert_eq!(
+      Some(json!("paused")),
+      paused.body.unwrap().get("state").cloned()
+    );
+
+    let again = route(
+      &mut entities,
+      &Method::Post,
+      "/time-stamps/Code%20review/pause",
+      "",
+    );
+    assert_eq!(409, again.status);
+    assert!(!again.changed);
+    assert_eq!(
+      404,
+      route(
+        &mut entities,
+        &Method::Get,
+        "/stop-watches/Code%20review",
+        ""
+      )
+      .status
+    );
+    assert_eq!(
+      405,
+      route(&mut entities, &Method::Put, "/time-stamps", "").status
+    );
+  }
+
+  #[test]
+  fn should_create_stop_watch_and_reject_duplicate() {
+    let mut entities = entities();
+    let body = r#"{"title": "Tea", "count_down_seconds": 240, "tags": ["break"]}"#;
+
+    let created = route(&mut entities, &Method::Post, "/stop-watches", body);
+    assert_eq!(201, created.status);
+    assert_eq!(
+      json!({
+        "kind": "stop_watch",
+        "title": "Tea",
+        "state": "running",
+        "started": "2022-10-20T09:30:00Z",
+        "ended": null,
+        "project": null,
+        "tags": ["break"],
+        "note": null,
+        "active_seconds": 0,
+        "paused_seconds": 0,
+        "remaining_seconds": 240
+      }),
+      created.body.unwrap()
+    );
+
+    assert_eq!(
+      409,
+      route(&mut entities, &Method::Post, "/stop-watches", body).status
+    );
+    assert_eq!(
+      400,
+      route(&mut entities, &Method::Post, "/time-stamps", body).status
+    );
+  }
+
+  fn headers(lines: &[(&str, &str)]) -> Vec<Header> {
+    lines
+      .iter()
+      .map(|(field, value)| Header::from_bytes(*field, *value).unwrap())
+      .collect()
+  }
+
+  #[test]
+  fn should_only_allow_localhost_json_and_right_token() {
+    assert!(is_allowed_host(
+      &headers(&[("Host", "127.0.0.1:7878")]),
+      7878
+    ));
+    assert!(is_allowed_host(&headers(&[("host", "LocalHost")]), 7878));
+    assert!(!is_allowed_host(
+      &headers(&[("Host", "localhost:8080")]),
+      7878
+    ));
+    assert!(!is_allowed_host(
+      &headers(&[("Host", "evil.example:7878")]),
+      7878
+    ));
+    assert!(!is_allowed_host(&[], 7878));
+
+    assert!(is_json(&headers(&[(
+      "Content-Type",
+      "application/json; charset=utf-8"
+    )])));
+    assert!(!is_json(&headers(&[("Content-Type", "text/plain")])));
+    assert!(!is_json(&[]));
+
+    let authorization = headers(&[("Authorization", "Bearer secret")]);
+    assert!(is_authorized(&authorization, Some("secret")));
+    assert!(!is_authorized(&authorization, Some("secreT")));
+    assert!(!is_authorized(&authorization, Some("secret2")));
+    assert!(is_authorized(&[], None));
+  }
+
+  #[test]
+  fn should_reject_count_down_out_of_range() {
+    let mut entities = entities();
+
+    for seconds in [0, i64::MAX] {
+      let body = format!(r#"{{"title": "Tea", "count_down_seconds": {seconds}}}"#);
+      let rejected = route(&mut entities, &Method::Post, "/stop-watches", &body);
+      assert_eq!(400, rejected.status);
+      assert!(!rejected.changed);
+    }
+  }
+}
@@ -0,0 +1,717 @@
+//! Full-screen interactive view to manage time stamps and stop watches with the keyboard.
+//! Every change is applied as a request of the daemon protocol to the saved data, so changes of
+//! other invocations in between are kept, and the view is read again afterwards.
+use std::{io, sync::Arc, time::Duration as StdDuration};
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::{
+  backend::CrosstermBackend,
+  layout::{Constraint, Layout},
+  style::{Color, Modifier, Style},
+  widgets::{Block, Borders, Paragraph, Row, Table, TableState, Wrap},
+  Frame, Terminal,
+};
+
+use crate::{
+  chrono_utility::{clock::Clock, date_time_display::DateTimeDisplay},
+  config::Config,
+  daemon::{DaemonRequest, DaemonResponse},
+  format_utils, theme,
+  theme::Theme,
+  time_entities::{
+    listing::{self, Column, EntityState, ListedEntity},
+    time_entities_controller::{EntityKind, EntityOperationError, TimeEntitiesController},
+    time_stamp::TimeStamp,
+    TimeEntity,
+  },
+};
+
+use super::{app_command_errors::AppDataIoOrJsonError, live::AlternateScreen};
+
+const REDRAW_INTERVAL: StdDuration = StdDuration::from_secs(1);
+const TABLE_COLUMNS: [Column; 4] = [
+  Column::Title,
+  Column::State,
+  Column::Active,
+  Column::Remaining,
+];
+const DETAIL_COLUMNS: [Column; 7] = [
+  Column::Project,
+  Column::Tags,
+  Column::Started,
+  Column::Ended,
+  Column::State,
+  Column::Active,
+  Column::Remaining,
+];
+const HELP: &str =
+  "j/k move  a start  p pause  r resume  f finish  d delete  e rename  n note  / filter  q quit";
+
+/// Saved time stamps and stop watches the view shows and changes.
+pub trait EntityStore {
+  /// Reads the saved entities. Returns them with the titles of the stop watches which were
+  /// noted as expired while reading.
+  fn load(&mut self) -> Result<(TimeEntitiesController, Vec<String>), AppDataIoOrJsonError>;
+
+  /// Applies the request to the saved entities as they are at this moment.
+  fn apply(&mut self, request: DaemonRequest) -> Result<DaemonResponse, AppDataIoOrJsonError>;
+}
+
+/// Store of the selected profile, which goes through the daemon if it runs.
+struct SavedEntities {
+  config: Config,
+  clock: Arc<dyn Clock>,
+}
+
+impl EntityStore for SavedEntities {
+  fn load(&mut self) -> Result<(TimeEntitiesController, Vec<String>), AppDataIoOrJsonError> {
+    super::load_entities_noting_expiries(&self.config, &self.clock)
+  }
+
+  fn apply(&mut self, request: DaemonRequest) -> Result<DaemonResponse, AppDataIoOrJsonError> {
+    super::apply_request(request, &self.config, &self.clock)
+  }
+}
+
+/// What keys do at the moment. All modes except normal read a line of text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+  Normal,
+  Filter,
+  Start,
+  Rename,
+  Note,
+  ConfirmDelete,
+}
+
+/// State of the interactive view without the terminal, so it can be driven by key events.
+pub struct TuiApp {
+  /// Entities as they were read last.
+  entities: TimeEntitiesController,
+  store: Box<dyn EntityStore>,
+  display: DateTimeDisplay,
+  /// Kind and title of the shown entities. Time stamps come before stop watches.
+  rows: Vec<(EntityKind, String)>,
+  selected: usize,
+  mode: Mode,
+  /// Text typed into the input line.
+  input: String,
+  /// Filter expression like state=running or else a part of the title to look for.
+  filter: String,
+  /// Outcome of the last action.
+  status: Option<String>,
+  quit: bool,
+}
+
+/// Shows the interactive view until the user quits with q, Esc or Ctrl-C.
+/// # Errors
+/// If the data can not be read or the terminal fails. Failed saves are shown in the view.
+pub fn run_tui(
+  config: &Config,
+  display: &DateTimeDisplay,
+  theme: &Theme,
+  clock: &Arc<dyn Clock>,
+) -> Result<(), AppDataIoOrJsonError> {
+  let store = SavedEntities {
+    config: config.clone(),
+    clock: Arc::clone(clock),
+  };
+  let mut app = TuiApp::new(Box::new(store), display.clone())?;
+
+  let _screen = AlternateScreen::enter()?;
+  let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+  terminal.clear()?;
+  while !app.should_quit() {
+    app.reload();
+    terminal.draw(|frame| app.render(frame, theme))?;
+    // Without a key the view is redrawn to count up the durations.
+    if event::poll(REDRAW_INTERVAL)? {
+      if let Event::Key(key) = event::read()? {
+        app.handle_key(key);
+      }
+    }
+  }
+
+  Ok(())
+}
+
+impl TuiApp {
+  /// # Errors
+  /// If the entities of the store can not be read.
+  pub fn new(
+    mut store: Box<dyn EntityStore>,
+    display: DateTimeDisplay,
+  ) -> Result<Self, AppDataIoOrJsonError> {
+    let (entities, _) = store.load()?;
+    let mut app = TuiApp {
+      entities,
+      store,
+      display,
+      rows: Vec::new(),
+      selected: 0,
+      mode: Mode::Normal,
+      input: String::new(),
+      filter: String::new(),
+      status: None,
+      quit: false,
+    };
+    app.refresh_rows();
+    Ok(app)
+  }
+
+  pub fn should_quit(&self) -> bool {
+    self.quit
+  }
+
+  pub fn entities(&self) -> &TimeEntitiesController {
+    &self.entities
+  }
+
+  /// Titles of the shown time stamps and stop watches in order.
+  pub fn shown_titles(&self) -> Vec<&str> {
+    self.rows.iter().map(|(_, title)| title.as_str()).collect()
+  }
+
+  /// Reads the entities again to show changes of other invocations. Stop watches which expired
+  /// since the last read are saved as expired and reported.
+  pub fn reload(&mut self) {
+    match self.store.load() {
+      Ok((entities, expired)) => {
+        self.entities = entities;
+        if !expired.is_empty() {
+          let titles: Vec<String> = expired.iter().map(|title| format!("\"{title}\"")).collect();
+          self.status = Some(format!("Stop watch {} expired", titles.join(", ")));
+        }
+      }
+      Err(error) => self.status = Some(format!("Reading failed: {error}")),
+    }
+    self.refresh_rows();
+  }
+
+  pub fn handle_key(&mut self, key: KeyEvent) {
+    if key.kind != KeyEventKind::Press {
+      return;
+    }
+    if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+      self.quit = true;
+      return;
+    }
+
+    match self.mode {
+      Mode::Normal => self.handle_normal_key(key.code),
+      Mode::ConfirmDelete => {
+        self.mode = Mode::Normal;
+        if key.code == KeyCode::Char('y') {
+          self.on_selected(|kind, title| {
+            let message = format!("Deleted \"{title}\"");
+            (DaemonRequest::Delete { kind, title }, message)
+          });
+        } else {
+          self.status = Some("Nothing deleted".to_string());
+        }
+      }
+      Mode::Filter => self.handle_filter_key(key.code),
+      Mode::Start | Mode::Rename | Mode::Note => self.handle_input_key(key.code),
+    }
+  }
+
+  fn handle_normal_key(&mut self, code: KeyCode) {
+    self.status = None;
+    let last = self.rows.len().saturating_sub(1);
+    match code {
+      KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+      KeyCode::Char('j') | KeyCode::Down => self.selected = (self.selected + 1).min(last),
+      KeyCode::Char('k') | KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+      KeyCode::Char('g') | KeyCode::Home => self.selected = 0,
+      KeyCode::Char('G') | KeyCode::End => self.selected = last,
+      KeyCode::Char('p') => self.on_selected(|kind, title| {
+        let message = format!("Paused \"{title}\"");
+        (DaemonRequest::Pause { kind, title }, message)
+      }),
+      KeyCode::Char('r') => self.on_selected(|kind, title| {
+        let message = format!("Resumed \"{title}\"");
+        (DaemonRequest::Resume { kind, title }, message)
+      }),
+      KeyCode::Char('f') => self.on_selected(|kind, title| {
+        let message = format!("Finished \"{title}\"");
+        (DaemonRequest::Finish { kind, title }, message)
+      }),
+      KeyCode::Char('d') if self.selected_row().is_some() => self.mode = Mode::ConfirmDelete,
+      KeyCode::Char('a') => self.begin_input(Mode::Start, String::new()),
+      KeyCode::Char('e') => {
+        if let Some((_, title)) = self.selected_row() {
+          let title = title.clone();
+          self.begin_input(Mode::Rename, title);
+        }
+      }
+      KeyCode::Char('n') => {
+        if let Some((kind, title)) = self.selected_row() {
+          let note = self
+            .entities
+            .time_stamp_of(*kind, title)
+            .and_then(|time_stamp| time_stamp.get_note())
+            .unwrap_or_default()
+            .to_string();
+          self.begin_input(Mode::Note, note);
+        }
+      }
+      KeyCode::Char('/') => self.begin_input(Mode::Filter, self.filter.clone()),
+      _ => (),
+    }
+  }
+
+  /// The filter is applied while typing. Enter keeps it and Esc removes it.
+  fn handle_filter_key(&mut self, code: KeyCode) {
+    match code {
+      KeyCode::Enter => self.mode = Mode::Normal,
+      KeyCode::Esc => {
+        self.mode = Mode::Normal;
+        self.input.clear();
+      }
+      KeyCode::Backspace => {
+        self.input.pop();
+      }
+      KeyCode::Char(typed) => self.input.push(typed),
+      _ => return,
+    }
+    self.filter = self.input.clone();
+    self.refresh_rows();
+  }
+
+  fn handle_input_key(&mut self, code: KeyCode) {
+    match code {
+      KeyCode::Esc => self.mode = Mode::Normal,
+      KeyCode::Backspace => {
+        self.input.pop();
+      }
+      KeyCode::Char(typed) => self.input.push(typed),
+      KeyCode::Enter => {
+        let mode = self.mode;
+        let input = std::mem::take(&mut self.input);
+        self.mode = Mode::Normal;
+        self.submit(mode, &input);
+      }
+      _ => (),
+    }
+  }
+
+  fn begin_input(&mut self, mode: Mode, input: String) {
+    self.mode = mode;
+    self.input = input;
+  }
+
+  fn submit(&mut self, mode: Mode, input: &str) {
+    match mode {
+      Mode::Start => {
+        let title = input.trim();
+        if title.is_empty() {
+          self.status = Some(EntityOperationError::BlankTitle.to_string());
+          return;
+        }
+        let request = DaemonRequest::Start {
+          title: title.to_string(),
+        };
+        self.apply(request, format!("Started \"{title}\""));
+        if let Some(index) = self
+          .rows
+          .iter()
+          .position(|row| *row == (EntityKind::TimeStamp, title.to_string()))
+        {
+          self.selected = index;
+        }
+      }
+      Mode::Rename => self.on_selected(|kind, title| {
+        let message = format!("Renamed \"{title}\" to \"{}\"", input.trim());
+        let new_title = input.to_string();
+        (
+          DaemonRequest::Rename {
+            kind,
+            title,
+            new_title,
+          },
+          message,
+        )
+      }),
+      Mode::Note => self.on_selected(|kind, title| {
+        let message = format!("Note of \"{title}\" saved");
+        let note = input.to_string();
+        (DaemonRequest::Note { kind, title, note }, message)
+      }),
+      Mode::Normal | Mode::Filter | Mode::ConfirmDelete => (),
+    }
+  }
+
+  fn selected_row(&self) -> Option<&(EntityKind, String)> {
+    self.rows.get(self.selected)
+  }
+
+  /// Applies the request built for the selected entity with the message shown if it succeeds.
+  fn on_selected(&mut self, request: impl FnOnce(EntityKind, String) -> (DaemonRequest, String)) {
+    let Some((kind, title)) = self.selected_row().cloned() else {
+      return;
+    };
+    let (request, message) = request(kind, title);
+    self.apply(request, message);
+  }
+
+  /// Applies the request to the saved entities and reads them again, so the view shows the
+  /// outcome together with the changes of other invocations.
+  fn apply(&mut self, request: DaemonRequest, message: String) {
+    let status = match self.store.apply(request) {
+      Ok(DaemonResponse::Ok) => message,
+      Ok(DaemonResponse::Error { message }) => message,
+      Ok(other) => format!("Unexpected answer: {other:?}"),
+      Err(error) => format!("Saving failed: {error}"),
+    };
+    self.status = None;
+    self.reload();
+    // A stop watch which expired meanwhile is reported too.
+    self.status = Some(match self.status.take() {
+      Some(expired) => format!("{status}. {expired}"),
+      None => status,
+    });
+  }
+
+  /// Collects the entities which match the filter. The selection stays on the same entity if it
+  /// is still shown and on the same row otherwise.
+  fn refresh_rows(&mut self) {
+    let clock = self.entities.clock();
+    let text = self.filter.trim();
+    let parsed = match text {
+      "" => None,
+      _ => Some(super::parse_filter(text, &self.display, clock).map_err(|_| text.to_lowercase())),
+    };
+    let is_shown = |entity: &dyn ListedEntity| match &parsed {
+      None => true,
+      Some(Ok(filter)) => filter.matches(entity, clock),
+      Some(Err(part)) => entity
+        .time_stamp()
+        .get_title()
+        .to_lowercase()
+        .contains(part.as_str()),
+    };
+
+    let time_stamps = self
+      .entities
+      .time_stamps()
+      .iter()
+      .filter(|time_stamp| is_shown(*time_stamp))
+      .map(|time_stamp| (EntityKind::TimeStamp, time_stamp.get_title().to_string()));
+    let stop_watches = self
+      .entities
+      .stop_watches()
+      .iter()
+      .filter(|stop_watch| is_shown(*stop_watch))
+      .map(|stop_watch| (EntityKind::StopWatch, stop_watch.get_title().to_string()));
+    let rows: Vec<(EntityKind, String)> = time_stamps.chain(stop_watches).collect();
+
+    let previous = self.rows.get(self.selected);
+    let selected = previous
+      .and_then(|previous| rows.iter().position(|row| row == previous))
+      .unwrap_or(self.selected);
+    self.selected = selected.min(rows.len().saturating_sub(1));
+    self.rows = rows;
+  }
+
+  fn listed(&self, kind: EntityKind, title: &str) -> Option<&dyn ListedEntity> {
+    match kind {
+      EntityKind::TimeStamp => self
+        .entities
+        .time_stamps()
+        .iter()
+        .find(|time_stamp| time_stamp.get_title() == title)
+        .map(|time_stamp| time_stamp as &dyn ListedEntity),
+      EntityKind::StopWatch => self
+        .entities
+        .stop_watches()
+        .iter()
+        .find(|stop_watch| stop_watch.get_title() == title)
+        .map(|stop_watch| stop_watch as &dyn ListedEntity),
+    }
+  }
+
+  /// Lines of the detail pane about the selected entity with its segments of active time and
+  /// its note.
+  pub fn detail_lines(&self) -> Vec<String> {
+    let Some((kind, title)) = self.selected_row() else {
+      return vec!["Nothing to show. Press a to start a time stamp.".to_string()];
+    };
+    let Some(entity) = self.listed(*kind, title) else {
+      return Vec::new();
+    };
+    let clock = self.entities.clock();
+    let time_stamp = entity.time_stamp();
+
+    let mut lines = vec![title.clone(), kind.name().to_string(), String::new()];
+    let columns: &[Column] = match kind {
+      EntityKind::TimeStamp => &DETAIL_COLUMNS[..DETAIL_COLUMNS.len() - 1],
+      EntityKind::StopWatch => &DETAIL_COLUMNS,
+    };
+    for (column, cell) in columns
+      .iter()
+      .zip(listing::cells(entity, columns, &self.display, clock))
+    {
+      lines.push(format!("{}: {cell}", column.header()));
+    }
+
+    lines.push(String::new());
+    lines.push("Segments:".to_string());
+    let is_running = EntityState::of(entity, clock) == EntityState::Running
+      || EntityState::of(entity, clock) == EntityState::Expired;
+    let intervals = time_stamp.get_active_intervals(clock);
+    for (index, (start, end)) in intervals.iter().enumerate() {
+      let end_text = if is_running && index + 1 == intervals.len() {
+        "now".to_string()
+      } else {
+        self.display.format(end)
+      };
+      lines.push(format!("  {} - {end_text}", self.display.format(start)));
+    }
+
+    lines.push(String::new());
+    lines.push(format!(
+      "Note: {}",
+      time_stamp.get_note().unwrap_or(TimeStamp::NOT_AVAILABLE)
+    ));
+    lines
+  }
+
+  /// Text of the bottom line: the input of the current mode or else the status or help.
+  fn bottom_line(&self) -> String {
+    match self.mode {
+      Mode::Normal => self.status.clone().unwrap_or_else(|| HELP.to_string()),
+      Mode::Filter => format!("/{}", self.input),
+      Mode::Start => format!("New time stamp: {}", self.input),
+      Mode::Rename => format!("Rename to: {}", self.input),
+      Mode::Note => format!("Note: {}", self.input),
+      Mode::ConfirmDelete => match self.selected_row() {
+        Some((kind, title)) => format!("Delete {} \"{title}\"? y/n", kind.name().to_lowercase()),
+        None => String::new(),
+      },
+    }
+  }
+
+  pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+    let [main, bottom] =
+      Layout::vertical([Constraint::Min(3), Constraint::Length(1)]).areas(frame.area());
+    let [list, detail] =
+      Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)]).areas(main);
+    let clock = self.entities.clock();
+
+    let header = Row::new(
+      std::iter::once("Kind")
+        .chain(TABLE_COLUMNS.iter().map(Column::header))
+        .collect::<Vec<&str>>(),
+    )
+    .style(tui_style(theme.header()));
+    let rows: Vec<Row> = self
+      .rows
+      .iter()
+      .filter_map(|(kind, title)| {
+        let entity = self.listed(*kind, title)?;
+        let kind_text = match kind {
+          EntityKind::TimeStamp => "stamp",
+          EntityKind::StopWatch => "watch",
+        };
+        let cells = std::iter::once(kind_text.to_string()).chain(listing::cells(
+          entity,
+          &TABLE_COLUMNS,
+          &self.display,
+          clock,
+        ));
+        let style = tui_style(theme.state(EntityState::of(entity, clock)));
+        Some(Row::new(cells.collect::<Vec<String>>()).style(style))
+      })
+      .collect();
+    let widths = [
+      Constraint::Length(5),
+      Constraint::Fill(1),
+      Constraint::Length(8),
+      Constraint::Length(12),
+      Constraint::Length(16),
+    ];
+    let title = match self.filter.trim() {
+      "" => " Time stamps and stop watches ".to_string(),
+      filter => format!(" Time stamps and stop watches matching {filter} "),
+    };
+    let table = Table::new(rows, widths)
+      .header(header)
+      .block(Block::default().borders(Borders::ALL).title(title))
+      .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+      .highlight_symbol("> ");
+    let mut table_state = TableState::default().with_selected(Some(self.selected));
+    frame.render_stateful_widget(table, list, &mut table_state);
+
+    let details = Paragraph::new(self.detail_lines().join("\n"))
+      .block(Block::default().borders(Borders::ALL).title(" Details "))
+      .wrap(Wrap { trim: false });
+    frame.render_widget(details, detail);
+
+    let bottom_text = self.bottom_line();
+    if !matches!(self.mode, Mode::Normal | Mode::ConfirmDelete) {
+      let cursor_x = bottom.x + format_utils::display_width(&bottom_text) as u16;
+      frame.set_cursor_position((cursor_x.min(bottom.right().saturating_sub(1)), bottom.y));
+    }
+    frame.render_widget(Paragraph::new(bottom_text), bottom);
+  }
+}
+
+/// Returns the terminal style of the ANSI codes of a style of the theme.
+fn tui_style(style: &theme::Style) -> Style {
+  let mut tui_style = Style::default();
+  let mut codes = style
+    .codes()
+    .split(';')
+    .filter_map(|code| code.parse::<u8>().ok());
+  while let Some(code) = codes.next() {
+    tui_style = match code {
+      1 => tui_style.add_modifier(Modifier::BOLD),
+      2 => tui_style.add_modifier(Modifier::DIM),
+      3 => tui_style.add_modifier(Modifier::ITALIC),
+      4 => tui_style.add_modifier(Modifier::UNDERLINED),
+      30..=37 => tui_style.fg(Color::Indexed(code - 30)),
+      90..=97 => tui_style.fg(Color::Indexed(code - 90 + 8)),
+      38 => match (codes.next(), codes.next()) {
+        (Some(5), Some(palette_index)) => tui_style.fg(Color::Indexed(palette_index)),
+        _ => tui_style,
+      },
+      _ => tui_style,
+    };
+  }
+  tui_style
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+  };
+
+  use chrono::{TimeZone, Utc};
+
+  use super::*;
+  use crate::{chrono_utility::clock::FixedClock, time_entities::stop_watch::StopWatch};
+
+  /// Keeps the data as JSON like the data file and counts how often it is saved.
+  struct MemoryStore {
+    data: Rc<RefCell<String>>,
+    saves: Rc<Cell<u32>>,
+    clock: Arc<dyn Clock>,
+  }
+
+  impl MemoryStore {
+    fn read(&self) -> TimeEntitiesController {
+      TimeEntitiesController::from_json(&self.data.borrow())
+        .unwrap()
+        .with_clock(Arc::clone(&self.clock))
+    }
+  }
+
+  impl EntityStore for MemoryStore {
+    fn load(&mut self) -> Result<(TimeEntitiesController, Vec<String>), AppDataIoOrJsonError> {
+      let entities = self.read();
+      Ok((entities, Vec::new()))
+    }
+
+    fn apply(&mut self, request: DaemonRequest) -> Result<DaemonResponse, AppDataIoOrJsonError> {
+      let mut entities = self.read();
+      let (response, changed) = crate::daemon::handle_request(&mut entities, request);
+      if changed {
+        *self.data.borrow_mut() = entities.to_json()?;
+        self.saves.set(self.saves.get() + 1);
+      }
+      Ok(response)
+    }
+  }
+
+  /// Returns the view with the saved data and the count of saves.
+  fn app_with_saves() -> (TuiApp, Rc<RefCell<String>>, Rc<Cell<u32>>) {
+    let started = Utc.ymd(2022, 10, 20).and_hms(9, 0, 0);
+    let entities = TimeEntitiesController::new(
+      vec![
+        TimeStamp::with_started("Coding", started),
+        TimeStamp::with_started("Meeting", started),
+      ],
+      vec![StopWatch::new(
+        TimeStamp::with_started("Tea", started),
+        chrono::Duration::minutes(4),
+      )],
+    );
+    let data = Rc::new(RefCell::new(entities.to_json().unwrap()));
+    let saves = Rc::new(Cell::new(0));
+    let store = MemoryStore {
+      data: Rc::clone(&data),
+      saves: Rc::clone(&saves),
+      clock: Arc::new(FixedClock::new(started + chrono::Duration::hours(1))),
+    };
+    let app = TuiApp::new(Box::new(store), DateTimeDisplay::default()).unwrap();
+    (app, data, saves)
+  }
+
+  fn press(app: &mut TuiApp, keys: &str) {
+    for key in keys.chars() {
+      let code = match key {
+        '\n' => KeyCode::Enter,
+        '\x08' => KeyCode::Backspace,
+        '\x1b' => KeyCode::Esc,
+        _ => KeyCode::Char(key),
+      };
+      app.handle_key(KeyEvent::new(code, KeyModifiers::NONE));
+    }
+  }
+
+  #[test]
+  fn should_pause_rename_and_start_through_keys_and_save() {
+    let (mut app, _, saves) = app_with_saves();
+
+    press(&mut app, "jp");
+    assert!(app.entities().time_stamps()[1].is_paused());
+    press(&mut app, "e\x08\x08\x08\x08\x08\x08\x08Call\n");
+    press(&mut app, "aReview\n");
+
+    assert_eq!(vec!["Coding", "Call", "Review", "Tea"], app.shown_titles());
+    assert_eq!(3, saves.get());
+    press(&mut app, "dy");
+    assert_eq!(vec!["Coding", "Call", "Tea"], app.shown_titles());
+  }
+
+  #[test]
+  fn should_filter_by_expression_or_part_of_title() {
+    let (mut app, _, _) = app_with_saves();
+
+    press(&mut app, "/tin\n");
+    assert_eq!(vec!["Meeting"], app.shown_titles());
+
+    press(&mut app, "pg/\x1b/state=running\n");
+    assert_eq!(vec!["Coding"], app.shown_titles());
+  }
+
+  #[test]
+  fn should_keep_changes_of_other_invocations() {
+    let (mut app, data, _) = app_with_saves();
+    let mut other = TimeEntitiesController::from_json(&data.borrow()).unwrap();
+    other.add_new_time_stamp("Lunch").unwrap();
+    *data.borrow_mut() = other.to_json().unwrap();
+
+    press(&mut app, "p");
+
+    assert_eq!(
+      vec!["Coding", "Meeting", "Lunch", "Tea"],
+      app.shown_titles()
+    );
+    assert!(app.entities().time_stamps()[0].is_paused());
+  }
+
+  #[test]
+  fn should_map_theme_style_to_terminal_style() {
+    let style = theme::Style::parse("bold green 208").unwrap();
+
+    assert_eq!(
+      Style::default()
+        .add_modifier(Modifier::BOLD)
+        .fg(Color::Indexed(208)),
+      tui_style(&style)
+    );
+  }
+}
@@ -1,23 +1,264 @@
 //! Loads and saves data specific for the this app.
 //! It uses the corresponding data folders of the user or the resource folder
 //! under dev_resources if in dev build
-use std::io;
+use std::{
+  fs::File,
+  io,
+  path::PathBuf,
+  thread,
+  time::{Duration, Instant},
+};
 
+#[cfg(unix)]
+use crate::daemon;
 use crate::{
   data_access::{self},
+  profile::Profile,
   time_entities::{time_entities_controller::TimeEntitiesController, time_stamp::TimeStamp},
 };
 use chrono::prelude::*;
 
+/// How long an invocation waits for another one to finish changing the data.
+const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Data of a profile which no other invocation changes until it is saved or dropped.
+pub struct LockedData(LockHolder);
+
+enum LockHolder {
+  /// The data file is read and written by this invocation under the lock of the file.
+  File {
+    data: String,
+    path: PathBuf,
+    _lock: File,
+  },
+  /// The daemon keeps other requests waiting.
+  #[cfg(unix)]
+  Daemon(daemon::Transaction),
+}
+
+impl LockedData {
+  /// All data as JSON like it is saved in the data file.
+  pub fn data(&self) -> &str {
+    match &self.0 {
+      LockHolder::File { data, .. } => data,
+      #[cfg(unix)]
+      LockHolder::Daemon(transaction) => transaction.data(),
+    }
+  }
+
+  /// Saves the changed data given as JSON and releases the lock.
+  pub fn save(self, data: &str) -> io::Result<()> {
+    match self.0 {
+      LockHolder::File { path, .. } => data_access::save_data(&path, data),
+      #[cfg(unix)]
+      LockHolder::Daemon(transaction) => transaction.save(data),
+    }
+  }
+}
+
+/// Returns all data of the selected profile as JSON. It is asked from the daemon if it runs
+/// and read from the data file otherwise.
 pub fn read_app_data() -> io::Result<String> {
-  let path = data_access::paths::get_data_path()?;
-  let data = data_access::get_all_data(&path)?;
-  Ok(data)
+  read_profile_data(&data_access::paths::selected_profile()?)
+}
+
+/// Returns all data of the profile as JSON from its daemon or its data file.
+pub fn read_profile_data(profile: &Profile) -> io::Result<String> {
+  #[cfg(unix)]
+  if let Some(data) = daemon::load(profile)? {
+    return Ok(data);
+  }
+  read_data_file(profile)
 }
 
+/// Reads all data of the profile from its data file. The default profile has no entries
+/// until its data file is written first.
+fn read_data_file(profile: &Profile) -> io::Result<String> {
+  let path = data_access::paths::get_profile_data_path(profile)?;
+  match data_access::get_all_data(&path) {
+    Err(error) if error.kind() == io::ErrorKind::NotFound && profile.is_default() => {
+      Ok(TimeEntitiesController::empty().to_json()?)
+    }
+    Err(error) if error.kind() == io::ErrorKind::NotFound => Err(io::Error::new(
+      io::ErrorKind::NotFound,
+      format!("Profile \"{profile}\" does not exist, create it via \"profile create {profile}\""),
+    )),
+    result => result,
+  }
+}
+
+/// Reads all data of the selected profile and keeps other invocations from changing it until
+/// the returned data is saved or dropped.
+pub fn lock_app_data() -> io::Result<LockedData> {
+  lock_profile_data(&data_access::paths::selected_profile()?)
+}
+
+/// Reads all data of the profile via its daemon or from its data file and keeps other
+/// invocations from changing it until the returned data is saved or dropped.
+/// # Errors
+/// If the data can not be read or another invocation holds the lock longer than
+/// [`LOCK_TIMEOUT`].
+pub fn lock_profile_data(profile: &Profile) -> io::Result<LockedData> {
+  // Checked first, so no lock file is left for a profile which does not exist.
+  if !profile_exists(profile)? {
+    read_data_file(profile)?;
+  }
+  wait_for_lock(profile, || {
+    #[cfg(unix)]
+    if let Some(transaction) = daemon::lock(profile)? {
+      return Ok(Some(LockedData(LockHolder::Daemon(transaction))));
+    }
+    let Some(lock) = try_lock_data_file(profile)? else {
+      return Ok(None);
+    };
+    Ok(Some(LockedData(LockHolder::File {
+      data: read_data_file(profile)?,
+      path: data_access::paths::get_profile_data_path(profile)?,
+      _lock: lock,
+    })))
+  })
+}
+
+/// Waits until no other invocation changes the data file of the profile and returns the lock,
+/// which is held until it is dropped.
+/// # Errors
+/// If the lock file can not be created or another invocation holds the lock longer than
+/// [`LOCK_TIMEOUT`].
+pub fn lock_data_file(profile: &Profile) -> io::Result<File> {
+  wait_for_lock(profile, || try_lock_data_file(profile))
+}
+
+fn try_lock_data_file(profile: &Profile) -> io::Result<Option<File>> {
+  let path = data_access::paths::get_profile_lock_path(profile)?;
+  if let Some(directory) = path.parent() {
+    std::fs::create_dir_all(directory)?;
+  }
+  data_access::try_lock(&path)
+}
+
+/// Tries to take the lock until it succeeds or [`LOCK_TIMEOUT`] passed.
+fn wait_for_lock<T>(
+  profile: &Profile,
+  mut try_lock: impl FnMut() -> io::Result<Option<T>>,
+) -> io::Result<T> {
+  let deadline = Instant::now() + LOCK_TIMEOUT;
+  loop {
+    if let Some(locked) = try_lock()? {
+      return Ok(locked);
+    }
+    if Instant::now() >= deadline {
+      return Err(io::Error::new(
+        io::ErrorKind::WouldBlock,
+        format!("The data of profile \"{profile}\" is changed by another invocation, try again"),
+      ));
+    }
+    thread::sleep(LOCK_RETRY_INTERVAL);
+  }
+}
+
+/// Reads all data from the data file, even if the daemon runs.
+pub fn read_app_data_file() -> io::Result<String> {
+  read_data_file(&data_access::paths::selected_profile()?)
+}
+
+/// Returns the content of the user config file or None if there is no config file.
+pub fn read_app_config() -> io::Result<Option<String>> {
+  let path = data_access::paths::get_config_path()?;
+  read_config_file(&path)
+}
+
+/// Returns the path and content of the project config file which applies to the working
+/// directory or None if there is none.
+pub fn read_project_config() -> io::Result<Option<(PathBuf, String)>> {
+  let Some(path) = data_access::paths::find_project_config_path(&std::env::current_dir()?) else {
+    return Ok(None);
+  };
+  Ok(read_config_file(&path)?.map(|content| (path, content)))
+}
+
+/// Returns the content of a config file or None if it does not exist.
+pub fn read_config_file(path: &PathBuf) -> io::Result<Option<String>> {
+  match data_access::get_all_data(path) {
+    Ok(config) => Ok(Some(config)),
+    Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+    Err(error) => Err(error),
+  }
+}
+
+/// Returns the default profile followed by every profile with a data file ordered by name.
+pub fn list_profiles() -> io::Result<Vec<Profile>> {
+  let directory = data_access::paths::get_profiles_data_dir()?;
+  let entries = match std::fs::read_dir(directory) {
+    Ok(entries) => entries,
+    Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(vec![Profile::default()]),
+    Err(error) => return Err(error),
+  };
+  let mut profiles = Vec::new();
+  for entry in entries {
+    let path = entry?.path();
+    if path.extension().is_none_or(|extension| extension != "json") {
+      continue;
+    }
+    let profile = path
+      .file_stem()
+      .and_then(|name| name.to_str())
+      .and_then(|name| Profile::parse(name).ok());
+    if let Some(profile) = profile.filter(|profile| !profile.is_default()) {
+      profiles.push(profile);
+    }
+  }
+  profiles.sort();
+  profiles.insert(0, Profile::default());
+  Ok(profiles)
+}
+
+/// Returns whether the profile has a data file. The default profile always exists.
+pub fn profile_exists(profile: &Profile) -> io::Result<bool> {
+  Ok(profile.is_default() || data_access::paths::get_profile_data_path(profile)?.is_file())
+}
+
+/// Deletes the data file and the config file of the profile.
+pub fn delete_profile_files(profile: &Profile) -> io::Result<()> {
+  std::fs::remove_file(data_access::paths::get_profile_data_path(profile)?)?;
+  match std::fs::remove_file(data_access::paths::get_profile_config_path(profile)?) {
+    Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error),
+    _ => Ok(()),
+  }
+}
+
+/// Writes a config file and creates its directory if needed.
+pub fn save_config_file(path: &PathBuf, content: &str) -> io::Result<()> {
+  if let Some(directory) = path.parent() {
+    std::fs::create_dir_all(directory)?;
+  }
+  data_access::save_data(path, content)
+}
+
+/// Saves all data of the selected profile given as JSON. It is handed to the daemon if it runs
+/// and written to the data file otherwise.
 pub fn save_app_data(data: &str) -> io::Result<()> {
+  save_profile_data(&data_access::paths::selected_profile()?, data)
+}
+
+/// Saves all data of the profile given as JSON via its daemon or into its data file, whose
+/// directory is created if needed.
+pub fn save_profile_data(profile: &Profile, data: &str) -> io::Result<()> {
+  #[cfg(unix)]
+  if daemon::save(profile, data)? {
+    return Ok(());
+  }
+  let path = data_access::paths::get_profile_data_path(profile)?;
+  if let Some(directory) = path.parent() {
+    std::fs::create_dir_all(directory)?;
+  }
+  data_access::save_data(&path, data)
+}
+
+/// Writes all data into the data file, even if the daemon runs.
+pub fn save_app_data_file(data: &str) -> io::Result<()> {
   let path = data_access::paths::get_data_path()?;
-  data_access::save_data(&path, &data)?;
+  data_access::save_data(&path, data)?;
   Ok(())
 }
 #[allow(dead_code)]
@@ -1,34 +1,403 @@
-use clap::{Args, Parser};
+use chrono::Duration;
+use clap::{Args, Parser, Subcommand};
+use clap_complete::Shell;
+
+use crate::{
+  billing::HourlyRate,
+  chrono_utility::{
+    date_time_display::{DateTimeFormat, DisplayZone},
+    parsing, DifferenceFormat,
+  },
+  profile::{Profile, PROFILE_ENV_VAR},
+  report::{
+    timesheet::{RoundingMode, TimesheetOutput},
+    ReportGrouping, ReportOutput,
+  },
+  status::{StatusOutput, StatusSnippet, StatusTemplate},
+  theme::ColorChoice,
+  time_entities::listing::{Column, ListOutput, SortKey},
+};
 #[derive(Args, Debug)]
 pub struct Title {
   /// Name of time stamp or stop watch
   pub name: String,
 }
 #[derive(Args, Debug)]
+pub struct SwitchCliArgs {
+  /// Name of the time stamp to start or resume
+  pub name: String,
+  /// Finishes the running time stamps instead of pausing them.
+  #[arg(long)]
+  pub finish: bool,
+}
+#[derive(Args, Debug)]
+pub struct TimeStampCliArgs {
+  /// Name of time stamp
+  pub name: String,
+  /// Moment at which the time stamp started instead of now. For example a time of today like
+  /// 09:15, an ISO 8601 date time like 2022-10-20T09:15, "yesterday 14:00" or "20m ago".
+  /// Without offset it is taken as local time.
+  #[arg(long, conflicts_with = "ago")]
+  pub at: Option<String>,
+  /// Duration since the time stamp started instead of now, like 20m, 1h30m or 01:30:00.
+  #[arg(long)]
+  pub ago: Option<String>,
+  /// Project the tracked time belongs to, like a customer.
+  #[arg(long)]
+  pub project: Option<String>,
+  /// Label to group the time stamp in reports. Can be given several times.
+  #[arg(long = "tag")]
+  pub tags: Vec<String>,
+  /// Hourly rate like "95.50 EUR" used instead of the rate of the project.
+  #[arg(long, value_parser = HourlyRate::parse)]
+  pub rate: Option<HourlyRate>,
+  /// Reports the tracked time, but bills nothing for it.
+  #[arg(long)]
+  pub non_billable: bool,
+}
+#[derive(Args, Debug)]
+pub struct RateCliArgs {
+  /// Project whose rate is shown or set. Lists all rates if not given.
+  pub project: Option<String>,
+  /// New hourly rate of the project like "95.50 EUR".
+  #[arg(value_parser = HourlyRate::parse)]
+  pub rate: Option<HourlyRate>,
+  /// Removes the rate of the project.
+  #[arg(long, requires = "project", conflicts_with = "rate")]
+  pub remove: bool,
+}
+#[derive(Args, Debug)]
 pub struct StopWatchCliArgs {
   /// Name of stop watch
   name: String,
-  /// Count down until watch is done.
-  left_time: String,
+  /// Count down until watch is done, like 25m, 1h30m, 01:30:00 or PT1H30M.
+  #[arg(value_parser = parsing::parse_duration)]
+  left_time: Duration,
+}
+#[derive(Args, Debug)]
+pub struct RangeCliArgs {
+  /// Only today. This is the default without any other range.
+  #[arg(long, conflicts_with_all = ["week", "from", "to"])]
+  pub today: bool,
+  /// The current week from Monday to Sunday.
+  #[arg(long, conflicts_with_all = ["from", "to"])]
+  pub week: bool,
+  /// First day like 2022-10-17, yesterday or "3d ago". Default is the last day.
+  #[arg(long)]
+  pub from: Option<String>,
+  /// Last day, included. Default is today.
+  #[arg(long)]
+  pub to: Option<String>,
+}
+#[derive(Args, Debug)]
+pub struct ReportCliArgs {
+  #[command(flatten)]
+  pub range: RangeCliArgs,
+  /// Sums up active time per entity, project, tag or day.
+  #[arg(long, default_value = "entity", value_parser = ReportGrouping::from_name)]
+  pub by: ReportGrouping,
+  /// Output as table, json or csv.
+  #[arg(long, default_value = "table", value_parser = ReportOutput::from_name)]
+  pub output: ReportOutput,
+  /// Shows a bar per group and a heatmap of active time per day instead of a table.
+  #[arg(long, conflicts_with = "output")]
+  pub chart: bool,
+}
+#[derive(Args, Debug)]
+pub struct TimesheetCliArgs {
+  #[command(flatten)]
+  pub range: RangeCliArgs,
+  /// Step to which billed time is rounded, like 6m or 15m. Default is no rounding.
+  #[arg(long, value_parser = parsing::parse_duration)]
+  pub round: Option<Duration>,
+  /// Rounds to the nearest step, always up or always down.
+  #[arg(long, default_value = "nearest", value_parser = RoundingMode::from_name)]
+  pub rounding: RoundingMode,
+  /// Least time billed for a day of a project with any tracked time, like 15m.
+  #[arg(long, value_parser = parsing::parse_duration)]
+  pub minimum: Option<Duration>,
+  /// Only includes time stamps of this project.
+  #[arg(long)]
+  pub project: Option<String>,
+  /// Output as table, csv or markdown.
+  #[arg(long, default_value = "table", value_parser = TimesheetOutput::from_name)]
+  pub output: TimesheetOutput,
+}
+#[derive(Args, Debug)]
+pub struct AllCliArgs {
+  /// Only lists entries matching all filters like state=running, tag=billable, project=Shop,
+  /// started>yesterday, started<2022-10-01, title=Meeting or title~^Meet as regular
+  /// expression. States are running, paused, finished and expired.
+  #[arg(long = "filter")]
+  pub filters: Vec<String>,
+  /// Column to sort by followed by :asc or :desc like active:desc. Default is ascending.
+  #[arg(long, value_parser = SortKey::parse)]
+  pub sort: Option<SortKey>,
+  /// Lists at most this many time stamps and stop watches each.
+  #[arg(long)]
+  pub limit: Option<usize>,
+  /// Comma separated columns in the order to show like title,project,active. Columns are
+  /// title, started, ended, is-paused, last-paused, elapsed, paused-total, active, remaining,
+  /// project, tags and state.
+  #[arg(long, value_delimiter = ',', value_parser = Column::from_name)]
+  pub columns: Vec<Column>,
+  /// Draws lines around cells.
+  #[arg(long)]
+  pub borders: bool,
+  /// Wraps text too wide for the terminal into several lines instead of cutting it.
+  #[arg(long)]
+  pub wrap: bool,
+}
+#[derive(Args, Debug)]
+pub struct ShowAllCliArgs {
+  #[command(flatten)]
+  pub list: AllCliArgs,
+  /// Output as table or json. JSON has the schema of the HTTP API of serve and ignores the
+  /// columns, borders and wrap.
+  #[arg(long, default_value = "table", value_parser = ListOutput::from_name)]
+  pub output: ListOutput,
+}
+#[derive(Args, Debug)]
+pub struct LiveCliArgs {
+  #[command(flatten)]
+  pub list: AllCliArgs,
+  /// Time between redraws like 1s or 5s.
+  #[arg(long, default_value = "1s", value_parser = parsing::parse_duration)]
+  pub interval: Duration,
+}
+#[derive(Args, Debug)]
+pub struct DaemonCliArgs {
+  /// Asks the running daemon to save and quit.
+  #[arg(long, conflicts_with = "status")]
+  pub stop: bool,
+  /// Tells whether a daemon is running.
+  #[arg(long)]
+  pub status: bool,
+}
+#[derive(Args, Debug)]
+pub struct ServeCliArgs {
+  /// Port on localhost to listen on. Default is serve.port of the config or 7878.
+  #[arg(long)]
+  pub port: Option<u16>,
+  /// Requires every request to send this token as "Authorization: Bearer <token>".
+  #[arg(long, env = "STAMP_MEMBER_API_TOKEN", hide_env_values = true)]
+  pub token: Option<String>,
+}
+#[derive(Args, Debug)]
+pub struct PomodoroCliArgs {
+  /// Name of the task. Its work is tracked by a stop watch with this name.
+  pub title: String,
+  /// Length of one work cycle like 25m or 50m. Default is pomodoro.work of the config or 25m.
+  #[arg(long, value_parser = parsing::parse_duration)]
+  pub work: Option<Duration>,
+  /// Length of a short break. Default is pomodoro.short_break of the config or 5m.
+  #[arg(long, value_parser = parsing::parse_duration)]
+  pub short_break: Option<Duration>,
+  /// Length of a long break. Default is pomodoro.long_break of the config or 15m.
+  #[arg(long, value_parser = parsing::parse_duration)]
+  pub long_break: Option<Duration>,
+  /// Number of work cycles after which the break is long. Default is
+  /// pomodoro.long_break_every of the config or 4.
+  #[arg(long)]
+  pub long_break_every: Option<u32>,
+  /// Finishes the pomodoro on the task and deletes its break.
+  #[arg(long, conflicts_with = "detach")]
+  pub stop: bool,
+  /// Starts the pomodoro without following it. Later invocations move it on.
+  #[arg(long)]
+  pub detach: bool,
+}
+#[derive(Args, Debug)]
+pub struct StatusCliArgs {
+  /// Template of the line like "{title} {elapsed:short}". Placeholders are title, kind,
+  /// project, count, elapsed and remaining. Durations take a format like {elapsed:compact}.
+  /// Default is status.template of the config or "{title} {elapsed:short}".
+  #[arg(long, env = "STAMP_MEMBER_STATUS_TEMPLATE", value_parser = StatusTemplate::parse)]
+  pub template: Option<StatusTemplate>,
+  /// Text shown if nothing is running. Default is status.idle of the config or nothing.
+  #[arg(long)]
+  pub idle: Option<String>,
+  /// Output: text or json for waybar and i3blocks.
+  #[arg(long, default_value = "text", value_parser = StatusOutput::from_name)]
+  pub output: StatusOutput,
+  /// Prints the configuration to show the status in bash, zsh, tmux, waybar or i3blocks.
+  #[arg(long, value_parser = StatusSnippet::from_name, conflicts_with_all = ["idle", "output"])]
+  pub snippet: Option<StatusSnippet>,
+}
+#[derive(Args, Debug)]
+pub struct CompletionsCliArgs {
+  /// Shell to complete in.
+  #[arg(value_enum)]
+  pub shell: Shell,
+}
+#[derive(Args, Debug)]
+pub struct CompleteTitlesCliArgs {
+  /// Command whose title is completed like stop or resume.
+  pub command: String,
+}
+#[derive(Args, Debug)]
+pub struct ConfigCliArgs {
+  #[command(subcommand)]
+  pub action: ConfigAction,
+}
+#[derive(Subcommand, Debug)]
+pub enum ConfigAction {
+  /// Prints the value of a key like tracking.policy which applies here.
+  Get { key: String },
+  /// Sets a key in the user config file.
+  Set {
+    key: String,
+    value: String,
+    /// Sets it in the project config file instead, which is created in the working directory
+    /// if no parent directory has one.
+    #[arg(long)]
+    project: bool,
+    /// Sets it in the config file of the selected profile instead.
+    #[arg(long, conflicts_with = "project")]
+    for_profile: bool,
+  },
+  /// Removes a key from the user config file.
+  Unset {
+    key: String,
+    /// Removes it from the project config file instead.
+    #[arg(long)]
+    project: bool,
+    /// Removes it from the config file of the selected profile instead.
+    #[arg(long, conflicts_with = "project")]
+    for_profile: bool,
+  },
+  /// Lists the keys which are set with their values and where they are set.
+  List {
+    /// Lists keys which are not set too.
+    #[arg(long)]
+    all: bool,
+  },
+}
+#[derive(Args, Debug)]
+pub struct ProfileCliArgs {
+  #[command(subcommand)]
+  pub action: ProfileAction,
+}
+#[derive(Subcommand, Debug)]
+pub enum ProfileAction {
+  /// Lists all profiles with their number of entries. The selected one is marked with *.
+  List,
+  /// Creates a profile without entries.
+  Create {
+    #[arg(value_parser = Profile::parse)]
+    name: Profile,
+  },
+  /// Copies the entries and the config file of a profile into a new profile.
+  Copy {
+    #[arg(value_parser = Profile::parse)]
+    from: Profile,
+    #[arg(value_parser = Profile::parse)]
+    to: Profile,
+  },
+  /// Deletes a profile with its entries and its config file. The default profile is kept.
+  Delete {
+    #[arg(value_parser = Profile::parse)]
+    name: Profile,
+    /// Deletes it even if it has entries.
+    #[arg(long)]
+    force: bool,
+  },
+  /// Moves the time stamp and the stop watch with the title from the selected profile into
+  /// another one.
+  Move {
+    title: String,
+    /// Profile to move them into.
+    #[arg(long, value_parser = Profile::parse)]
+    to: Profile,
+  },
+}
+#[derive(Args, Debug)]
+pub struct DisplayCliArgs {
+  /// IANA time zone like Europe/Berlin in which moments are shown.
+  /// Default is display.time_zone of the config or the local time zone of the system.
+  #[arg(long, global = true, env = "STAMP_MEMBER_TIME_ZONE", value_parser = DisplayZone::from_name)]
+  pub time_zone: Option<DisplayZone>,
+  /// strftime-like pattern like "%d.%m.%Y %H:%M" in which moments are shown or "iso" for
+  /// ISO 8601. Default is display.time_format of the config or "On %m.%d.%Y at %H:%M:%S".
+  #[arg(long, global = true, env = "STAMP_MEMBER_TIME_FORMAT", value_parser = DateTimeFormat::from_pattern)]
+  pub time_format: Option<DateTimeFormat>,
+  /// Format of durations: compact like 2h 5m, clock like 02:05:00, human like about 2 hours or
+  /// decimal like 2.08 hours. Default is display.duration_format of the config or clock.
+  #[arg(long, global = true, env = "STAMP_MEMBER_DURATION_FORMAT", value_parser = DifferenceFormat::from_name)]
+  pub duration_format: Option<DifferenceFormat>,
+  /// Colours output: auto only if output is a terminal and NO_COLOR is not set, always or
+  /// never. Default is display.color of the config or auto.
+  #[arg(long, global = true, env = "STAMP_MEMBER_COLOR", value_parser = ColorChoice::from_name)]
+  pub color: Option<ColorChoice>,
 }
 #[derive(Parser, Debug)]
 #[command(author = "NiceGraphic", version = "1.0.0", about="Tool to manage timestamps", long_about = None)]
+pub struct CliArgs {
+  #[command(subcommand)]
+  pub command: AppCommand,
+  /// Profile whose data and settings are used like work or personal. Default is
+  /// profile.name of the config or the default profile.
+  #[arg(long, global = true, env = PROFILE_ENV_VAR, value_parser = Profile::parse)]
+  pub profile: Option<Profile>,
+  #[command(flatten)]
+  pub display: DisplayCliArgs,
+}
+#[derive(Subcommand, Debug)]
 pub enum AppCommand {
-  /// Creates a time stamp which starts from current time.
-  Time(Title),
+  /// Creates a time stamp which starts from current time or from a given earlier moment.
+  Time(TimeStampCliArgs),
   /// Creates a stop watch which starts from current time and given count down.
   Watch(StopWatchCliArgs),
   /// Show time stamp or stop watch of a given title.
   Show(Title),
   /// Deletes time stamp or stop watch with the given title
   Delete(Title),
-  /// Stops time stamp and stop watch. Stops counting time.
+  /// Pauses the time stamp or else the stop watch with the given title. Stops counting time.
   Stop(Title),
-  /// Continues times stamp or stop watch if stopped before. Continues counting
-  /// again.
+  /// Continues the time stamp or else the stop watch with the given title if paused before.
+  /// Continues counting again.
   Resume(Title),
+  /// Pauses every running time stamp and starts or resumes the given one in one step.
+  Switch(SwitchCliArgs),
   /// Lists all created time stamps and stop watches.
-  All,
+  All(ShowAllCliArgs),
+  /// Prints one line about the running entry for shell prompts and status bars. Never writes
+  /// any data.
+  Status(StatusCliArgs),
+  /// Shows all time stamps and stop watches and redraws them every second until q is pressed.
+  Live(LiveCliArgs),
+  /// Opens a full-screen view to start, pause, resume, finish, delete, rename and annotate time
+  /// stamps and stop watches with the keyboard.
+  Tui,
+  /// Keeps all data in memory and serves other invocations over a Unix socket until stopped.
+  /// Other invocations use it while it runs and access the data file otherwise.
+  #[cfg(unix)]
+  Daemon(DaemonCliArgs),
+  /// Serves a JSON API over HTTP on localhost to list, create, pause, resume, finish and delete
+  /// time stamps and stop watches.
+  Serve(ServeCliArgs),
+  /// Works on a task in pomodoros: work cycles counted down by a stop watch with breaks in
+  /// between. Follows the pomodoro until Ctrl-C is pressed or it is stopped.
+  Pomodoro(PomodoroCliArgs),
+  /// Sums up active time per entity, project, tag or day over a range of days.
+  Report(ReportCliArgs),
+  /// Lists billable time per day and project with rounding over a range of days.
+  Timesheet(TimesheetCliArgs),
+  /// Shows, sets or removes the hourly rate of a project.
+  Rate(RateCliArgs),
+  /// Prints the completion script for bash, zsh, fish, elvish or powershell. Bash, zsh and
+  /// fish also complete titles, like running ones after stop. Load it in bash via
+  /// `source <(stamp_member completions bash)`.
+  Completions(CompletionsCliArgs),
+  /// Shows and changes the settings of the user and project config files. Settings of the
+  /// project config file win over the user ones, environment variables and flags over both.
+  Config(ConfigCliArgs),
+  /// Lists, creates, copies and deletes profiles, which are separate data sets with their own
+  /// settings, and moves entries between them.
+  Profile(ProfileCliArgs),
+  /// Prints the titles which fit the given command, one per line. Used by completion scripts.
+  #[command(hide = true)]
+  CompleteTitles(CompleteTitlesCliArgs),
   #[cfg(debug_assertions)]
   /// Clears and saves initial dev dummy date into data.json under dev_resources
   DevInit,
@@ -0,0 +1,266 @@
+//! Hourly rates and the money billed for tracked time. Amounts are calculated with exact
+//! decimals and are only rounded to cents when shown, so sums match the invoice.
+use std::{collections::BTreeMap, fmt::Display, str::FromStr};
+
+use chrono::Duration;
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+
+const SECS_PER_HOUR: i64 = 3600;
+/// Highest hourly rate, so amounts of any tracked time stay within the range of [`Decimal`].
+const MAX_PER_HOUR: i64 = 1_000_000_000;
+/// Decimal places of shown amounts like cents.
+const SHOWN_DECIMAL_PLACES: u32 = 2;
+const CURRENCY_CODE_LEN: usize = 3;
+
+/// Hourly rates of projects by the name of the project.
+pub type ProjectRates = BTreeMap<String, HourlyRate>;
+
+/// Money billed for one hour of tracked time like 95.50 EUR.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HourlyRate {
+  per_hour: Decimal,
+  /// ISO 4217 code like EUR or USD.
+  currency: String,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RateParseError {
+  InvalidAmount(String),
+  NegativeAmount(Decimal),
+  /// Above [`MAX_PER_HOUR`].
+  TooLargeAmount(Decimal),
+  InvalidCurrency(String),
+  MissingCurrency,
+}
+
+/// Sums of money per currency. Tracked time of different currencies can not be added up.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Amounts(BTreeMap<String, Decimal>);
+
+impl HourlyRate {
+  /// # Errors
+  /// If the amount is negative or above a billion or the currency is not a code of 3 letters
+  /// like EUR.
+  pub fn new(per_hour: Decimal, currency: &str) -> Result<Self, RateParseError> {
+    if per_hour.is_sign_negative() && !per_hour.is_zero() {
+      return Err(RateParseError::NegativeAmount(per_hour));
+    }
+    if per_hour > Decimal::from(MAX_PER_HOUR) {
+      return Err(RateParseError::TooLargeAmount(per_hour));
+    }
+    let currency = currency.trim();
+    let is_code = currency.len() == CURRENCY_CODE_LEN
+      && currency.chars().all(|letter| letter.is_ascii_alphabetic());
+    if !is_code {
+      return Err(RateParseError::InvalidCurrency(currency.to_string()));
+    }
+
+    Ok(HourlyRate {
+      per_hour,
+      currency: currency.to_ascii_uppercase(),
+    })
+  }
+
+  /// Parses an amount with its currency like "95.50 EUR" or "EUR 95.50".
+  /// # Errors
+  /// If the amount is not a number from 0 up to a billion or the currency is missing or invalid.
+  /// # Example
+  /// ```
+  /// use stamp_member::billing::HourlyRate;
+  ///
+  /// let rate = HourlyRate::parse("eur 95.5").unwrap();
+  ///
+  /// assert_eq!("95.50 EUR", rate.to_string());
+  /// ```
+  pub fn parse(input: &str) -> Result<Self, RateParseError> {
+    let parts: Vec<&str> = input.split_whitespace().collect();
+    let (amount, currency) = match parts.as_slice() {
+      [amount, currency] if starts_with_digit(amount) => (*amount, *currency),
+      [currency, amount] => (*amount, *currency),
+      [_] => return Err(RateParseError::MissingCurrency),
+      _ => return Err(RateParseError::InvalidAmount(input.trim().to_string())),
+    };
+    let per_hour =
+      Decimal::from_str(amount).map_err(|_| RateParseError::InvalidAmount(amount.to_string()))?;
+
+    HourlyRate::new(per_hour, currency)
+  }
+
+  pub fn per_hour(&self) -> Decimal {
+    self.per_hour
+  }
+
+  pub fn currency(&self) -> &str {
+    &self.currency
+  }
+
+  /// Returns the exact amount for the duration without rounding. Amounts beyond the range of
+  /// [`Decimal`], which only rates of a changed data file reach, are capped at its maximum.
+  pub fn amount_for(&self, duration: Duration) -> Decimal {
+    self
+      .per_hour
+      .checked_mul(Decimal::from(duration.num_seconds()))
+      .and_then(|amount| amount.checked_div(Decimal::from(SECS_PER_HOUR)))
+      .unwrap_or(Decimal::MAX)
+  }
+}
+
+fn starts_with_digit(text: &str) -> bool {
+  text.starts_with(|first: char| first.is_ascii_digit())
+}
+
+impl Amounts {
+  /// Adds the amount billed for the duration at the given rate.
+  pub fn add_for(&mut self, rate: &HourlyRate, duration: Duration) {
+    self.add(rate.currency(), rate.amount_for(duration));
+  }
+
+  pub fn add(&mut self, currency: &str, amount: Decimal) {
+    let sum = self.0.entry(currency.to_string()).or_default();
+    *sum = sum.saturating_add(amount);
+  }
+
+  pub fn merge(&mut self, other: &Amounts) {
+    for (currency, amount) in &other.0 {
+      self.add(currency, *amount);
+    }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.0.is_empty()
+  }
+
+  /// Returns the exact sum of the given currency.
+  pub fn get(&self, currency: &str) -> Option<Decimal> {
+    self.0.get(currency).copied()
+  }
+
+  /// Returns the amounts rounded to cents by currency, sorted by currency. Half a cent is
+  /// rounded away from zero.
+  pub fn rounded(&self) -> BTreeMap<String, Decimal> {
+    self
+      .0
+      .iter()
+      .map(|(currency, amount)| (currency.clone(), round_to_shown_places(*amount)))
+      .collect()
+  }
+}
+
+fn round_to_shown_places(amount: Decimal) -> Decimal {
+  let mut rounded =
+    amount.round_dp_with_strategy(SHOWN_DECIMAL_PLACES, RoundingStrategy::MidpointAwayFromZero);
+  rounded.rescale(SHOWN_DECIMAL_PLACES);
+  rounded
+}
+
+impl Display for HourlyRate {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(
+      f,
+      "{} {}",
+      round_to_shown_places(self.per_hour),
+      self.currency
+    )
+  }
+}
+
+/// Shows amounts like "12.50 EUR, 3.00 USD". Nothing is shown without any amount.
+impl Display for Amounts {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let texts: Vec<String> = self
+      .rounded()
+      .into_iter()
+      .map(|(currency, amount)| format!("{amount} {currency}"))
+      .collect();
+    write!(f, "{}", texts.join(", "))
+  }
+}
+
+impl Display for RateParseError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      RateParseError::InvalidAmount(amount) => {
+        write!(f, "\"{amount}\" is not an amount like 95.50")
+      }
+      RateParseError::NegativeAmount(amount) => {
+        write!(f, "Hourly rate {amount} must not be negative")
+      }
+      RateParseError::TooLargeAmount(amount) => {
+        write!(f, "Hourly rate {amount} must not be above {MAX_PER_HOUR}")
+      }
+      RateParseError::InvalidCurrency(currency) => write!(
+        f,
+        "\"{currency}\" is not a currency code of 3 letters like EUR or USD"
+      ),
+      RateParseError::MissingCurrency => {
+        write!(f, "Hourly rate needs a currency like \"95.50 EUR\"")
+      }
+    }
+  }
+}
+
+impl std::error::Error for RateParseError {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_parse_rate_with_currency_before_or_after() {
+    let expected = HourlyRate::new(Decimal::new(9550, 2), "EUR").unwrap();
+
+    assert_eq!(Ok(expected.clone()), HourlyRate::parse("95.50 EUR"));
+    assert_eq!(Ok(expected), HourlyRate::parse(" eur 95.50 "));
+    assert_eq!(
+      Err(RateParseError::MissingCurrency),
+      HourlyRate::parse("95.50")
+    );
+    assert_eq!(
+      Err(RateParseError::InvalidCurrency("EURO".to_string())),
+      HourlyRate::parse("95.50 EURO")
+    );
+    assert_eq!(
+      Err(RateParseError::NegativeAmount(Decimal::new(-10, 0))),
+      HourlyRate::parse("EUR -10")
+    );
+  }
+
+  #[test]
+  fn should_refuse_rates_above_a_billion_and_cap_amounts() {
+    let too_large = "79228162514264337593543950335";
+
+    assert_eq!(
+      Err(RateParseError::TooLargeAmount(
+        Decimal::from_str(too_large).unwrap()
+      )),
+      HourlyRate::parse(&format!("{too_large} EUR"))
+    );
+    let rate: HourlyRate = serde_json::from_str(&format!(
+      "{{\"per_hour\":\"{too_large}\",\"currency\":\"EUR\"}}"
+    ))
+    .unwrap();
+    assert_eq!(Decimal::MAX, rate.amount_for(Duration::hours(2)));
+  }
+
+  #[test]
+  fn should_sum_exact_amounts_before_rounding() {
+    let rate = HourlyRate::parse("100 EUR").unwrap();
+    let mut amounts = Amounts::default();
+
+    // 1 minute is 1.666... EUR. Rounding every minute on its own would give 3.34 EUR.
+    amounts.add_for(&rate, Duration::minutes(1));
+    amounts.add_for(&rate, Duration::minutes(1));
+
+    assert_eq!("3.33 EUR", amounts.to_string());
+  }
+
+  #[test]
+  fn should_keep_currencies_apart() {
+    let mut amounts = Amounts::default();
+    amounts.add_for(&HourlyRate::parse("60 USD").unwrap(), Duration::minutes(90));
+    amounts.add_for(&HourlyRate::parse("10.10 EUR").unwrap(), Duration::hours(2));
+
+    assert_eq!("20.20 EUR, 90.00 USD", amounts.to_string());
+  }
+}
@@ -1,21 +1,142 @@
-use std::ops::{Add, Sub};
+pub mod clock;
+pub mod date_time_display;
+pub mod parsing;
 
-use chrono::{DateTime, Duration, Utc};
+use std::{
+  cmp::Ordering,
+  fmt::Display,
+  ops::{Add, Neg, Sub},
+};
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, TimeZone};
 
 use crate::{format_utils, return_if_with};
-#[derive(Default, PartialEq, Debug)]
+
+const SECS_PER_MINUTE: u64 = 60;
+const SECS_PER_HOUR: u64 = 60 * SECS_PER_MINUTE;
+const SECS_PER_DAY: u64 = 24 * SECS_PER_HOUR;
+const MONTHS_PER_YEAR: i64 = 12;
+
+/// Signed length of time with a precision of seconds. Negative if the end lies before the start.
+/// Days have always 24 hours. Use [`CalendarDifference`] for years and months which depend
+/// on the calendar.
+#[derive(Default, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
 pub struct DateDifference {
+  total_secs: i64,
+}
+
+/// Text format for a [`DateDifference`].
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum DifferenceFormat {
+  /// Like 2h 5m
+  Compact,
+  /// Like 02:05:00. Hours are not limited to 24.
+  #[default]
+  Clock,
+  /// Like about 2 hours
+  Humanized,
+  /// Like 2.08 as hours with 2 decimal places.
+  DecimalHours,
+}
+
+/// Difference between 2 moments split into calendar units. Years and months are counted on the
+/// calendar from the earlier moment. For example from January 31 to March 1 is 1 month and
+/// 1 or 2 days depending on the leap year.
+#[derive(Default, PartialEq, Eq, Debug, Clone, Copy)]
+pub struct CalendarDifference {
+  pub is_negative: bool,
+  pub years: u64,
+  pub months: u64,
+  pub days: u64,
+  pub hours: u64,
+  pub minutes: u64,
+  pub seconds: u64,
+}
+
+/// Absolute value of a difference split into units up to days.
+#[derive(Default)]
+struct UnitBreakdown {
   seconds: u64,
   minutes: u64,
   hours: u64,
   days: u64,
-  years: u64,
 }
 
+#[derive(Debug, PartialEq, Eq)]
+pub struct UnknownDifferenceFormat(String);
+
 impl DateDifference {
-  pub fn new(total_secs: u64) -> Self {
-    let mut result: DateDifference = Default::default();
-    let mut left_total_secs = total_secs;
+  pub fn new(total_secs: i64) -> Self {
+    DateDifference { total_secs }
+  }
+
+  /// Returns the signed difference from param earlier to param later.
+  pub fn between<Tz: TimeZone>(earlier: &DateTime<Tz>, later: &DateTime<Tz>) -> Self {
+    DateDifference::from(later.clone() - earlier.clone())
+  }
+
+  pub fn total_seconds(&self) -> i64 {
+    self.total_secs
+  }
+
+  pub fn is_negative(&self) -> bool {
+    self.total_secs < 0
+  }
+
+  pub fn abs(&self) -> Self {
+    DateDifference::new(self.total_secs.abs())
+  }
+
+  /// Number of full days of the absolute value.
+  pub fn days(&self) -> u64 {
+    self.breakdown().days
+  }
+
+  /// Hours of the absolute value which are left after full days.
+  pub fn hours(&self) -> u64 {
+    self.breakdown().hours
+  }
+
+  /// Minutes of the absolute value which are left after full hours.
+  pub fn minutes(&self) -> u64 {
+    self.breakdown().minutes
+  }
+
+  /// Seconds of the absolute value which are left after full minutes.
+  pub fn seconds(&self) -> u64 {
+    self.breakdown().seconds
+  }
+
+  /// Returns this difference as text in the given format.
+  /// # Example
+  /// ```
+  /// use stamp_member::chrono_utility::{DateDifference, DifferenceFormat};
+  ///
+  /// let difference = DateDifference::new(2 * 3600 + 5 * 60);
+  ///
+  /// assert_eq!("2h 5m", difference.format(DifferenceFormat::Compact));
+  /// assert_eq!("02:05:00", difference.format(DifferenceFormat::Clock));
+  /// assert_eq!("about 2 hours", difference.format(DifferenceFormat::Humanized));
+  /// assert_eq!("2.08", difference.format(DifferenceFormat::DecimalHours));
+  /// ```
+  pub fn format(&self, format: DifferenceFormat) -> String {
+    let sign = if self.is_negative() { "-" } else { "" };
+    match format {
+      DifferenceFormat::Compact => format!("{sign}{}", self.compact_text()),
+      DifferenceFormat::Clock => format!("{sign}{}", self.clock_text()),
+      DifferenceFormat::Humanized if self.is_negative() => {
+        format!("minus {}", self.humanized_text())
+      }
+      DifferenceFormat::Humanized => self.humanized_text(),
+      DifferenceFormat::DecimalHours => {
+        format!("{:.2}", self.total_secs as f64 / SECS_PER_HOUR as f64)
+      }
+    }
+  }
+
+  fn breakdown(&self) -> UnitBreakdown {
+    let mut result: UnitBreakdown = Default::default();
+    let mut left_total_secs = self.total_secs.unsigned_abs();
 
     extract_advance_time_unit(&mut result.seconds, &mut left_total_secs, 60);
     return_if_with!(left_total_secs, 0, result);
@@ -24,11 +145,8 @@ impl DateDifference {
     return_if_with!(left_total_secs, 0, result);
 
     extract_advance_time_unit(&mut result.hours, &mut left_total_secs, 24);
-    return_if_with!(left_total_secs, 0, result);
-
-    extract_advance_time_unit(&mut result.days, &mut left_total_secs, 365);
 
-    result.years = left_total_secs;
+    result.days = left_total_secs;
 
     return result;
 
@@ -37,38 +155,216 @@ impl DateDifference {
       *t_secs /= unit;
     }
   }
+
+  fn compact_text(&self) -> String {
+    let units = self.breakdown();
+    let parts: Vec<String> = [
+      (units.days, "d"),
+      (units.hours, "h"),
+      (units.minutes, "m"),
+      (units.seconds, "s"),
+    ]
+    .iter()
+    .filter(|(amount, _)| *amount > 0)
+    .map(|(amount, unit)| format!("{amount}{unit}"))
+    .collect();
+
+    if parts.is_empty() {
+      "0s".to_string()
+    } else {
+      parts.join(" ")
+    }
+  }
+
+  fn clock_text(&self) -> String {
+    let units = self.breakdown();
+    let hours = format_utils::with_at_least_2_digits(units.days * 24 + units.hours);
+    let minutes = format_utils::with_at_least_2_digits(units.minutes);
+    let seconds = format_utils::with_at_least_2_digits(units.seconds);
+
+    format!("{hours}:{minutes}:{seconds}")
+  }
+
+  /// Rounds the absolute value to the most fitting unit. Months are taken as 30 days and years
+  /// as 365 days because there is no calendar anchor.
+  fn humanized_text(&self) -> String {
+    let secs = self.total_secs.unsigned_abs();
+    let rounded = |unit_secs: u64| (secs + unit_secs / 2) / unit_secs;
+
+    match secs {
+      0..=44 => "less than a minute".to_string(),
+      45..=89 => "about a minute".to_string(),
+      _ if secs < 45 * SECS_PER_MINUTE => format!("about {} minutes", rounded(SECS_PER_MINUTE)),
+      _ if secs < 90 * SECS_PER_MINUTE => "about an hour".to_string(),
+      _ if secs < 22 * SECS_PER_HOUR => format!("about {} hours", rounded(SECS_PER_HOUR)),
+      _ if secs < 36 * SECS_PER_HOUR => "about a day".to_string(),
+      _ if secs < 30 * SECS_PER_DAY => format!("about {} days", rounded(SECS_PER_DAY)),
+      _ if secs < 45 * SECS_PER_DAY => "about a month".to_string(),
+      _ if secs < 365 * SECS_PER_DAY => format!("about {} months", rounded(30 * SECS_PER_DAY)),
+      _ if secs < 545 * SECS_PER_DAY => "about a year".to_string(),
+      _ => format!("about {} years", rounded(365 * SECS_PER_DAY)),
+    }
+  }
+}
+
+impl DifferenceFormat {
+  pub const NAMES: [&'static str; 4] = ["compact", "clock", "human", "decimal"];
+
+  /// Returns the format for one of the names in [`DifferenceFormat::NAMES`].
+  /// # Errors
+  /// If the name is not known.
+  pub fn from_name(name: &str) -> Result<Self, UnknownDifferenceFormat> {
+    match name.trim().to_lowercase().as_str() {
+      "compact" => Ok(DifferenceFormat::Compact),
+      "clock" => Ok(DifferenceFormat::Clock),
+      "human" => Ok(DifferenceFormat::Humanized),
+      "decimal" => Ok(DifferenceFormat::DecimalHours),
+      _ => Err(UnknownDifferenceFormat(name.to_string())),
+    }
+  }
+}
+
+impl CalendarDifference {
+  /// Returns the difference from param earlier to param later in calendar units of the
+  /// time zone of the moments. It is negative if param later lies before param earlier.
+  pub fn between<Tz: TimeZone>(earlier: &DateTime<Tz>, later: &DateTime<Tz>) -> Self {
+    let (start, end, is_negative) = match earlier.cmp(later) {
+      Ordering::Greater => (later.naive_local(), earlier.naive_local(), true),
+      _ => (earlier.naive_local(), later.naive_local(), false),
+    };
+
+    let mut total_months = (end.year() as i64 - start.year() as i64) * MONTHS_PER_YEAR
+      + (end.month() as i64 - start.month() as i64);
+    let mut anchor = add_months(&start, total_months);
+    if anchor > end {
+      total_months -= 1;
+      anchor = add_months(&start, total_months);
+    }
+
+    let rest = DateDifference::from(end - anchor).breakdown();
+    CalendarDifference {
+      is_negative,
+      years: (total_months / MONTHS_PER_YEAR) as u64,
+      months: (total_months % MONTHS_PER_YEAR) as u64,
+      days: rest.days,
+      hours: rest.hours,
+      minutes: rest.minutes,
+      seconds: rest.seconds,
+    }
+  }
+}
+
+/// Adds months to a moment. The day is reduced to the last day of the target month if the
+/// target month is shorter, for example January 31 plus 1 month is the end of February.
+fn add_months(moment: &NaiveDateTime, months: i64) -> NaiveDateTime {
+  let zero_based_month = moment.year() as i64 * MONTHS_PER_YEAR + moment.month0() as i64 + months;
+  let year = zero_based_month.div_euclid(MONTHS_PER_YEAR) as i32;
+  let month = zero_based_month.rem_euclid(MONTHS_PER_YEAR) as u32 + 1;
+  let day = moment.day().min(days_in_month(year, month));
+
+  NaiveDate::from_ymd(year, month, day).and_time(moment.time())
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+  let (next_year, next_month) = if month == 12 {
+    (year + 1, 1)
+  } else {
+    (year, month + 1)
+  };
+  NaiveDate::from_ymd(next_year, next_month, 1).pred().day()
+}
+
+impl From<Duration> for DateDifference {
+  fn from(duration: Duration) -> Self {
+    DateDifference::new(duration.num_seconds())
+  }
+}
+
+impl From<DateDifference> for Duration {
+  /// Differences beyond the range of [`Duration`] are clamped to its bounds.
+  fn from(difference: DateDifference) -> Self {
+    let max_secs = Duration::max_value().num_seconds();
+    Duration::seconds(difference.total_secs.clamp(-max_secs, max_secs))
+  }
 }
 
-impl ToString for DateDifference {
-  fn to_string(&self) -> String {
-    let seconds = format_utils::with_at_least_2_digits(self.seconds);
-    let minutes = format_utils::with_at_least_2_digits(self.minutes);
-    let hours = format_utils::with_at_least_2_digits(self.hours);
+/// Sums beyond the range of seconds in an i64 are clamped to its bounds.
+impl Add for DateDifference {
+  type Output = DateDifference;
 
-    let days = format_utils::with_at_least_3_digits(self.days);
-    let years = format_utils::with_at_least_4_digits(self.years);
+  fn add(self, rhs: Self) -> Self::Output {
+    DateDifference::new(self.total_secs.saturating_add(rhs.total_secs))
+  }
+}
 
-    format!("{years}:{days} {hours}:{minutes}:{seconds}")
+/// Differences beyond the range of seconds in an i64 are clamped to its bounds.
+impl Sub for DateDifference {
+  type Output = DateDifference;
+
+  fn sub(self, rhs: Self) -> Self::Output {
+    DateDifference::new(self.total_secs.saturating_sub(rhs.total_secs))
   }
 }
-#[allow(dead_code)]
-/// TODO: remove dead code
-fn calc_date_moment_difference(later: &DateTime<Utc>, earlier: &DateTime<Utc>) -> DateDifference {
-  debug_assert!(
-    earlier <= later,
-    "1. param should be greater, later in time, than the 2. one."
-  );
 
-  let difference: Duration = later.sub(earlier.clone());
+impl Neg for DateDifference {
+  type Output = DateDifference;
+
+  fn neg(self) -> Self::Output {
+    DateDifference::new(self.total_secs.saturating_neg())
+  }
+}
 
-  debug_assert!(
-    difference.num_seconds() >= 0,
-    "Total seconds should be positive because 1. param should be greater than the 2."
-  );
+impl std::iter::Sum for DateDifference {
+  fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+    iter.fold(DateDifference::default(), Add::add)
+  }
+}
 
-  DateDifference::new(difference.num_seconds() as u64)
+impl Display for DateDifference {
+  /// Shows the difference in clock format like 02:05:00
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.format(DifferenceFormat::Clock))
+  }
 }
 
+impl Display for CalendarDifference {
+  /// Shows only units which are not zero like 1y 2mo 3d 4h 5m 6s
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let parts: Vec<String> = [
+      (self.years, "y"),
+      (self.months, "mo"),
+      (self.days, "d"),
+      (self.hours, "h"),
+      (self.minutes, "m"),
+      (self.seconds, "s"),
+    ]
+    .iter()
+    .filter(|(amount, _)| *amount > 0)
+    .map(|(amount, unit)| format!("{amount}{unit}"))
+    .collect();
+
+    let sign = if self.is_negative { "-" } else { "" };
+    if parts.is_empty() {
+      write!(f, "0s")
+    } else {
+      write!(f, "{sign}{}", parts.join(" "))
+    }
+  }
+}
+
+impl Display for UnknownDifferenceFormat {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(
+      f,
+      "\"{}\" is not a duration format, expected one of {}",
+      self.0,
+      DifferenceFormat::NAMES.join(", ")
+    )
+  }
+}
+
+impl std::error::Error for UnknownDifferenceFormat {}
+
 pub fn duration_with_hms(hours: i64, minutes: i64, secs: i64) -> chrono::Duration {
   chrono::Duration::hours(hours)
     .add(Duration::minutes(minutes))
@@ -77,87 +373,180 @@ pub fn duration_with_hms(hours: i64, minutes: i64, secs: i64) -> chrono::Duratio
 
 #[cfg(test)]
 mod tests {
-  use chrono::TimeZone;
+  use chrono::{TimeZone, Utc};
 
   use super::*;
 
+  fn assert_units(actual: DateDifference, days: u64, hours: u64, minutes: u64, seconds: u64) {
+    assert_eq!(
+      (days, hours, minutes, seconds),
+      (
+        actual.days(),
+        actual.hours(),
+        actual.minutes(),
+        actual.seconds()
+      )
+    );
+  }
+
   #[test]
   fn should_have_seconds_difference() {
     let greater = Utc.ymd(2000, 1, 1).and_hms(1, 0, 25);
     let smaller = Utc.ymd(2000, 1, 1).and_hms(1, 0, 12);
-    let default = Default::default();
-    let expected = DateDifference {
-      seconds: 13,
-      ..default
-    };
 
-    let actual = calc_date_moment_difference(&greater, &smaller);
+    let actual = DateDifference::between(&smaller, &greater);
 
-    assert_eq!(expected, actual);
+    assert_units(actual, 0, 0, 0, 13);
   }
 
   #[test]
   fn should_have_secs_min_difference() {
     let greater = Utc.ymd(2000, 1, 1).and_hms(1, 2, 35);
     let smaller = Utc.ymd(2000, 1, 1).and_hms(1, 0, 12);
-    let default = Default::default();
-    let expected = DateDifference {
-      seconds: 23,
-      minutes: 2,
-      ..default
-    };
 
-    let actual = calc_date_moment_difference(&greater, &smaller);
+    let actual = DateDifference::between(&smaller, &greater);
 
-    assert_eq!(expected, actual);
+    assert_units(actual, 0, 0, 2, 23);
   }
 
   #[test]
   fn should_have_secs_min_hour_difference() {
     let greater = Utc.ymd(2000, 1, 1).and_hms(8, 2, 35);
     let smaller = Utc.ymd(2000, 1, 1).and_hms(1, 23, 0);
-    let default = Default::default();
-    let expected = DateDifference {
-      seconds: 35,
-      minutes: 39,
-      hours: 6,
-      ..default
-    };
 
-    let actual = calc_date_moment_difference(&greater, &smaller);
+    let actual = DateDifference::between(&smaller, &greater);
 
-    assert_eq!(expected, actual);
+    assert_units(actual, 0, 6, 39, 35);
   }
 
   #[test]
   fn should_have_secs_min_hour_day_year_difference() {
     let greater = Utc.ymd(2000, 5, 28).and_hms(13, 7, 12);
     let smaller = Utc.ymd(1998, 3, 11).and_hms(2, 31, 5);
-    let expected = DateDifference {
-      seconds: 7,
-      minutes: 36,
-      hours: 10,
-      days: 79,
+    let expected = CalendarDifference {
+      is_negative: false,
       years: 2,
+      months: 2,
+      days: 17,
+      hours: 10,
+      minutes: 36,
+      seconds: 7,
     };
 
-    let actual = calc_date_moment_difference(&greater, &smaller);
+    let actual = CalendarDifference::between(&smaller, &greater);
 
     assert_eq!(expected, actual);
+    assert_units(DateDifference::between(&smaller, &greater), 809, 10, 36, 7);
+  }
+
+  #[test]
+  fn should_have_negative_difference_with_swapped_moments() {
+    let later = Utc.ymd(2000, 1, 1).and_hms(3, 0, 0);
+    let earlier = Utc.ymd(2000, 1, 1).and_hms(1, 0, 0);
+
+    let actual = DateDifference::between(&later, &earlier);
+
+    assert!(actual.is_negative());
+    assert_eq!(-2 * 3600, actual.total_seconds());
+    assert_eq!("-02:00:00", actual.to_string());
+    assert!(CalendarDifference::between(&later, &earlier).is_negative);
+  }
+
+  #[test]
+  fn should_count_calendar_months_over_month_end_and_leap_year() {
+    let start = Utc.ymd(2020, 1, 31).and_hms(0, 0, 0);
+
+    let to_leap_february_end =
+      CalendarDifference::between(&start, &Utc.ymd(2020, 2, 29).and_hms(0, 0, 0));
+    let to_march = CalendarDifference::between(&start, &Utc.ymd(2020, 3, 1).and_hms(0, 0, 0));
+
+    assert_eq!("1mo", to_leap_february_end.to_string());
+    assert_eq!("1mo 1d", to_march.to_string());
+  }
+
+  #[test]
+  fn should_calculate_and_compare() {
+    let two_hours = DateDifference::from(Duration::hours(2));
+    let five_minutes = DateDifference::new(5 * 60);
+
+    assert_eq!(
+      DateDifference::new(2 * 3600 + 5 * 60),
+      two_hours + five_minutes
+    );
+    assert_eq!(
+      DateDifference::new(-(2 * 3600 - 5 * 60)),
+      five_minutes - two_hours
+    );
+    assert!(five_minutes < two_hours);
+    assert!(-two_hours < five_minutes);
+    assert_eq!(
+      Duration::minutes(125),
+      Duration::from(two_hours + five_minutes)
+    );
+    assert_eq!(
+      DateDifference::new(3 * 5 * 60),
+      [five_minutes; 3].into_iter().sum()
+    );
+  }
+
+  #[test]
+  fn should_clamp_instead_of_overflowing() {
+    let max = DateDifference::new(i64::MAX);
+    let min = DateDifference::new(i64::MIN);
+
+    assert_eq!(max, max + DateDifference::new(1));
+    assert_eq!(min, min - DateDifference::new(1));
+    assert_eq!(max, -min);
+    assert_eq!(
+      Duration::max_value().num_seconds(),
+      Duration::from(max).num_seconds()
+    );
+    assert_eq!(
+      -Duration::max_value().num_seconds(),
+      Duration::from(min).num_seconds()
+    );
   }
 
   #[test]
   fn should_to_string_date_difference() {
-    let to_convert = DateDifference {
-      seconds: 7,
-      minutes: 36,
-      hours: 10,
-      days: 79,
-      years: 2,
-    };
+    let to_convert = DateDifference::new(((79 * 24 + 10) * 60 + 36) * 60 + 7);
+
+    assert_eq!("1906:36:07", to_convert.to_string());
+    assert_eq!(
+      "79d 10h 36m 7s",
+      to_convert.format(DifferenceFormat::Compact)
+    );
+    assert_eq!(
+      "about 3 months",
+      to_convert.format(DifferenceFormat::Humanized)
+    );
+    assert_eq!("1906.60", to_convert.format(DifferenceFormat::DecimalHours));
+  }
 
-    let text = to_convert.to_string();
-    let expected = "0002:079 10:36:07";
-    assert_eq!(expected, text);
+  #[test]
+  fn should_format_small_and_negative_differences() {
+    let zero = DateDifference::default();
+    let overrun = DateDifference::new(-90);
+
+    assert_eq!("0s", zero.format(DifferenceFormat::Compact));
+    assert_eq!(
+      "less than a minute",
+      zero.format(DifferenceFormat::Humanized)
+    );
+    assert_eq!("-1m 30s", overrun.format(DifferenceFormat::Compact));
+    assert_eq!(
+      "minus about 2 minutes",
+      overrun.format(DifferenceFormat::Humanized)
+    );
+    assert_eq!("-0.03", overrun.format(DifferenceFormat::DecimalHours));
+  }
+
+  #[test]
+  fn should_get_difference_format_by_name() {
+    assert_eq!(
+      Ok(DifferenceFormat::Humanized),
+      DifferenceFormat::from_name("Human")
+    );
+    assert!(DifferenceFormat::from_name("fancy").is_err());
   }
 }
@@ -0,0 +1,117 @@
+//! Source of the current moment in time. Every operation which depends on now, like pausing or
+//! calculating elapsed time, asks a clock instead of the system time directly. This allows
+//! tests and embedding tools to control time.
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Duration, Utc};
+
+use super::parsing::{self, TimeParseError};
+
+/// Environment variable which fixes the current moment for the whole cli run.
+/// Accepts every moment [`parsing::parse_moment`] accepts, like 2022-10-20T09:15:00Z.
+pub const NOW_ENV_VAR: &str = "STAMP_MEMBER_NOW";
+
+pub trait Clock: Send + Sync {
+  fn now(&self) -> DateTime<Utc>;
+}
+
+/// Returns the current moment of the system.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+/// Returns always the same moment.
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock {
+  now: DateTime<Utc>,
+}
+
+/// Returns a moment which only changes if it is set or advanced manually.
+/// Can be shared via [`Arc`] and advanced while others hold it.
+#[derive(Debug)]
+pub struct ManualClock {
+  now: Mutex<DateTime<Utc>>,
+}
+
+impl Clock for SystemClock {
+  fn now(&self) -> DateTime<Utc> {
+    Utc::now()
+  }
+}
+
+impl FixedClock {
+  pub fn new(now: DateTime<Utc>) -> Self {
+    FixedClock { now }
+  }
+}
+
+impl Clock for FixedClock {
+  fn now(&self) -> DateTime<Utc> {
+    self.now
+  }
+}
+
+impl ManualClock {
+  pub fn new(now: DateTime<Utc>) -> Self {
+    ManualClock {
+      now: Mutex::new(now),
+    }
+  }
+
+  pub fn set(&self, now: DateTime<Utc>) {
+    *self.now.lock().expect("Clock lock is poisoned") = now;
+  }
+
+  pub fn advance(&self, to_add: Duration) {
+    *self.now.lock().expect("Clock lock is poisoned") += to_add;
+  }
+}
+
+impl Clock for ManualClock {
+  fn now(&self) -> DateTime<Utc> {
+    *self.now.lock().expect("Clock lock is poisoned")
+  }
+}
+
+/// Returns a fixed clock if the environment variable [`NOW_ENV_VAR`] is set, otherwise the
+/// system clock.
+/// # Errors
+/// If the value of the environment variable is not a moment.
+pub fn clock_from_env() -> Result<Arc<dyn Clock>, TimeParseError> {
+  clock_from_env_value(std::env::var(NOW_ENV_VAR).ok().as_deref())
+}
+
+fn clock_from_env_value(value: Option<&str>) -> Result<Arc<dyn Clock>, TimeParseError> {
+  match value {
+    Some(moment) if !moment.trim().is_empty() => {
+      let now = parsing::parse_moment(moment, &chrono::Local::now())?;
+      Ok(Arc::new(FixedClock::new(now)))
+    }
+    _ => Ok(Arc::new(SystemClock)),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono::TimeZone;
+
+  use super::*;
+
+  #[test]
+  fn should_advance_manual_clock_shared_with_others() {
+    let start = Utc.ymd(2022, 10, 20).and_hms(9, 0, 0);
+    let manual = Arc::new(ManualClock::new(start));
+    let shared: Arc<dyn Clock> = manual.clone();
+
+    manual.advance(Duration::minutes(5));
+
+    assert_eq!(start + Duration::minutes(5), shared.now());
+  }
+
+  #[test]
+  fn should_fix_clock_from_env_value() {
+    let clock = clock_from_env_value(Some("2022-10-20T09:15:00Z")).unwrap();
+
+    assert_eq!(Utc.ymd(2022, 10, 20).and_hms(9, 15, 0), clock.now());
+    assert!(clock_from_env_value(Some("not a moment")).is_err());
+  }
+}
@@ -0,0 +1,259 @@
+//! Turns moments in time into text for the user. Moments are saved in UTC, but are shown in the
+//! local time zone of the system or in a chosen IANA time zone like Europe/Berlin.
+use std::fmt::Display;
+
+use chrono::{
+  format::{Item, StrftimeItems},
+  DateTime, Local, NaiveDate, SecondsFormat, TimeZone, Utc,
+};
+use chrono_tz::Tz;
+
+use super::{DateDifference, DifferenceFormat};
+
+/// Pattern used if the user did not choose one.
+/// Shows date as month, day, year and then time as hours, minutes, seconds.
+pub const DEFAULT_PATTERN: &str = "On %m.%d.%Y at %H:%M:%S";
+/// Name of the format which shows moments in ISO 8601 like 2022-10-20T09:15:00+02:00
+pub const ISO_8601_NAME: &str = "iso";
+/// Name of the time zone of the system
+pub const LOCAL_ZONE_NAME: &str = "local";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DisplayZone {
+  /// Time zone of the system.
+  #[default]
+  Local,
+  /// IANA time zone like Europe/Berlin or America/Toronto
+  Named(Tz),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DateTimeFormat {
+  Iso8601,
+  /// strftime-like pattern like %d.%m.%Y %H:%M
+  Pattern(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+/// Time zone and format in which moments are shown to the user and the format for durations.
+pub struct DateTimeDisplay {
+  zone: DisplayZone,
+  format: DateTimeFormat,
+  duration_format: DifferenceFormat,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum DisplaySettingError {
+  UnknownTimeZone(String),
+  InvalidPattern(String),
+}
+
+impl DisplayZone {
+  /// Returns local zone for "local" or the IANA time zone with the given name.
+  /// # Errors
+  /// If no IANA time zone has the given name.
+  pub fn from_name(name: &str) -> Result<Self, DisplaySettingError> {
+    let name = name.trim();
+    if name.eq_ignore_ascii_case(LOCAL_ZONE_NAME) {
+      return Ok(DisplayZone::Local);
+    }
+
+    name
+      .parse::<Tz>()
+      .map(DisplayZone::Named)
+      .map_err(|_| DisplaySettingError::UnknownTimeZone(name.to_string()))
+  }
+
+  /// Returns the calendar day on which the moment lies in this time zone.
+  pub fn date_of(&self, moment: &DateTime<Utc>) -> NaiveDate {
+    match self {
+      DisplayZone::Local => moment.with_timezone(&Local).naive_local().date(),
+      DisplayZone::Named(zone) => moment.with_timezone(zone).naive_local().date(),
+    }
+  }
+
+  /// Returns the first moment of the given day in this time zone. This is midnight unless the
+  /// clock skips midnight on that day, then it is the first existing hour after it.
+  pub fn start_of_day(&self, day: NaiveDate) -> DateTime<Utc> {
+    match self {
+      DisplayZone::Local => start_of_day_in(&Local, day),
+      DisplayZone::Named(zone) => start_of_day_in(zone, day),
+    }
+  }
+}
+
+fn start_of_day_in<Z: TimeZone>(zone: &Z, day: NaiveDate) -> DateTime<Utc> {
+  (0..24)
+    .find_map(|hour| {
+      zone
+        .from_local_datetime(&day.and_hms(hour, 0, 0))
+        .earliest()
+    })
+    .map(|moment| moment.with_timezone(&Utc))
+    .unwrap_or_else(|| Utc.from_utc_datetime(&day.and_hms(0, 0, 0)))
+}
+
+impl DateTimeFormat {
+  /// Returns ISO 8601 format for "iso" or the given strftime-like pattern.
+  /// # Errors
+  /// If the pattern contains unknown specifiers like %Q.
+  pub fn from_pattern(pattern: &str) -> Result<Self, DisplaySettingError> {
+    if pattern.trim().eq_ignore_ascii_case(ISO_8601_NAME) {
+      return Ok(DateTimeFormat::Iso8601);
+    }
+
+    let has_invalid_item = StrftimeItems::new(pattern).any(|item| item == Item::Error);
+    if has_invalid_item {
+      Err(DisplaySettingError::InvalidPattern(pattern.to_string()))
+    } else {
+      Ok(DateTimeFormat::Pattern(pattern.to_string()))
+    }
+  }
+}
+
+impl Default for DateTimeFormat {
+  fn default() -> Self {
+    DateTimeFormat::Pattern(DEFAULT_PATTERN.to_string())
+  }
+}
+
+impl DateTimeDisplay {
+  pub fn new(zone: DisplayZone, format: DateTimeFormat) -> Self {
+    DateTimeDisplay {
+      zone,
+      format,
+      duration_format: Default::default(),
+    }
+  }
+
+  /// Returns this display which shows durations in the given format.
+  pub fn with_duration_format(self, duration_format: DifferenceFormat) -> Self {
+    DateTimeDisplay {
+      duration_format,
+      ..self
+    }
+  }
+
+  /// Returns the difference as text in the duration format of this display.
+  pub fn format_duration(&self, difference: &DateDifference) -> String {
+    difference.format(self.duration_format)
+  }
+
+  pub fn zone(&self) -> DisplayZone {
+    self.zone
+  }
+
+  /// Returns the moment as text in the time zone and format of this display.
+  /// # Example
+  /// ```
+  /// use chrono::prelude::*;
+  /// use stamp_member::chrono_utility::date_time_display::*;
+  ///
+  /// let zone = DisplayZone::from_name("Europe/Berlin").unwrap();
+  /// let display = DateTimeDisplay::new(zone, Default::default());
+  ///
+  /// let text = display.format(&Utc.ymd(2022, 10, 20).and_hms(7, 15, 0));
+  ///
+  /// assert_eq!("On 10.20.2022 at 09:15:00", text);
+  /// ```
+  pub fn format(&self, moment: &DateTime<Utc>) -> String {
+    match self.zone {
+      DisplayZone::Local => self.format_in_zone(moment.with_timezone(&Local)),
+      DisplayZone::Named(zone) => self.format_in_zone(moment.with_timezone(&zone)),
+    }
+  }
+
+  fn format_in_zone<Z>(&self, moment: DateTime<Z>) -> String
+  where
+    Z: TimeZone,
+    Z::Offset: Display,
+  {
+    match &self.format {
+      DateTimeFormat::Iso8601 => moment.to_rfc3339_opts(SecondsFormat::Secs, false),
+      DateTimeFormat::Pattern(pattern) => moment.format(pattern).to_string(),
+    }
+  }
+}
+
+impl Display for DisplaySettingError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      DisplaySettingError::UnknownTimeZone(name) => write!(
+        f,
+        "\"{name}\" is not an IANA time zone like Europe/Berlin or \"{LOCAL_ZONE_NAME}\""
+      ),
+      DisplaySettingError::InvalidPattern(pattern) => write!(
+        f,
+        "\"{pattern}\" is not a valid strftime pattern like \"%d.%m.%Y %H:%M\" or \"{ISO_8601_NAME}\""
+      ),
+    }
+  }
+}
+
+impl std::error::Error for DisplaySettingError {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn moment() -> DateTime<Utc> {
+    Utc.ymd(2014, 7, 8).and_hms(22, 45, 21)
+  }
+
+  #[test]
+  fn should_convert_to_right_format_from_data() {
+    let display = DateTimeDisplay::new(DisplayZone::Named(Tz::UTC), Default::default());
+
+    let actual_text_format = display.format(&moment());
+
+    const EXPECTED: &str = "On 07.08.2014 at 22:45:21";
+    assert_eq!(EXPECTED, actual_text_format);
+  }
+
+  #[test]
+  fn should_show_same_moment_in_different_zones() {
+    let format = DateTimeFormat::from_pattern("%Y-%m-%d %H:%M").unwrap();
+    let in_berlin = DateTimeDisplay::new(DisplayZone::Named(Tz::Europe__Berlin), format.clone());
+    let in_toronto = DateTimeDisplay::new(DisplayZone::Named(Tz::America__Toronto), format);
+
+    assert_eq!("2014-07-09 00:45", in_berlin.format(&moment()));
+    assert_eq!("2014-07-08 18:45", in_toronto.format(&moment()));
+  }
+
+  #[test]
+  fn should_show_iso_8601_with_offset() {
+    let display = DateTimeDisplay::new(
+      DisplayZone::from_name("America/Toronto").unwrap(),
+      DateTimeFormat::from_pattern(ISO_8601_NAME).unwrap(),
+    );
+
+    assert_eq!("2014-07-08T18:45:21-04:00", display.format(&moment()));
+  }
+
+  #[test]
+  fn should_reject_unknown_zone_and_invalid_pattern() {
+    assert_eq!(
+      Err(DisplaySettingError::UnknownTimeZone(
+        "Mars/Olympus".to_string()
+      )),
+      DisplayZone::from_name("Mars/Olympus")
+    );
+    assert_eq!(
+      Err(DisplaySettingError::InvalidPattern("%Q".to_string())),
+      DateTimeFormat::from_pattern("%Q")
+    );
+    assert_eq!(Ok(DisplayZone::Local), DisplayZone::from_name("Local"));
+  }
+
+  #[test]
+  fn should_find_day_and_its_start_in_zone() {
+    let toronto = DisplayZone::Named(Tz::America__Toronto);
+    let day = NaiveDate::from_ymd(2014, 7, 8);
+
+    assert_eq!(day, toronto.date_of(&moment()));
+    assert_eq!(
+      Utc.ymd(2014, 7, 8).and_hms(4, 0, 0),
+      toronto.start_of_day(day)
+    );
+  }
+}
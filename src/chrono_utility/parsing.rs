@@ -0,0 +1,900 @@
+//! Parses durations and moments in time which are given as text by the user.
+//! Local wall clock times are interpreted in the time zone of the given current moment and
+//! are returned in UTC.
+//!
+//! Accepted durations:
+//! - Numbers with units like `90s`, `1h30m`, `1.5h` or `2 days 3 hours`
+//! - Clock form like `01:30:00` or `01:30` as hours and minutes
+//! - ISO 8601 like `PT1H30M` or `P2DT3H`
+//!
+//! Accepted moments:
+//! - Time of the current day like `09:15` or `09:15:30`
+//! - ISO 8601 date time like `2022-10-20T09:15`, `2022-10-20T09:15:00+02:00` or a date
+//! - `now`, `today`, `yesterday` or `tomorrow`, optionally followed by a time like `14:00`
+//! - A duration followed by `ago` like `20m ago` or `in` followed by a duration like `in 2h`
+use std::{fmt::Display, ops::Range};
+
+use chrono::{DateTime, Duration, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+
+/// Formats accepted for a full local date time without an offset.
+const DATE_TIME_FORMATS: [&str; 4] = [
+  "%Y-%m-%dT%H:%M:%S%.f",
+  "%Y-%m-%dT%H:%M",
+  "%Y-%m-%d %H:%M:%S%.f",
+  "%Y-%m-%d %H:%M",
+];
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const ISO_DURATION_START: char = 'P';
+const ISO_DURATION_TIME_START: char = 'T';
+/// Upper limit for the seconds of a duration so that chrono does not panic on overflow.
+pub(crate) const MAX_DURATION_SECS: i64 = i64::MAX / 1_000_000;
+
+const SECS_PER_MINUTE: i64 = 60;
+const SECS_PER_HOUR: i64 = 60 * SECS_PER_MINUTE;
+const SECS_PER_DAY: i64 = 24 * SECS_PER_HOUR;
+const SECS_PER_WEEK: i64 = 7 * SECS_PER_DAY;
+
+/// Error with the location of the part of the input which could not be parsed.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TimeParseError {
+  input: String,
+  span: Range<usize>,
+  kind: TimeParseErrorKind,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum TimeParseErrorKind {
+  /// Nothing to parse was given.
+  Empty,
+  /// Word after a number is not a known time unit.
+  UnknownUnit(String),
+  /// Number is not followed by a time unit.
+  MissingUnit,
+  /// Time unit without a number before it.
+  MissingNumber,
+  /// Number could not be read or is too large.
+  InvalidNumber,
+  /// Hours, minutes or seconds in a clock form like 14:00 are out of range.
+  InvalidClockTime,
+  /// Text starting with P is not a valid ISO 8601 duration.
+  InvalidIsoDuration,
+  /// Token which is not expected at this position.
+  UnexpectedToken,
+  /// Text is neither a known relative expression, a time nor a date time.
+  UnknownMoment,
+  /// Local time does not exist, for example within the gap of a daylight saving time change.
+  NonExistentLocalTime,
+  /// Parsed moment lies after the current moment.
+  InFuture(DateTime<Utc>),
+  /// Duration added to or subtracted from the current moment leaves the supported dates.
+  OutOfRange,
+}
+
+impl TimeParseError {
+  fn new(input: &str, span: Range<usize>, kind: TimeParseErrorKind) -> Self {
+    TimeParseError {
+      input: input.to_string(),
+      span,
+      kind,
+    }
+  }
+
+  fn whole_input(input: &str, kind: TimeParseErrorKind) -> Self {
+    TimeParseError::new(input, 0..input.len(), kind)
+  }
+
+  pub fn kind(&self) -> &TimeParseErrorKind {
+    &self.kind
+  }
+
+  /// Byte range within the input of the part which could not be parsed.
+  pub fn span(&self) -> Range<usize> {
+    self.span.clone()
+  }
+
+  fn message(&self) -> String {
+    let token = &self.input[self.span.clone()];
+    match &self.kind {
+      TimeParseErrorKind::Empty => "Expected a duration or moment in time but got nothing".into(),
+      TimeParseErrorKind::UnknownUnit(unit) => format!(
+        "Unknown time unit \"{unit}\", expected one of s, m, h, d, w or their long forms like minutes"
+      ),
+      TimeParseErrorKind::MissingUnit => {
+        format!("Number \"{token}\" needs a time unit like h, m or s")
+      }
+      TimeParseErrorKind::MissingNumber => format!("Time unit \"{token}\" needs a number before it"),
+      TimeParseErrorKind::InvalidNumber => format!("\"{token}\" is not a valid number or too large"),
+      TimeParseErrorKind::InvalidClockTime => format!(
+        "\"{token}\" is not a valid clock time, expected hours:minutes or hours:minutes:seconds"
+      ),
+      TimeParseErrorKind::InvalidIsoDuration => format!(
+        "\"{token}\" is not valid within an ISO 8601 duration, expected a form like PT1H30M"
+      ),
+      TimeParseErrorKind::UnexpectedToken => format!("Unexpected \"{token}\""),
+      TimeParseErrorKind::UnknownMoment => format!(
+        "\"{token}\" is not a known moment, expected a time like 09:15, a date time like \
+        2022-10-20T09:15, now, today, yesterday or a duration followed by ago"
+      ),
+      TimeParseErrorKind::NonExistentLocalTime => {
+        format!("\"{token}\" does not exist in the local time zone")
+      }
+      TimeParseErrorKind::InFuture(moment) => format!("{moment} lies in the future"),
+      TimeParseErrorKind::OutOfRange => {
+        format!("\"{token}\" is too far away from now to be a supported date")
+      }
+    }
+  }
+}
+
+impl Display for TimeParseError {
+  /// Shows the message and below it the input with markers under the invalid part.
+  /// # Example
+  /// ```text
+  /// Unknown time unit "x", expected one of s, m, h, d, w or their long forms like minutes
+  ///   20x
+  ///     ^
+  /// ```
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let offset = self.input[..self.span.start].chars().count();
+    let marker_len = self.input[self.span.clone()].chars().count().max(1);
+    writeln!(f, "{}", self.message())?;
+    writeln!(f, "  {}", self.input)?;
+    write!(f, "  {}{}", " ".repeat(offset), "^".repeat(marker_len))
+  }
+}
+
+impl std::error::Error for TimeParseError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+  Number,
+  Word,
+  Colon,
+  Other,
+}
+
+#[derive(Debug, Clone)]
+struct Token<'a> {
+  kind: TokenKind,
+  text: &'a str,
+  span: Range<usize>,
+}
+
+/// Parses a duration. See module documentation for accepted forms.
+/// # Example
+/// ```
+/// use stamp_member::chrono_utility::{self, parsing};
+///
+/// let expected = chrono_utility::duration_with_hms(1, 30, 0);
+///
+/// assert_eq!(expected, parsing::parse_duration("1h30m").unwrap());
+/// assert_eq!(expected, parsing::parse_duration("01:30:00").unwrap());
+/// assert_eq!(expected, parsing::parse_duration("PT1H30M").unwrap());
+/// assert_eq!(expected, parsing::parse_duration("1 hour 30 minutes").unwrap());
+/// ```
+/// # Errors
+/// If the text is not a duration. The error points at the invalid part.
+pub fn parse_duration(input: &str) -> Result<Duration, TimeParseError> {
+  let start = input.len() - input.trim_start().len();
+  if input.trim().starts_with(ISO_DURATION_START) {
+    return parse_iso_duration(input, start);
+  }
+
+  let tokens = tokenize(input);
+  duration_from_tokens(input, &tokens)
+}
+
+/// Parses a moment in time. See module documentation for accepted forms.
+/// A date time without offset and a time of day are interpreted in the time zone of param now.
+/// # Errors
+/// If the text is not a moment. The error points at the invalid part.
+pub fn parse_moment<Tz: TimeZone>(
+  input: &str,
+  now: &DateTime<Tz>,
+) -> Result<DateTime<Utc>, TimeParseError> {
+  let trimmed = input.trim();
+  if let Ok(with_offset) = DateTime::parse_from_rfc3339(trimmed) {
+    return Ok(with_offset.with_timezone(&Utc));
+  }
+  if let Some(local) = parse_local_date_time(trimmed) {
+    return local_to_utc(input, 0..input.len(), &local, now);
+  }
+
+  let tokens = tokenize(input);
+  moment_from_tokens(input, &tokens, now)
+}
+
+/// Same as [`parse_moment`], but only accepts moments which are not after param now.
+/// # Errors
+/// If the text is not a moment or if the moment lies after param now.
+pub fn parse_past_moment<Tz: TimeZone>(
+  input: &str,
+  now: &DateTime<Tz>,
+) -> Result<DateTime<Utc>, TimeParseError> {
+  let moment = parse_moment(input, now)?;
+  if moment > now.with_timezone(&Utc) {
+    Err(TimeParseError::whole_input(
+      input,
+      TimeParseErrorKind::InFuture(moment),
+    ))
+  } else {
+    Ok(moment)
+  }
+}
+
+/// Returns the moment which lies the parsed duration before param now.
+/// # Errors
+/// If the text is not a duration.
+pub fn parse_moment_ago<Tz: TimeZone>(
+  input: &str,
+  now: &DateTime<Tz>,
+) -> Result<DateTime<Utc>, TimeParseError> {
+  let duration = parse_duration(input)?;
+  now
+    .with_timezone(&Utc)
+    .checked_sub_signed(duration)
+    .ok_or_else(|| TimeParseError::whole_input(input, TimeParseErrorKind::OutOfRange))
+}
+
+/// Parses a calendar day like 2022-10-20. Every moment [`parse_moment`] accepts is allowed too,
+/// like yesterday or 3d ago, and is turned into its day in the time zone of param now.
+/// # Errors
+/// If the text is neither a date nor a moment.
+pub fn parse_day<Tz: TimeZone>(
+  input: &str,
+  now: &DateTime<Tz>,
+) -> Result<NaiveDate, TimeParseError> {
+  if let Ok(day) = NaiveDate::parse_from_str(input.trim(), DATE_FORMAT) {
+    return Ok(day);
+  }
+
+  let moment = parse_moment(input, now)?;
+  Ok(moment.with_timezone(&now.timezone()).naive_local().date())
+}
+
+fn tokenize(input: &str) -> Vec<Token<'_>> {
+  let mut tokens = Vec::new();
+  let mut chars = input.char_indices().peekable();
+
+  while let Some((start, next)) = chars.next() {
+    if next.is_whitespace() {
+      continue;
+    }
+
+    let kind = if next.is_ascii_digit() || next == '.' {
+      TokenKind::Number
+    } else if next.is_alphabetic() {
+      TokenKind::Word
+    } else if next == ':' {
+      TokenKind::Colon
+    } else {
+      TokenKind::Other
+    };
+
+    let mut end = start + next.len_utf8();
+    if matches!(kind, TokenKind::Number | TokenKind::Word) {
+      while let Some(&(index, following)) = chars.peek() {
+        let same_kind = match kind {
+          TokenKind::Number => following.is_ascii_digit() || following == '.',
+          _ => following.is_alphabetic(),
+        };
+        if !same_kind {
+          break;
+        }
+        end = index + following.len_utf8();
+        chars.next();
+      }
+    }
+
+    tokens.push(Token {
+      kind,
+      text: &input[start..end],
+      span: start..end,
+    });
+  }
+
+  tokens
+}
+
+fn duration_from_tokens(input: &str, tokens: &[Token]) -> Result<Duration, TimeParseError> {
+  if tokens.is_empty() {
+    return Err(TimeParseError::whole_input(
+      input,
+      TimeParseErrorKind::Empty,
+    ));
+  }
+
+  if tokens.iter().any(|token| token.kind == TokenKind::Colon) {
+    let (hours, minutes, seconds) = clock_from_tokens(input, tokens)?;
+    return Ok(Duration::seconds(
+      hours * SECS_PER_HOUR + minutes * SECS_PER_MINUTE + seconds,
+    ));
+  }
+
+  let mut total_millis: i64 = 0;
+  let mut index = 0;
+  while index < tokens.len() {
+    let number = &tokens[index];
+    match number.kind {
+      TokenKind::Number => (),
+      TokenKind::Word if index == 0 && unit_in_secs(number.text).is_some() => {
+        return Err(error_at(input, number, TimeParseErrorKind::MissingNumber))
+      }
+      _ => return Err(error_at(input, number, TimeParseErrorKind::UnexpectedToken)),
+    }
+
+    let unit = match tokens.get(index + 1) {
+      Some(unit) if unit.kind == TokenKind::Word => unit,
+      Some(other) if other.kind != TokenKind::Number => {
+        return Err(error_at(input, other, TimeParseErrorKind::UnexpectedToken))
+      }
+      _ => return Err(error_at(input, number, TimeParseErrorKind::MissingUnit)),
+    };
+    let secs_per_unit = unit_in_secs(unit.text).ok_or_else(|| {
+      error_at(
+        input,
+        unit,
+        TimeParseErrorKind::UnknownUnit(unit.text.to_string()),
+      )
+    })?;
+
+    let millis = number_times_unit_in_millis(number.text, secs_per_unit)
+      .ok_or_else(|| error_at(input, number, TimeParseErrorKind::InvalidNumber))?;
+    total_millis = total_millis
+      .checked_add(millis)
+      .filter(|sum| *sum / 1000 <= MAX_DURATION_SECS)
+      .ok_or_else(|| error_at(input, number, TimeParseErrorKind::InvalidNumber))?;
+
+    index += 2;
+  }
+
+  Ok(Duration::milliseconds(total_millis))
+}
+
+/// Reads tokens of the form hours:minutes or hours:minutes:seconds.
+/// Minutes and seconds must be below 60. Hours are not limited.
+fn clock_from_tokens(input: &str, tokens: &[Token]) -> Result<(i64, i64, i64), TimeParseError> {
+  let clock_span = tokens[0].span.start..tokens[tokens.len() - 1].span.end;
+  let invalid = || {
+    TimeParseError::new(
+      input,
+      clock_span.clone(),
+      TimeParseErrorKind::InvalidClockTime,
+    )
+  };
+
+  let mut numbers = Vec::new();
+  for (index, token) in tokens.iter().enumerate() {
+    let expected_kind = if index % 2 == 0 {
+      TokenKind::Number
+    } else {
+      TokenKind::Colon
+    };
+    if token.kind != expected_kind {
+      return Err(error_at(input, token, TimeParseErrorKind::UnexpectedToken));
+    }
+    if expected_kind == TokenKind::Number {
+      let number: i64 = token
+        .text
+        .parse()
+        .map_err(|_| error_at(input, token, TimeParseErrorKind::InvalidNumber))?;
+      numbers.push(number);
+    }
+  }
+
+  let (hours, minutes, seconds) = match numbers[..] {
+    [hours, minutes] if tokens.len() == 3 => (hours, minutes, 0),
+    [hours, minutes, seconds] if tokens.len() == 5 => (hours, minutes, seconds),
+    _ => return Err(invalid()),
+  };
+
+  if minutes >= 60 || seconds >= 60 || hours > MAX_DURATION_SECS / SECS_PER_HOUR {
+    return Err(invalid());
+  }
+
+  Ok((hours, minutes, seconds))
+}
+
+/// Parses durations like PT1H30M or P1W. Years and months are rejected because their length
+/// depends on the calendar.
+fn parse_iso_duration(input: &str, start: usize) -> Result<Duration, TimeParseError> {
+  let body = input.trim_end();
+  let mut total_millis: i64 = 0;
+  let mut in_time_part = false;
+  let mut has_component = false;
+  let mut number_start: Option<usize> = None;
+
+  let invalid_at = |index: usize, len: usize| {
+    TimeParseError::new(
+      input,
+      index..index + len,
+      TimeParseErrorKind::InvalidIsoDuration,
+    )
+  };
+
+  for (index, next) in body
+    .char_indices()
+    .skip_while(|(index, _)| *index < start)
+    .skip(1)
+  {
+    if next.is_ascii_digit() || next == '.' {
+      number_start.get_or_insert(index);
+      continue;
+    }
+
+    if next == ISO_DURATION_TIME_START && number_start.is_none() && !in_time_part {
+      in_time_part = true;
+      continue;
+    }
+
+    let secs_per_unit = match (in_time_part, next) {
+      (false, 'W') => SECS_PER_WEEK,
+      (false, 'D') => SECS_PER_DAY,
+      (true, 'H') => SECS_PER_HOUR,
+      (true, 'M') => SECS_PER_MINUTE,
+      (true, 'S') => 1,
+      _ => return Err(invalid_at(index, next.len_utf8())),
+    };
+
+    let number_begin = number_start
+      .take()
+      .ok_or_else(|| invalid_at(index, next.len_utf8()))?;
+    let number_text = &body[number_begin..index];
+    let millis = number_times_unit_in_millis(number_text, secs_per_unit).ok_or_else(|| {
+      TimeParseError::new(
+        input,
+        number_begin..index,
+        TimeParseErrorKind::InvalidNumber,
+      )
+    })?;
+    total_millis = total_millis
+      .checked_add(millis)
+      .filter(|sum| *sum / 1000 <= MAX_DURATION_SECS)
+      .ok_or_else(|| {
+        TimeParseError::new(
+          input,
+          number_begin..index,
+          TimeParseErrorKind::InvalidNumber,
+        )
+      })?;
+    has_component = true;
+  }
+
+  if let Some(dangling) = number_start {
+    return Err(TimeParseError::new(
+      input,
+      dangling..body.len(),
+      TimeParseErrorKind::MissingUnit,
+    ));
+  }
+  if !has_component || body.ends_with(ISO_DURATION_TIME_START) {
+    return Err(TimeParseError::new(
+      input,
+      start..body.len(),
+      TimeParseErrorKind::InvalidIsoDuration,
+    ));
+  }
+
+  Ok(Duration::milliseconds(total_millis))
+}
+
+fn moment_from_tokens<Tz: TimeZone>(
+  input: &str,
+  tokens: &[Token],
+  now: &DateTime<Tz>,
+) -> Result<DateTime<Utc>, TimeParseError> {
+  let first = match tokens.first() {
+    Some(first) => first,
+    None => {
+      return Err(TimeParseError::whole_input(
+        input,
+        TimeParseErrorKind::Empty,
+      ))
+    }
+  };
+  let last = &tokens[tokens.len() - 1];
+  let now_utc = now.with_timezone(&Utc);
+
+  if first.kind == TokenKind::Word {
+    let day_offset = match first.text.to_lowercase().as_str() {
+      "now" => {
+        expect_end(input, &tokens[1..])?;
+        return Ok(now_utc);
+      }
+      "in" => {
+        let duration = duration_from_tokens(input, &tokens[1..])?;
+        return now_utc
+          .checked_add_signed(duration)
+          .ok_or_else(|| out_of_range(input, tokens));
+      }
+      "today" => 0,
+      "yesterday" => -1,
+      "tomorrow" => 1,
+      _ => return Err(error_at(input, first, TimeParseErrorKind::UnknownMoment)),
+    };
+
+    let date = now.naive_local().date() + Duration::days(day_offset);
+    let rest = &tokens[1..];
+    let time = if rest.is_empty() {
+      NaiveTime::from_hms(0, 0, 0)
+    } else {
+      time_of_day_from_tokens(input, rest)?
+    };
+    let span = first.span.start..last.span.end;
+    return local_to_utc(input, span, &date.and_time(time), now);
+  }
+
+  if last.kind == TokenKind::Word && last.text.eq_ignore_ascii_case("ago") {
+    let before = &tokens[..tokens.len() - 1];
+    if before.is_empty() {
+      return Err(error_at(input, last, TimeParseErrorKind::UnexpectedToken));
+    }
+    let duration = duration_from_tokens(input, before)?;
+    return now_utc
+      .checked_sub_signed(duration)
+      .ok_or_else(|| out_of_range(input, tokens));
+  }
+
+  if first.kind == TokenKind::Number && tokens.iter().any(|token| token.kind == TokenKind::Colon) {
+    let time = time_of_day_from_tokens(input, tokens)?;
+    let today = now.naive_local().date();
+    let span = first.span.start..last.span.end;
+    return local_to_utc(input, span, &today.and_time(time), now);
+  }
+
+  Err(TimeParseError::new(
+    input,
+    first.span.start..last.span.end,
+    TimeParseErrorKind::UnknownMoment,
+  ))
+}
+
+fn time_of_day_from_tokens(input: &str, tokens: &[Token]) -> Result<NaiveTime, TimeParseError> {
+  let (hours, minutes, seconds) = clock_from_tokens(input, tokens)?;
+  u32::try_from(hours)
+    .ok()
+    .and_then(|hours| NaiveTime::from_hms_opt(hours, minutes as u32, seconds as u32))
+    .ok_or_else(|| {
+      TimeParseError::new(
+        input,
+        tokens[0].span.start..tokens[tokens.len() - 1].span.end,
+        TimeParseErrorKind::InvalidClockTime,
+      )
+    })
+}
+
+fn expect_end(input: &str, rest: &[Token]) -> Result<(), TimeParseError> {
+  match rest.first() {
+    Some(unexpected) => Err(error_at(
+      input,
+      unexpected,
+      TimeParseErrorKind::UnexpectedToken,
+    )),
+    None => Ok(()),
+  }
+}
+
+fn error_at(input: &str, token: &Token, kind: TimeParseErrorKind) -> TimeParseError {
+  TimeParseError::new(input, token.span.clone(), kind)
+}
+
+fn out_of_range(input: &str, tokens: &[Token]) -> TimeParseError {
+  TimeParseError::new(
+    input,
+    tokens[0].span.start..tokens[tokens.len() - 1].span.end,
+    TimeParseErrorKind::OutOfRange,
+  )
+}
+
+/// Returns how many seconds one of the given unit has or None if the unit is unknown.
+fn unit_in_secs(unit: &str) -> Option<i64> {
+  match unit.to_lowercase().as_str() {
+    "s" | "sec" | "secs" | "second" | "seconds" => Some(1),
+    "m" | "min" | "mins" | "minute" | "minutes" => Some(SECS_PER_MINUTE),
+    "h" | "hr" | "hrs" | "hour" | "hours" => Some(SECS_PER_HOUR),
+    "d" | "day" | "days" => Some(SECS_PER_DAY),
+    "w" | "wk" | "week" | "weeks" => Some(SECS_PER_WEEK),
+    _ => None,
+  }
+}
+
+/// Multiplies a number like 2 or 1.5 with the seconds of a unit and returns milliseconds.
+/// Returns None if the number is not valid or too large.
+fn number_times_unit_in_millis(number: &str, secs_per_unit: i64) -> Option<i64> {
+  if let Ok(whole) = number.parse::<i64>() {
+    return whole
+      .checked_mul(secs_per_unit)
+      .filter(|secs| *secs <= MAX_DURATION_SECS)
+      .map(|secs| secs * 1000);
+  }
+
+  let fraction: f64 = number.parse().ok()?;
+  let millis = (fraction * secs_per_unit as f64 * 1000.0).round();
+  if millis.is_finite() && millis >= 0.0 && millis / 1000.0 <= MAX_DURATION_SECS as f64 {
+    Some(millis as i64)
+  } else {
+    None
+  }
+}
+
+fn parse_local_date_time(input: &str) -> Option<NaiveDateTime> {
+  for format in DATE_TIME_FORMATS {
+    if let Ok(parsed) = NaiveDateTime::parse_from_str(input, format) {
+      return Some(parsed);
+    }
+  }
+
+  NaiveDate::parse_from_str(input, DATE_FORMAT)
+    .ok()
+    .map(|date| date.and_hms(0, 0, 0))
+}
+
+/// Interprets a wall clock time in the time zone of param now. On ambiguous local times, for
+/// example while the clock is turned back, the earlier moment is taken.
+fn local_to_utc<Tz: TimeZone>(
+  input: &str,
+  span: Range<usize>,
+  local: &NaiveDateTime,
+  now: &DateTime<Tz>,
+) -> Result<DateTime<Utc>, TimeParseError> {
+  match now.timezone().from_local_datetime(local) {
+    LocalResult::Single(moment) | LocalResult::Ambiguous(moment, _) => {
+      Ok(moment.with_timezone(&Utc))
+    }
+    LocalResult::None => Err(TimeParseError::new(
+      input,
+      span,
+      TimeParseErrorKind::NonExistentLocalTime,
+    )),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono::FixedOffset;
+
+  use super::*;
+  use crate::chrono_utility;
+
+  fn berlin_summer_now() -> DateTime<FixedOffset> {
+    FixedOffset::east(2 * 3600)
+      .ymd(2022, 10, 20)
+      .and_hms(11, 0, 0)
+  }
+
+  fn assert_error(
+    actual: Result<impl std::fmt::Debug, TimeParseError>,
+    expected_kind: TimeParseErrorKind,
+    expected_span: Range<usize>,
+  ) {
+    match actual {
+      Ok(parsed) => panic!("Should fail, but parsed {parsed:?}"),
+      Err(error) => {
+        assert_eq!(&expected_kind, error.kind());
+        assert_eq!(expected_span, error.span());
+      }
+    }
+  }
+
+  #[test]
+  fn should_parse_durations_with_units() {
+    let cases = [
+      ("90s", Duration::seconds(90)),
+      ("1h30m", chrono_utility::duration_with_hms(1, 30, 0)),
+      ("1d2h5s", chrono_utility::duration_with_hms(26, 0, 5)),
+      ("2 days", Duration::days(2)),
+      (
+        "1 hour 30 minutes",
+        chrono_utility::duration_with_hms(1, 30, 0),
+      ),
+      ("1.5h", chrono_utility::duration_with_hms(1, 30, 0)),
+      ("1w", Duration::weeks(1)),
+    ];
+    for (input, expected) in cases {
+      assert_eq!(expected, parse_duration(input).unwrap(), "Input: {input}");
+    }
+  }
+
+  #[test]
+  fn should_parse_durations_in_clock_form() {
+    assert_eq!(
+      chrono_utility::duration_with_hms(1, 30, 0),
+      parse_duration("01:30:00").unwrap()
+    );
+    assert_eq!(
+      chrono_utility::duration_with_hms(26, 5, 0),
+      parse_duration("26:05").unwrap()
+    );
+  }
+
+  #[test]
+  fn should_parse_iso_durations() {
+    assert_eq!(
+      chrono_utility::duration_with_hms(1, 30, 0),
+      parse_duration("PT1H30M").unwrap()
+    );
+    assert_eq!(
+      chrono_utility::duration_with_hms(51, 0, 1),
+      parse_duration("P2DT3H1S").unwrap()
+    );
+    assert_eq!(Duration::weeks(1), parse_duration("P1W").unwrap());
+  }
+
+  #[test]
+  fn should_point_at_unknown_unit() {
+    let actual = parse_duration("1h 20x");
+
+    assert_error(
+      actual,
+      TimeParseErrorKind::UnknownUnit("x".to_string()),
+      5..6,
+    );
+  }
+
+  #[test]
+  fn should_point_at_number_without_unit() {
+    assert_error(
+      parse_duration("1h 20"),
+      TimeParseErrorKind::MissingUnit,
+      3..5,
+    );
+    assert_error(parse_duration("h"), TimeParseErrorKind::MissingNumber, 0..1);
+    assert_error(parse_duration("  "), TimeParseErrorKind::Empty, 0..2);
+  }
+
+  #[test]
+  fn should_point_at_invalid_iso_designator() {
+    assert_error(
+      parse_duration("P1Y"),
+      TimeParseErrorKind::InvalidIsoDuration,
+      2..3,
+    );
+    assert_error(
+      parse_duration("PT"),
+      TimeParseErrorKind::InvalidIsoDuration,
+      0..2,
+    );
+  }
+
+  #[test]
+  fn should_reject_too_large_duration() {
+    assert_error(
+      parse_duration("99999999999999999999h"),
+      TimeParseErrorKind::InvalidNumber,
+      0..20,
+    );
+  }
+
+  #[test]
+  fn should_show_marker_under_invalid_part() {
+    let error = parse_duration("20x").unwrap_err();
+
+    let lines: Vec<String> = error.to_string().lines().map(String::from).collect();
+
+    assert_eq!(vec!["  20x".to_string(), "    ^".to_string()], lines[1..]);
+  }
+
+  #[test]
+  fn should_parse_time_of_today_in_local_zone() {
+    let actual = parse_moment("09:15", &berlin_summer_now()).unwrap();
+
+    assert_eq!(Utc.ymd(2022, 10, 20).and_hms(7, 15, 0), actual);
+  }
+
+  #[test]
+  fn should_parse_iso_date_time_with_and_without_offset() {
+    let now = berlin_summer_now();
+
+    let with_offset = parse_moment("2022-10-19T22:30:00Z", &now).unwrap();
+    let without_offset = parse_moment("2022-10-19T22:30", &now).unwrap();
+    let only_date = parse_moment("2022-10-19", &now).unwrap();
+
+    assert_eq!(Utc.ymd(2022, 10, 19).and_hms(22, 30, 0), with_offset);
+    assert_eq!(Utc.ymd(2022, 10, 19).and_hms(20, 30, 0), without_offset);
+    assert_eq!(Utc.ymd(2022, 10, 18).and_hms(22, 0, 0), only_date);
+  }
+
+  #[test]
+  fn should_parse_relative_moments() {
+    let now = berlin_summer_now();
+    let cases = [
+      ("now", Utc.ymd(2022, 10, 20).and_hms(9, 0, 0)),
+      ("yesterday 14:00", Utc.ymd(2022, 10, 19).and_hms(12, 0, 0)),
+      ("Today", Utc.ymd(2022, 10, 19).and_hms(22, 0, 0)),
+      ("tomorrow 8:30:10", Utc.ymd(2022, 10, 21).and_hms(6, 30, 10)),
+      ("20m ago", Utc.ymd(2022, 10, 20).and_hms(8, 40, 0)),
+      ("in 1 hour", Utc.ymd(2022, 10, 20).and_hms(10, 0, 0)),
+    ];
+    for (input, expected) in cases {
+      assert_eq!(
+        expected,
+        parse_moment(input, &now).unwrap(),
+        "Input: {input}"
+      );
+    }
+  }
+
+  #[test]
+  fn should_point_at_invalid_part_of_moment() {
+    let now = berlin_summer_now();
+
+    assert_error(
+      parse_moment("yesterday 25:00", &now),
+      TimeParseErrorKind::InvalidClockTime,
+      10..15,
+    );
+    assert_error(
+      parse_moment("lastweek", &now),
+      TimeParseErrorKind::UnknownMoment,
+      0..8,
+    );
+    assert_error(
+      parse_moment("now please", &now),
+      TimeParseErrorKind::UnexpectedToken,
+      4..10,
+    );
+    assert_error(
+      parse_moment("3 dayz ago", &now),
+      TimeParseErrorKind::UnknownUnit("dayz".to_string()),
+      2..6,
+    );
+  }
+
+  #[test]
+  fn should_reject_past_moment_in_future() {
+    let actual = parse_past_moment("11:00:01", &berlin_summer_now());
+
+    assert_error(
+      actual,
+      TimeParseErrorKind::InFuture(Utc.ymd(2022, 10, 20).and_hms(9, 0, 1)),
+      0..8,
+    );
+  }
+
+  #[test]
+  fn should_return_moment_ago() {
+    let actual = parse_moment_ago("20m", &berlin_summer_now()).unwrap();
+
+    assert_eq!(Utc.ymd(2022, 10, 20).and_hms(8, 40, 0), actual);
+  }
+
+  #[test]
+  fn should_reject_moment_beyond_supported_dates() {
+    let now = berlin_summer_now();
+
+    assert_error(
+      parse_moment_ago("100000000d", &now),
+      TimeParseErrorKind::OutOfRange,
+      0..10,
+    );
+    assert_error(
+      parse_moment("in 100000000d", &now),
+      TimeParseErrorKind::OutOfRange,
+      0..13,
+    );
+    assert_error(
+      parse_moment("100000000d ago", &now),
+      TimeParseErrorKind::OutOfRange,
+      0..14,
+    );
+  }
+
+  #[test]
+  fn should_parse_day_in_zone_of_now() {
+    let now = berlin_summer_now();
+
+    assert_eq!(
+      NaiveDate::from_ymd(2022, 10, 1),
+      parse_day("2022-10-01", &now).unwrap()
+    );
+    assert_eq!(
+      NaiveDate::from_ymd(2022, 10, 19),
+      parse_day("yesterday", &now).unwrap()
+    );
+    // 01:00 in Berlin, but still 23:00 of the day before in UTC.
+    assert_eq!(
+      NaiveDate::from_ymd(2022, 10, 20),
+      parse_day("10h ago", &now).unwrap()
+    );
+  }
+}
@@ -0,0 +1,183 @@
+//! Completion scripts for shells generated from the command line definition. Bash, zsh and fish
+//! also complete the titles of existing entries after `stop`, `resume`, `switch` and `pomodoro`
+//! by calling the hidden `complete-titles` command. Load them like:
+//! ```text
+//! source <(stamp_member completions bash)    # ~/.bashrc
+//! source <(stamp_member completions zsh)     # ~/.zshrc after compinit
+//! stamp_member completions fish > ~/.config/fish/completions/stamp_member.fish
+//! ```
+use std::io::{self, Write};
+
+use clap::CommandFactory;
+use clap_complete::Shell;
+
+use crate::{
+  args_parser::CliArgs,
+  time_entities::{
+    listing::{EntityState, ListedEntity},
+    pomodoro::PomodoroPhase,
+    time_entities_controller::TimeEntitiesController,
+    TimeEntity,
+  },
+};
+
+const BIN_NAME: &str = "stamp_member";
+/// Implemented commands which take a title of an existing entry.
+pub const TITLE_COMMANDS: [&str; 4] = ["stop", "resume", "switch", "pomodoro"];
+
+/// Writes the completion script for the shell.
+/// # Errors
+/// If the script can not be written.
+pub fn write_completions(shell: Shell, out: &mut impl Write) -> io::Result<()> {
+  clap_complete::generate(shell, &mut CliArgs::command(), BIN_NAME, out);
+  out.write_all(title_completion(shell).as_bytes())?;
+  out.flush()
+}
+
+/// Returns the part of the script which completes titles or nothing for other shells.
+fn title_completion(shell: Shell) -> String {
+  let commands = TITLE_COMMANDS.join(" ");
+  match shell {
+    // Typed text is compared without quotes and backslashes and titles are inserted escaped.
+    Shell::Bash => format!(
+      r#"
+_{BIN_NAME}_with_titles() {{
+    if [[ ${{COMP_CWORD}} -eq 2 && " {commands} " == *" ${{COMP_WORDS[1]}} "* ]]; then
+        local current="${{COMP_WORDS[COMP_CWORD]}}"
+        current="${{current#[\"\']}}"
+        current="${{current//\\/}}"
+        local title
+        COMPREPLY=()
+        while IFS= read -r title; do
+            if [[ "${{title}}" == "${{current}}"* ]]; then
+                COMPREPLY+=("$(printf '%q' "${{title}}")")
+            fi
+        done < <({BIN_NAME} complete-titles "${{COMP_WORDS[1]}}" 2>/dev/null)
+        return 0
+    fi
+    _{BIN_NAME} "$@"
+}}
+complete -F _{BIN_NAME}_with_titles -o bashdefault -o default {BIN_NAME}
+"#
+    ),
+    // compadd quotes spaces and special characters itself.
+    Shell::Zsh => format!(
+      r#"
+_{BIN_NAME}_with_titles() {{
+    if (( CURRENT == 3 )) && [[ " {commands} " == *" ${{words[2]}} "* ]]; then
+        local -a titles
+        titles=(${{(f)"$({BIN_NAME} complete-titles "${{words[2]}}" 2>/dev/null)"}})
+        compadd -a titles
+    else
+        _{BIN_NAME} "$@"
+    fi
+}}
+compdef _{BIN_NAME}_with_titles {BIN_NAME}
+"#
+    ),
+    Shell::Fish => format!(
+      r#"
+for command in {commands}
+    complete -c {BIN_NAME} -n "__fish_seen_subcommand_from $command" -f -a "({BIN_NAME} complete-titles $command 2>/dev/null)"
+end
+"#
+    ),
+    _ => String::new(),
+  }
+}
+
+/// Returns the sorted titles which fit the command: running ones for stop, paused ones for
+/// resume, unfinished time stamps for switch and running pomodoros for pomodoro. Other commands
+/// get none. Titles of time stamps and stop watches are mixed, a title used by both is returned
+/// once.
+pub fn titles_for(command: &str, entities: &TimeEntitiesController) -> Vec<String> {
+  let clock = entities.clock();
+  let in_state = |fits: fn(EntityState) -> bool| -> Vec<&str> {
+    let time_stamps = entities
+      .time_stamps()
+      .iter()
+      .filter(|time_stamp| fits(EntityState::of(*time_stamp, clock)))
+      .map(TimeEntity::get_title);
+    let stop_watches = entities
+      .stop_watches()
+      .iter()
+      .filter(|stop_watch| fits(EntityState::of(*stop_watch, clock)))
+      .map(TimeEntity::get_title);
+    time_stamps.chain(stop_watches).collect()
+  };
+  let titles = match command {
+    "stop" => in_state(|state| matches!(state, EntityState::Running | EntityState::Expired)),
+    "resume" => in_state(|state| state == EntityState::Paused),
+    "switch" => entities
+      .time_stamps()
+      .iter()
+      .filter(|time_stamp| EntityState::of(*time_stamp, clock) != EntityState::Finished)
+      .map(TimeEntity::get_title)
+      .collect(),
+    "pomodoro" => entities
+      .stop_watches()
+      .iter()
+      .filter(|stop_watch| {
+        matches!(stop_watch.get_pomodoro(), Some(PomodoroPhase::Work(_)))
+          && stop_watch.time_stamp().get_ended().is_none()
+      })
+      .map(TimeEntity::get_title)
+      .collect(),
+    _ => Vec::new(),
+  };
+
+  let mut titles: Vec<String> = titles.into_iter().map(str::to_string).collect();
+  titles.sort();
+  titles.dedup();
+  titles
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+
+  use chrono::{Duration, TimeZone, Utc};
+
+  use super::*;
+  use crate::{
+    chrono_utility::clock::FixedClock,
+    time_entities::{stop_watch::StopWatch, time_stamp::TimeStamp},
+  };
+
+  #[test]
+  fn should_offer_titles_by_state_of_command() {
+    let started = Utc.ymd(2022, 10, 20).and_hms(9, 0, 0);
+    let clock = FixedClock::new(started + Duration::minutes(10));
+    let mut paused = TimeStamp::with_started("Code review", started);
+    paused.pause(&clock).unwrap();
+    let entities = TimeEntitiesController::new(
+      vec![paused, TimeStamp::with_started("Coding", started)],
+      vec![StopWatch::new(
+        TimeStamp::with_started("Tea", started),
+        Duration::minutes(4),
+      )],
+    )
+    .with_clock(Arc::new(clock));
+
+    assert_eq!(
+      vec!["Code review", "Coding"],
+      titles_for("switch", &entities)
+    );
+    assert!(titles_for("pomodoro", &entities).is_empty());
+    assert_eq!(vec!["Coding", "Tea"], titles_for("stop", &entities));
+    assert_eq!(vec!["Code review"], titles_for("resume", &entities));
+    // Not implemented yet, so no titles are offered.
+    assert!(titles_for("delete", &entities).is_empty());
+  }
+
+  #[test]
+  fn should_append_title_completion_to_generated_script() {
+    let mut script = Vec::new();
+
+    write_completions(Shell::Fish, &mut script).unwrap();
+
+    let script = String::from_utf8(script).unwrap();
+    assert!(script.contains("complete -c stamp_member"));
+    assert!(script.contains("__fish_seen_subcommand_from $command"));
+  }
+}
@@ -0,0 +1,678 @@
+//! Settings in layers, later ones winning over earlier ones:
+//! 1. the user config file, by default ~/.config/stamp_member/config.toml,
+//! 2. the config file of the selected profile like ~/.config/stamp_member/profiles/work.toml,
+//! 3. the project config file `.stamp_member.toml` in the working directory or the nearest
+//!    parent directory which has one. It comes with a checked out repository, so it can not set
+//!    hooks, which run shell commands, nor the path of the data file,
+//! 4. environment variables like `STAMP_MEMBER_POLICY`,
+//! 5. command line flags like `--time-zone`.
+//!
+//! Every key is a section and a name like `tracking.policy` and is listed in [`SETTINGS`].
+//! Unknown keys and invalid values are errors which name the key and where it is set:
+//! ```toml
+//! [display]
+//! time_zone = "Europe/Berlin"
+//! duration_format = "compact"
+//!
+//! [tracking]
+//! policy = "auto-pause"
+//! ```
+use std::{
+  collections::BTreeMap,
+  fmt::Display,
+  path::{Path, PathBuf},
+};
+
+use crate::{
+  chrono_utility::{
+    date_time_display::{DateTimeFormat, DisplayZone},
+    parsing, DifferenceFormat,
+  },
+  data_access::paths::DATA_PATH_ENV_VAR,
+  profile::{Profile, PROFILE_ENV_VAR},
+  status::StatusTemplate,
+  theme::{ColorChoice, Style},
+  time_entities::running_policy::RunningPolicy,
+};
+
+pub const PROJECT_CONFIG_FILE_NAME: &str = ".stamp_member.toml";
+
+/// Key which can be set in a config file and maybe via an environment variable.
+pub struct Setting {
+  pub key: &'static str,
+  pub env: Option<&'static str>,
+  /// Short explanation of the allowed values.
+  pub description: &'static str,
+  kind: SettingKind,
+}
+
+#[derive(Clone, Copy)]
+enum SettingKind {
+  /// String which is valid if the function accepts it.
+  Text(fn(&str) -> Result<(), String>),
+  Integer {
+    min: i64,
+    max: i64,
+  },
+  /// One shell command or an array of them.
+  Commands,
+}
+
+pub const SETTINGS: [Setting; 24] = [
+  text(
+    "display.time_zone",
+    Some("STAMP_MEMBER_TIME_ZONE"),
+    "IANA time zone like Europe/Berlin or local",
+    |value| valid(DisplayZone::from_name(value)),
+  ),
+  text(
+    "display.time_format",
+    Some("STAMP_MEMBER_TIME_FORMAT"),
+    "strftime-like pattern like \"%d.%m.%Y %H:%M\" or iso",
+    |value| valid(DateTimeFormat::from_pattern(value)),
+  ),
+  text(
+    "display.duration_format",
+    Some("STAMP_MEMBER_DURATION_FORMAT"),
+    "compact, clock, human or decimal",
+    |value| valid(DifferenceFormat::from_name(value)),
+  ),
+  text(
+    "display.color",
+    Some("STAMP_MEMBER_COLOR"),
+    "auto, always or never",
+    |value| valid(ColorChoice::from_name(value)),
+  ),
+  text(
+    "tracking.policy",
+    Some("STAMP_MEMBER_POLICY"),
+    "concurrent, auto-pause or refuse",
+    |value| {
+      RunningPolicy::from_name(value).map(drop).map_err(|_| {
+        format!(
+          "\"{value}\" is not a running policy, expected one of: {}",
+          RunningPolicy::NAMES.join(", ")
+        )
+      })
+    },
+  ),
+  text(
+    "profile.name",
+    Some(PROFILE_ENV_VAR),
+    "profile whose data is used like work, default is the one without a name",
+    |value| valid(Profile::parse(value)),
+  ),
+  text(
+    "data.path",
+    Some(DATA_PATH_ENV_VAR),
+    "absolute path of the data file, the ones of other profiles lie in a profiles directory next \
+     to it",
+    |value| {
+      if Path::new(value).is_absolute() {
+        Ok(())
+      } else {
+        Err(format!("\"{value}\" is not an absolute path"))
+      }
+    },
+  ),
+  text(
+    "status.template",
+    Some("STAMP_MEMBER_STATUS_TEMPLATE"),
+    "template of the status line like \"{title} {elapsed:short}\"",
+    |value| valid(StatusTemplate::parse(value)),
+  ),
+  text(
+    "status.idle",
+    Some("STAMP_MEMBER_STATUS_IDLE"),
+    "text of the status line if nothing runs",
+    |_| Ok(()),
+  ),
+  Setting {
+    key: "serve.port",
+    env: Some("STAMP_MEMBER_PORT"),
+    description: "port of the JSON API from 1 to 65535",
+    kind: SettingKind::Integer { min: 1, max: 65535 },
+  },
+  text(
+    "pomodoro.work",
+    Some("STAMP_MEMBER_POMODORO_WORK"),
+    "length of a work cycle like 25m",
+    |value| valid(parsing::parse_duration(value)),
+  ),
+  text(
+    "pomodoro.short_break",
+    Some("STAMP_MEMBER_POMODORO_SHORT_BREAK"),
+    "length of a short break like 5m",
+    |value| valid(parsing::parse_duration(value)),
+  ),
+  text(
+    "pomodoro.long_break",
+    Some("STAMP_MEMBER_POMODORO_LONG_BREAK"),
+    "length of a long break like 15m",
+    |value| valid(parsing::parse_duration(value)),
+  ),
+  Setting {
+    key: "pomodoro.long_break_every",
+    env: Some("STAMP_MEMBER_POMODORO_LONG_BREAK_EVERY"),
+    description: "number of work cycles after which the break is long",
+    kind: SettingKind::Integer {
+      min: 1,
+      max: u32::MAX as i64,
+    },
+  },
+  style("theme.running"),
+  style("theme.paused"),
+  style("theme.finished"),
+  style("theme.expired"),
+  style("theme.header"),
+  commands("hooks.create"),
+  commands("hooks.pause"),
+  commands("hooks.resume"),
+  commands("hooks.finish"),
+  commands("hooks.expired"),
+];
+
+const fn text(
+  key: &'static str,
+  env: Option<&'static str>,
+  description: &'static str,
+  validate: fn(&str) -> Result<(), String>,
+) -> Setting {
+  Setting {
+    key,
+    env,
+    description,
+    kind: SettingKind::Text(validate),
+  }
+}
+
+const fn style(key: &'static str) -> Setting {
+  text(
+    key,
+    None,
+    "style like bold green, bright-red or 208",
+    |value| valid(Style::parse(value)),
+  )
+}
+
+const fn commands(key: &'static str) -> Setting {
+  Setting {
+    key,
+    env: None,
+    description: "shell command or array of shell commands",
+    kind: SettingKind::Commands,
+  }
+}
+
+fn valid<T, E: Display>(result: Result<T, E>) -> Result<(), String> {
+  result.map(drop).map_err(|error| error.to_string())
+}
+
+/// Where a setting is set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSource {
+  File(PathBuf),
+  Env(&'static str),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ConfigError {
+  InvalidToml {
+    source: ConfigSource,
+    error: String,
+  },
+  /// Source is none for keys given by the user directly like in `config get`.
+  UnknownKey {
+    source: Option<ConfigSource>,
+    key: String,
+  },
+  InvalidValue {
+    source: Option<ConfigSource>,
+    key: &'static str,
+    error: String,
+  },
+  NotSet(&'static str),
+}
+
+/// Valid settings of all layers merged.
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+  values: BTreeMap<&'static str, (toml::Value, ConfigSource)>,
+}
+
+impl Setting {
+  /// Returns the setting of the key like tracking.policy.
+  /// # Errors
+  /// If no setting has the key.
+  pub fn of(key: &str) -> Result<&'static Setting, ConfigError> {
+    SETTINGS
+      .iter()
+      .find(|setting| setting.key == key)
+      .ok_or_else(|| ConfigError::UnknownKey {
+        source: None,
+        key: key.to_string(),
+      })
+  }
+
+  /// Returns the value of text given on the command line or via an environment variable.
+  /// Integers are parsed, everything else is a string.
+  /// # Errors
+  /// If the value is not valid for this setting.
+  pub fn value_from_text(&self, text: &str) -> Result<toml::Value, String> {
+    let value = match self.kind {
+      SettingKind::Integer { .. } => text
+        .trim()
+        .parse()
+        .map(toml::Value::Integer)
+        .map_err(|_| format!("\"{text}\" is not a whole number"))?,
+      SettingKind::Text(_) | SettingKind::Commands => toml::Value::String(text.to_string()),
+    };
+    self.validate(&value)?;
+    Ok(value)
+  }
+
+  /// Returns why a project config file, which comes with a checked out repository, can not set
+  /// this or None if it can.
+  fn refused_in_project(&self) -> Option<&'static str> {
+    match self.kind {
+      SettingKind::Commands => Some("Runs shell commands"),
+      _ if self.key == "data.path" => Some("Chooses the data file which is read and written"),
+      _ => None,
+    }
+  }
+
+  /// # Errors
+  /// If a project config file can not set this, naming the reason.
+  pub fn check_in_project(&self, source: Option<ConfigSource>) -> Result<(), ConfigError> {
+    match self.refused_in_project() {
+      Some(reason) => Err(ConfigError::InvalidValue {
+        source,
+        key: self.key,
+        error: format!("{reason}, so it can only be set in the user or profile config file"),
+      }),
+      None => Ok(()),
+    }
+  }
+
+  fn validate(&self, value: &toml::Value) -> Result<(), String> {
+    match (self.kind, value) {
+      (SettingKind::Text(validate), toml::Value::String(text)) => validate(text),
+      (SettingKind::Integer { min, max }, toml::Value::Integer(number)) => {
+        if (min..=max).contains(number) {
+          Ok(())
+        } else {
+          Err(format!("{number} is not from {min} to {max}"))
+        }
+      }
+      (SettingKind::Commands, toml::Value::String(_)) => Ok(()),
+      (SettingKind::Commands, toml::Value::Array(commands))
+        if commands.iter().all(toml::Value::is_str) =>
+      {
+        Ok(())
+      }
+      (SettingKind::Text(_), _) => Err("Expected a string".to_string()),
+      (SettingKind::Integer { .. }, _) => Err("Expected a whole number".to_string()),
+      (SettingKind::Commands, _) => Err("Expected a string or an array of strings".to_string()),
+    }
+  }
+}
+
+impl Config {
+  /// Sets the settings of the content of a config file over the ones so far.
+  /// # Errors
+  /// If the content is no valid TOML or has an unknown key or invalid value. Nothing is set
+  /// then.
+  pub fn merge_file(&mut self, content: &str, path: &Path) -> Result<(), ConfigError> {
+    self.merge(content, path, false)
+  }
+
+  /// Like [`Config::merge_file`] for a project config file, which can not set settings that
+  /// run shell commands.
+  /// # Errors
+  /// Also if the content sets such a setting.
+  pub fn merge_project_file(&mut self, content: &str, path: &Path) -> Result<(), ConfigError> {
+    self.merge(content, path, true)
+  }
+
+  fn merge(&mut self, content: &str, path: &Path, is_project: bool) -> Result<(), ConfigError> {
+    let source = ConfigSource::File(path.to_path_buf());
+    let table: toml::Table = toml::from_str(content).map_err(|error| ConfigError::InvalidToml {
+      source: source.clone(),
+      error: error.message().to_string(),
+    })?;
+
+    let mut values = Vec::new();
+    for (section, keys) in table {
+      let toml::Value::Table(keys) = keys else {
+        return Err(ConfigError::UnknownKey {
+          source: Some(source),
+          key: section,
+        });
+      };
+      for (name, value) in keys {
+        let setting = Setting::of(&format!("{section}.{name}")).map_err(|error| match error {
+          ConfigError::UnknownKey { key, .. } => ConfigError::UnknownKey {
+            source: Some(source.clone()),
+            key,
+          },
+          error => error,
+        })?;
+        if is_project {
+          setting.check_in_project(Some(source.clone()))?;
+        }
+        setting
+          .validate(&value)
+          .map_err(|error| ConfigError::InvalidValue {
+            source: Some(source.clone()),
+            key: setting.key,
+            error,
+          })?;
+        values.push((setting.key, value));
+      }
+    }
+    for (key, value) in values {
+      self.values.insert(key, (value, source.clone()));
+    }
+    Ok(())
+  }
+
+  /// Sets the settings of the environment variables over the ones so far. Param lookup returns
+  /// the value of an environment variable, empty values are ignored.
+  /// # Errors
+  /// If a variable has an invalid value.
+  pub fn merge_env(&mut self, lookup: impl Fn(&str) -> Option<String>) -> Result<(), ConfigError> {
+    for setting in &SETTINGS {
+      let Some(env) = setting.env else {
+        continue;
+      };
+      let Some(text) = lookup(env).filter(|text| !text.is_empty()) else {
+        continue;
+      };
+      let value = setting
+        .value_from_text(&text)
+        .map_err(|error| ConfigError::InvalidValue {
+          source: Some(ConfigSource::Env(env)),
+          key: setting.key,
+          error,
+        })?;
+      self
+        .values
+        .insert(setting.key, (value, ConfigSource::Env(env)));
+    }
+    Ok(())
+  }
+
+  pub fn get(&self, key: &str) -> Option<&toml::Value> {
+    self.values.get(key).map(|(value, _)| value)
+  }
+
+  pub fn source(&self, key: &str) -> Option<&ConfigSource> {
+    self.values.get(key).map(|(_, source)| source)
+  }
+
+  /// Returns the value of a text setting parsed by param parse.
+  pub fn parsed<T, E>(&self, key: &str, parse: impl Fn(&str) -> Result<T, E>) -> Option<T> {
+    self.get(key)?.as_str().and_then(|text| parse(text).ok())
+  }
+
+  pub fn integer(&self, key: &str) -> Option<i64> {
+    self.get(key)?.as_integer()
+  }
+
+  /// Returns all settings as one config file like the sections read by other modules expect.
+  pub fn to_toml(&self) -> String {
+    let mut table = toml::Table::new();
+    for (key, (value, _)) in &self.values {
+      let (section, name) = key.split_once('.').expect("Keys have a section");
+      table
+        .entry(section)
+        .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+        .as_table_mut()
+        .expect("Sections are tables")
+        .insert(name.to_string(), value.clone());
+    }
+    table.to_string()
+  }
+}
+
+/// Returns the content of a config file with the key set to the value. Comments and the order
+/// of the other keys are kept.
+/// # Errors
+/// If the content is no valid TOML, the key is unknown or the value is invalid for the key.
+pub fn set_in_file(
+  content: &str,
+  path: &Path,
+  key: &str,
+  text: &str,
+) -> Result<String, ConfigError> {
+  let setting = Setting::of(key)?;
+  let value = setting
+    .value_from_text(text)
+    .map_err(|error| ConfigError::InvalidValue {
+      source: None,
+      key: setting.key,
+      error,
+    })?;
+  let mut document = parse_document(content, path)?;
+  let (section, name) = setting.key.split_once('.').expect("Keys have a section");
+  let value = match value {
+    toml::Value::Integer(number) => toml_edit::value(number),
+    value => toml_edit::value(value.as_str().unwrap_or_default()),
+  };
+  if !document.contains_table(section) {
+    document.insert(section, toml_edit::table());
+  }
+  document[section][name] = value;
+  Ok(document.to_string())
+}
+
+/// Returns the content of a config file without the key and whether it had the key. Unknown
+/// keys are removed too, so mistyped ones can be fixed.
+/// # Errors
+/// If the content is no valid TOML or the key is unknown and not in the content.
+pub fn unset_in_file(content: &str, path: &Path, key: &str) -> Result<(String, bool), ConfigError> {
+  let mut document = parse_document(content, path)?;
+  let removed = key.split_once('.').and_then(|(section, name)| {
+    document
+      .get_mut(section)
+      .and_then(toml_edit::Item::as_table_like_mut)
+      .and_then(|table| table.remove(name))
+  });
+  if removed.is_none() {
+    Setting::of(key)?;
+  }
+  Ok((document.to_string(), removed.is_some()))
+}
+
+fn parse_document(content: &str, path: &Path) -> Result<toml_edit::DocumentMut, ConfigError> {
+  content
+    .parse()
+    .map_err(|error: toml_edit::TomlError| ConfigError::InvalidToml {
+      source: ConfigSource::File(path.to_path_buf()),
+      error: error.message().to_string(),
+    })
+}
+
+impl Display for ConfigSource {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      ConfigSource::File(path) => write!(f, "{}", path.display()),
+      ConfigSource::Env(name) => write!(f, "${name}"),
+    }
+  }
+}
+
+impl Display for ConfigError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      ConfigError::InvalidToml { source, error } => write!(f, "{source}: Invalid TOML: {error}"),
+      ConfigError::UnknownKey { source, key } => {
+        if let Some(source) = source {
+          write!(f, "{source}: ")?;
+        }
+        write!(
+          f,
+          "{key}: Unknown key, see \"config list --all\" for all keys"
+        )
+      }
+      ConfigError::InvalidValue { source, key, error } => {
+        if let Some(source) = source {
+          write!(f, "{source}: ")?;
+        }
+        write!(f, "{key}: {error}")
+      }
+      ConfigError::NotSet(key) => write!(f, "{key}: Not set"),
+    }
+  }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_let_later_layers_win_per_key() {
+    let mut config = Config::default();
+    config
+      .merge_file(
+        "[tracking]\npolicy = \"refuse\"\n\n[display]\ncolor = \"never\"\n",
+        Path::new("user.toml"),
+      )
+      .unwrap();
+    config
+      .merge_file(
+        "[tracking]\npolicy = \"auto-pause\"\n",
+        Path::new("project.toml"),
+      )
+      .unwrap();
+    config
+      .merge_env(|name| (name == "STAMP_MEMBER_PORT").then(|| "8080".to_string()))
+      .unwrap();
+
+    assert_eq!(
+      Some("auto-pause"),
+      config.get("tracking.policy").and_then(|v| v.as_str())
+    );
+    assert_eq!(
+      Some(&ConfigSource::File(PathBuf::from("project.toml"))),
+      config.source("tracking.policy")
+    );
+    assert_eq!(
+      Some(ColorChoice::Never),
+      config.parsed("display.color", ColorChoice::from_name)
+    );
+    assert_eq!(Some(8080), config.integer("serve.port"));
+    assert_eq!(
+      Ok(RunningPolicy::AutoPause),
+      RunningPolicy::from_config(&config.to_toml())
+    );
+  }
+
+  #[test]
+  fn should_point_at_key_and_source_of_invalid_settings() {
+    let path = Path::new("config.toml");
+    let mut config = Config::default();
+
+    let unknown = config.merge_file("[tracking]\npolicyy = \"refuse\"\n", path);
+    let invalid = config.merge_file("[serve]\nport = 70000\n", path);
+    let from_env =
+      config.merge_env(|name| (name == "STAMP_MEMBER_POLICY").then(|| "one".to_string()));
+
+    assert_eq!(
+      "config.toml: tracking.policyy: Unknown key, see \"config list --all\" for all keys",
+      unknown.unwrap_err().to_string()
+    );
+    assert_eq!(
+      "config.toml: serve.port: 70000 is not from 1 to 65535",
+      invalid.unwrap_err().to_string()
+    );
+    assert_eq!(
+      "$STAMP_MEMBER_POLICY: tracking.policy: \"one\" is not a running policy, expected one of: \
+       concurrent, auto-pause, refuse",
+      from_env.unwrap_err().to_string()
+    );
+    assert_eq!(None, config.get("tracking.policy"));
+  }
+
+  #[test]
+  fn should_only_accept_absolute_data_path() {
+    let path = Path::new("config.toml");
+    let mut config = Config::default();
+
+    let relative = config.merge_file("[data]\npath = \"time.json\"\n", path);
+    config
+      .merge_file("[data]\npath = \"/home/me/time.json\"\n", path)
+      .unwrap();
+
+    assert_eq!(
+      "config.toml: data.path: \"time.json\" is not an absolute path",
+      relative.unwrap_err().to_string()
+    );
+    assert_eq!(
+      Some("/home/me/time.json"),
+      config.get("data.path").and_then(toml::Value::as_str)
+    );
+  }
+
+  #[test]
+  fn should_refuse_hooks_in_project_file() {
+    let content = "[tracking]\npolicy = \"refuse\"\n\n[hooks]\ncreate = \"curl evil.example\"\n";
+    let mut config = Config::default();
+
+    let refused = config.merge_project_file(content, Path::new(".stamp_member.toml"));
+
+    assert_eq!(
+      ".stamp_member.toml: hooks.create: Runs shell commands, so it can only be set in the user \
+       or profile config file",
+      refused.unwrap_err().to_string()
+    );
+    assert_eq!(None, config.get("tracking.policy"));
+    config
+      .merge_file(content, Path::new("config.toml"))
+      .unwrap();
+    assert!(config.get("hooks.create").is_some());
+  }
+
+  #[test]
+  fn should_refuse_data_path_in_project_file() {
+    let content = "[data]\npath = \"/home/someone/.bashrc\"\n";
+    let mut config = Config::default();
+
+    let refused = config.merge_project_file(content, Path::new(".stamp_member.toml"));
+
+    assert_eq!(
+      ".stamp_member.toml: data.path: Chooses the data file which is read and written, so it can \
+       only be set in the user or profile config file",
+      refused.unwrap_err().to_string()
+    );
+    assert_eq!(None, config.get("data.path"));
+  }
+
+  #[test]
+  fn should_set_and_unset_keys_keeping_comments() {
+    let path = Path::new("config.toml");
+    let content = "# Colours\n[theme]\nheader = \"bold\"\n";
+
+    let content = set_in_file(content, path, "tracking.policy", "refuse").unwrap();
+    let content = set_in_file(&content, path, "serve.port", "8080").unwrap();
+    let (content, removed) = unset_in_file(&content, path, "theme.header").unwrap();
+    let mistyped = unset_in_file("[serve]\nprot = 1\n", path, "serve.prot").unwrap();
+
+    assert!(removed);
+    assert_eq!(("[serve]\n".to_string(), true), mistyped);
+    assert!(unset_in_file("", path, "serve.prot").is_err());
+    assert_eq!(
+      "# Colours\n[theme]\n\n[tracking]\npolicy = \"refuse\"\n\n[serve]\nport = 8080\n",
+      content
+    );
+    assert!(matches!(
+      set_in_file(&content, path, "display.color", "sometimes"),
+      Err(ConfigError::InvalidValue {
+        key: "display.color",
+        ..
+      })
+    ));
+  }
+}
@@ -0,0 +1,236 @@
+//! Protocol of the optional daemon which keeps all time stamps and stop watches in memory and
+//! serves requests over a Unix socket. Every request and response is one line of JSON like:
+//! ```text
+//! {"request":"pause","kind":"time_stamp","title":"Coding"}
+//! {"response":"ok"}
+//! ```
+//! The client functions like [`load`] and [`lock`] let every invocation use the daemon if it
+//! runs. Without it, [`handle_request`] applies requests to the data file under its lock.
+#[cfg(unix)]
+mod client;
+
+use std::io::{self, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::time_entities::time_entities_controller::{
+  EntityKind, SwitchOutcome, TimeEntitiesController,
+};
+
+#[cfg(unix)]
+pub use self::client::{load, lock, save, send, send_to, switch, Transaction, CLIENT_TIMEOUT};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "request", rename_all = "snake_case")]
+pub enum DaemonRequest {
+  Ping,
+  /// Asks for all data as JSON like it is saved in the data file.
+  Load,
+  /// Asks for all data like load and keeps every other request waiting until the same
+  /// connection sends save or closes.
+  Lock,
+  /// Replaces all data with the given JSON.
+  Save {
+    data: String,
+  },
+  /// Starts a new time stamp now.
+  Start {
+    title: String,
+  },
+  Pause {
+    kind: EntityKind,
+    title: String,
+  },
+  Resume {
+    kind: EntityKind,
+    title: String,
+  },
+  Finish {
+    kind: EntityKind,
+    title: String,
+  },
+  Delete {
+    kind: EntityKind,
+    title: String,
+  },
+  Rename {
+    kind: EntityKind,
+    title: String,
+    new_title: String,
+  },
+  /// Sets the note, a blank one removes it.
+  Note {
+    kind: EntityKind,
+    title: String,
+    note: String,
+  },
+  /// Pauses or finishes all running time stamps and starts or resumes the one with the title.
+  Switch {
+    title: String,
+    finish: bool,
+  },
+  Shutdown,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "response", rename_all = "snake_case")]
+pub enum DaemonResponse {
+  Ok,
+  Data { data: String },
+  Switched { outcome: SwitchOutcome },
+  Error { message: String },
+}
+
+/// Applies the request to the entities. Returns the response and whether the entities changed
+/// and need to be persisted. Shutdown is answered with ok and left to the caller.
+pub fn handle_request(
+  entities: &mut TimeEntitiesController,
+  request: DaemonRequest,
+) -> (DaemonResponse, bool) {
+  let outcome = match request {
+    DaemonRequest::Ping | DaemonRequest::Shutdown => return (DaemonResponse::Ok, false),
+    DaemonRequest::Load | DaemonRequest::Lock => {
+      return match entities.to_json() {
+        Ok(data) => (DaemonResponse::Data { data }, false),
+        Err(error) => (error_response(error), false),
+      }
+    }
+    DaemonRequest::Save { data } => match TimeEntitiesController::from_json(&data) {
+      Ok(loaded) => {
+        *entities = loaded
+          .with_clock(entities.shared_clock())
+          .with_policy(entities.policy());
+        Ok(())
+      }
+      Err(error) => Err(error.to_string()),
+    },
+    DaemonRequest::Start { title } => entities
+      .add_new_time_stamp(title.trim())
+      .map_err(|error| error.to_string()),
+    DaemonRequest::Pause { kind, title } => entities
+      .pause(kind, &title)
+      .map(|_| ())
+      .map_err(|error| error.to_string()),
+    DaemonRequest::Resume { kind, title } => entities
+      .resume(kind, &title)
+      .map(|_| ())
+      .map_err(|error| error.to_string()),
+    DaemonRequest::Finish { kind, title } => entities
+      .finish(kind, &title)
+      .map(|_| ())
+      .map_err(|error| error.to_string()),
+    DaemonRequest::Delete { kind, title } => entities
+      .delete(kind, &title)
+      .map_err(|error| error.to_string()),
+    DaemonRequest::Rename {
+      kind,
+      title,
+      new_title,
+    } => entities
+      .rename(kind, &title, &new_title)
+      .map_err(|error| error.to_string()),
+    DaemonRequest::Note { kind, title, note } => entities
+      .set_note(kind, &title, Some(&note))
+      .map_err(|error| error.to_string()),
+    DaemonRequest::Switch { title, finish } => {
+      return match entities.switch_to(&title, finish) {
+        Ok(outcome) => (DaemonResponse::Switched { outcome }, true),
+        Err(error) => (error_response(error), false),
+      }
+    }
+  };
+
+  match outcome {
+    Ok(()) => (DaemonResponse::Ok, true),
+    Err(message) => (DaemonResponse::Error { message }, false),
+  }
+}
+
+fn error_response(error: impl std::fmt::Display) -> DaemonResponse {
+  DaemonResponse::Error {
+    message: error.to_string(),
+  }
+}
+
+/// Writes the message as one line of JSON.
+pub fn write_message<T: Serialize>(writer: &mut impl Write, message: &T) -> io::Result<()> {
+  let mut line = serde_json::to_string(message)?;
+  line.push('\n');
+  writer.write_all(line.as_bytes())?;
+  writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+
+  use chrono::{TimeZone, Utc};
+
+  use super::*;
+  use crate::{chrono_utility::clock::FixedClock, time_entities::time_stamp::TimeStamp};
+
+  #[test]
+  fn should_read_requests_as_tagged_json() {
+    let actual: DaemonRequest =
+      serde_json::from_str(r#"{"request":"pause","kind":"stop_watch","title":"Tea"}"#).unwrap();
+
+    assert_eq!(
+      DaemonRequest::Pause {
+        kind: EntityKind::StopWatch,
+        title: "Tea".to_string()
+      },
+      actual
+    );
+    assert_eq!(
+      r#"{"response":"error","message":"x"}"#,
+      serde_json::to_string(&error_response("x")).unwrap()
+    );
+  }
+
+  #[test]
+  fn should_only_report_change_on_successful_mutation() {
+    let started = Utc.ymd(2022, 10, 20).and_hms(9, 0, 0);
+    let mut entities =
+      TimeEntitiesController::new(vec![TimeStamp::with_started("Coding", started)], Vec::new())
+        .with_clock(Arc::new(FixedClock::new(started)));
+    let pause = DaemonRequest::Pause {
+      kind: EntityKind::TimeStamp,
+      title: "Coding".to_string(),
+    };
+
+    assert_eq!(
+      (DaemonResponse::Ok, true),
+      handle_request(&mut entities, pause.clone())
+    );
+    assert!(matches!(
+      handle_request(&mut entities, pause),
+      (DaemonResponse::Error { .. }, false)
+    ));
+    assert!(matches!(
+      handle_request(&mut entities, DaemonRequest::Load),
+      (DaemonResponse::Data { .. }, false)
+    ));
+  }
+
+  #[test]
+  fn should_rename_and_lock_without_change() {
+    let started = Utc.ymd(2022, 10, 20).and_hms(9, 0, 0);
+    let mut entities =
+      TimeEntitiesController::new(vec![TimeStamp::with_started("Coding", started)], Vec::new())
+        .with_clock(Arc::new(FixedClock::new(started)));
+    let rename = DaemonRequest::Rename {
+      kind: EntityKind::TimeStamp,
+      title: "Coding".to_string(),
+      new_title: "Review".to_string(),
+    };
+
+    assert_eq!(
+      (DaemonResponse::Ok, true),
+      handle_request(&mut entities, rename)
+    );
+    assert!(matches!(
+      handle_request(&mut entities, DaemonRequest::Lock),
+      (DaemonResponse::Data { data }, false) if data.contains("Review")
+    ));
+  }
+}
@@ -0,0 +1,146 @@
+//! Client side of the daemon protocol over its Unix socket.
+use std::{
+  io::{self, BufRead, BufReader},
+  os::unix::net::UnixStream,
+  time::Duration,
+};
+
+use crate::{
+  data_access::paths, profile::Profile, time_entities::time_entities_controller::SwitchOutcome,
+};
+
+use super::{write_message, DaemonRequest, DaemonResponse};
+
+/// How long a client waits for the answer of the daemon and the daemon for the next request of
+/// a client.
+pub const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Data of the daemon which keeps every other request waiting until it is saved or dropped.
+pub struct Transaction {
+  reader: BufReader<UnixStream>,
+  data: String,
+}
+
+impl Transaction {
+  /// All data as JSON like it is saved in the data file.
+  pub fn data(&self) -> &str {
+    &self.data
+  }
+
+  /// Hands the changed data given as JSON to the daemon, which saves it and lets the waiting
+  /// requests go on.
+  pub fn save(mut self, data: &str) -> io::Result<()> {
+    let request = DaemonRequest::Save {
+      data: data.to_string(),
+    };
+    match exchange(&mut self.reader, &request)? {
+      DaemonResponse::Ok => Ok(()),
+      other => Err(unexpected_response(other)),
+    }
+  }
+}
+
+/// Sends the request to the daemon of the selected profile and returns its response. Returns
+/// None if no daemon runs.
+/// # Errors
+/// If the daemon does not answer in time or its answer can not be read.
+pub fn send(request: &DaemonRequest) -> io::Result<Option<DaemonResponse>> {
+  send_to(&paths::selected_profile()?, request)
+}
+
+/// Sends the request to the daemon of the profile and returns its response. Returns None if no
+/// daemon runs.
+/// # Errors
+/// If the daemon does not answer in time or its answer can not be read.
+pub fn send_to(profile: &Profile, request: &DaemonRequest) -> io::Result<Option<DaemonResponse>> {
+  match connect(profile)? {
+    Some(mut reader) => Ok(Some(exchange(&mut reader, request)?)),
+    None => Ok(None),
+  }
+}
+
+/// Returns the connection to the daemon of the profile or None if no daemon runs.
+fn connect(profile: &Profile) -> io::Result<Option<BufReader<UnixStream>>> {
+  let path = paths::get_profile_socket_path(profile)?;
+  let stream = match UnixStream::connect(&path) {
+    Ok(stream) => stream,
+    // A socket file without a listening daemon is left over from a daemon which was killed.
+    Err(error)
+      if matches!(
+        error.kind(),
+        io::ErrorKind::NotFound | io::ErrorKind::ConnectionRefused
+      ) =>
+    {
+      return Ok(None)
+    }
+    Err(error) => return Err(error),
+  };
+  stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+  stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+  Ok(Some(BufReader::new(stream)))
+}
+
+/// Sends one request over the connection and reads its response.
+fn exchange(
+  reader: &mut BufReader<UnixStream>,
+  request: &DaemonRequest,
+) -> io::Result<DaemonResponse> {
+  write_message(&mut reader.get_ref(), request)?;
+  let mut line = String::new();
+  reader.read_line(&mut line)?;
+  Ok(serde_json::from_str(&line)?)
+}
+
+/// Returns all data of the profile as JSON from its daemon or None if it does not run.
+pub fn load(profile: &Profile) -> io::Result<Option<String>> {
+  match send_to(profile, &DaemonRequest::Load)? {
+    None => Ok(None),
+    Some(DaemonResponse::Data { data }) => Ok(Some(data)),
+    Some(other) => Err(unexpected_response(other)),
+  }
+}
+
+/// Returns all data of the profile from its daemon, which serves no other request until it is
+/// saved or dropped. Returns None if the daemon does not run.
+pub fn lock(profile: &Profile) -> io::Result<Option<Transaction>> {
+  let Some(mut reader) = connect(profile)? else {
+    return Ok(None);
+  };
+  match exchange(&mut reader, &DaemonRequest::Lock)? {
+    DaemonResponse::Data { data } => Ok(Some(Transaction { reader, data })),
+    other => Err(unexpected_response(other)),
+  }
+}
+
+/// Hands all data of the profile as JSON to its daemon. Returns false if it does not run.
+pub fn save(profile: &Profile, data: &str) -> io::Result<bool> {
+  let request = DaemonRequest::Save {
+    data: data.to_string(),
+  };
+  match send_to(profile, &request)? {
+    None => Ok(false),
+    Some(DaemonResponse::Ok) => Ok(true),
+    Some(other) => Err(unexpected_response(other)),
+  }
+}
+
+/// Lets the daemon switch to the time stamp with the title in one step. Returns None if no
+/// daemon runs.
+pub fn switch(title: &str, finish: bool) -> io::Result<Option<SwitchOutcome>> {
+  let request = DaemonRequest::Switch {
+    title: title.to_string(),
+    finish,
+  };
+  match send(&request)? {
+    None => Ok(None),
+    Some(DaemonResponse::Switched { outcome }) => Ok(Some(outcome)),
+    Some(other) => Err(unexpected_response(other)),
+  }
+}
+
+fn unexpected_response(response: DaemonResponse) -> io::Error {
+  match response {
+    DaemonResponse::Error { message } => io::Error::other(format!("Daemon: {message}")),
+    other => io::Error::other(format!("Daemon answered unexpectedly: {other:?}")),
+  }
+}
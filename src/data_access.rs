@@ -1,4 +1,8 @@
-use std::{fs, io, path::PathBuf};
+use std::{
+  fs::{self, File, OpenOptions, TryLockError},
+  io,
+  path::PathBuf,
+};
 
 pub mod paths;
 pub fn get_all_data(path: &PathBuf) -> io::Result<String> {
@@ -10,3 +14,36 @@ pub fn save_data(path: &PathBuf, data_json: &str) -> io::Result<()> {
   fs::write(path, data_json)?;
   Ok(())
 }
+
+/// Takes the exclusive lock of the file at the path, which is created if needed. Returns None if
+/// another process holds it. The lock is released when the returned file is dropped.
+pub fn try_lock(path: &PathBuf) -> io::Result<Option<File>> {
+  let file = OpenOptions::new()
+    .create(true)
+    .truncate(false)
+    .write(true)
+    .open(path)?;
+  match file.try_lock() {
+    Ok(()) => Ok(Some(file)),
+    Err(TryLockError::WouldBlock) => Ok(None),
+    Err(TryLockError::Error(error)) => Err(error),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_not_lock_file_twice() {
+    let path = std::env::temp_dir().join(format!("stamp_member_{}.lock", std::process::id()));
+
+    let lock = try_lock(&path).unwrap();
+    assert!(lock.is_some());
+    assert!(try_lock(&path).unwrap().is_none());
+    drop(lock);
+    assert!(try_lock(&path).unwrap().is_some());
+
+    fs::remove_file(&path).unwrap();
+  }
+}
@@ -1,22 +1,169 @@
 use std::{
+  collections::hash_map::DefaultHasher,
+  hash::{Hash, Hasher},
   io,
   path::{Path, PathBuf},
 };
 
+use crate::{config, profile::Profile};
+
 const DEV_PATH_RESOURCES: &str = "dev_resources";
 const NAME_DATA_FILE: &str = "data.json";
+const NAME_CONFIG_FILE: &str = "config.toml";
+const NAME_APP_CONFIG_DIR: &str = "stamp_member";
+const NAME_PROFILES_DIR: &str = "profiles";
+/// Environment variable with the path of the data file of the default profile.
+pub const DATA_PATH_ENV_VAR: &str = "STAMP_MEMBER_DATA_PATH";
+
+/// Returns the path of the data file of the selected profile.
 pub fn get_data_path() -> io::Result<PathBuf> {
+  get_profile_data_path(&selected_profile()?)
+}
+
+/// Returns the path of the data file of the profile. Other profiles than the default one lie
+/// in the profiles directory next to the data file of the default one.
+pub fn get_profile_data_path(profile: &Profile) -> io::Result<PathBuf> {
+  if profile.is_default() {
+    get_default_data_path()
+  } else {
+    Ok(get_profiles_data_dir()?.join(format!("{profile}.json")))
+  }
+}
+
+/// Returns the path of the file which is locked while the data file of the profile is changed.
+pub fn get_profile_lock_path(profile: &Profile) -> io::Result<PathBuf> {
+  let mut path = get_profile_data_path(profile)?.into_os_string();
+  path.push(".lock");
+  Ok(PathBuf::from(path))
+}
+
+/// Returns the directory of the data files of other profiles than the default one.
+pub fn get_profiles_data_dir() -> io::Result<PathBuf> {
+  let default_path = get_default_data_path()?;
+  Ok(
+    default_path
+      .parent()
+      .unwrap_or(Path::new(""))
+      .join(NAME_PROFILES_DIR),
+  )
+}
+
+/// Makes param path the data file of the default profile of this process and of the processes
+/// it starts, like hooks and daemons.
+pub fn select_data_path(path: &Path) {
+  std::env::set_var(DATA_PATH_ENV_VAR, path);
+}
+
+/// Returns the data path chosen via [`DATA_PATH_ENV_VAR`] or None if it is not set.
+fn chosen_data_path() -> Option<PathBuf> {
+  std::env::var_os(DATA_PATH_ENV_VAR)
+    .filter(|path| !path.is_empty())
+    .map(PathBuf::from)
+}
+
+fn get_default_data_path() -> io::Result<PathBuf> {
+  if let Some(path) = chosen_data_path() {
+    return Ok(path);
+  }
   if cfg!(debug_assertions) {
-    return Ok(get_dev_path_data());
+    Ok(get_dev_path_data())
   } else {
     let executable_path = std::env::current_exe()?;
-    return Ok(executable_path);
+    Ok(executable_path)
   }
 }
 
+/// Returns the profile selected via the environment.
+pub fn selected_profile() -> io::Result<Profile> {
+  Profile::selected()
+    .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error.to_string()))
+}
+
 fn get_dev_path_data() -> PathBuf {
   let project_path: &str = env!("CARGO_MANIFEST_DIR");
   Path::new(project_path)
     .join(DEV_PATH_RESOURCES)
     .join(NAME_DATA_FILE)
 }
+
+/// Returns the path of the config file. It lies under dev_resources in dev builds and in the
+/// XDG config directory, by default ~/.config/stamp_member, otherwise.
+pub fn get_config_path() -> io::Result<PathBuf> {
+  if cfg!(debug_assertions) {
+    let project_path: &str = env!("CARGO_MANIFEST_DIR");
+    return Ok(
+      Path::new(project_path)
+        .join(DEV_PATH_RESOURCES)
+        .join(NAME_CONFIG_FILE),
+    );
+  }
+
+  let config_dir = match std::env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+    Some(dir) => PathBuf::from(dir),
+    None => std::env::var_os("HOME")
+      .map(|home| PathBuf::from(home).join(".config"))
+      .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Home directory is unknown"))?,
+  };
+  Ok(config_dir.join(NAME_APP_CONFIG_DIR).join(NAME_CONFIG_FILE))
+}
+
+/// Returns the path of the config file of the profile. The one of the default profile is the
+/// user config file, the ones of others lie in the profiles directory next to it.
+pub fn get_profile_config_path(profile: &Profile) -> io::Result<PathBuf> {
+  let user_path = get_config_path()?;
+  if profile.is_default() {
+    return Ok(user_path);
+  }
+  Ok(
+    user_path
+      .parent()
+      .unwrap_or(Path::new(""))
+      .join(NAME_PROFILES_DIR)
+      .join(format!("{profile}.toml")),
+  )
+}
+
+/// Returns the path of the project config file in the directory start or the nearest parent
+/// directory which has one. None if no directory up to the root has one.
+pub fn find_project_config_path(start: &Path) -> Option<PathBuf> {
+  start
+    .ancestors()
+    .map(|directory| directory.join(config::PROJECT_CONFIG_FILE_NAME))
+    .find(|path| path.is_file())
+}
+
+/// Returns the path of the Unix socket of the daemon of the selected profile.
+pub fn get_socket_path() -> io::Result<PathBuf> {
+  get_profile_socket_path(&selected_profile()?)
+}
+
+/// Returns the path of the Unix socket of the daemon of the profile. It lies under
+/// dev_resources in dev builds and in $XDG_RUNTIME_DIR or else the temporary directory
+/// otherwise. A chosen data path gets a socket of its own, so no daemon serves the data of
+/// another path.
+pub fn get_profile_socket_path(profile: &Profile) -> io::Result<PathBuf> {
+  let mut name = if profile.is_default() {
+    NAME_APP_CONFIG_DIR.to_string()
+  } else {
+    format!("{NAME_APP_CONFIG_DIR}-{profile}")
+  };
+  if let Some(data_path) = chosen_data_path() {
+    let mut hasher = DefaultHasher::new();
+    data_path.hash(&mut hasher);
+    name.push_str(&format!("-{:016x}", hasher.finish()));
+  }
+  name.push_str(".sock");
+  if cfg!(debug_assertions) {
+    let project_path: &str = env!("CARGO_MANIFEST_DIR");
+    return Ok(Path::new(project_path).join(DEV_PATH_RESOURCES).join(name));
+  }
+
+  match std::env::var_os("XDG_RUNTIME_DIR").filter(|dir| !dir.is_empty()) {
+    Some(dir) => Ok(PathBuf::from(dir).join(name)),
+    // The temporary directory is shared by all users.
+    None => {
+      let user = std::env::var("USER").unwrap_or_default();
+      Ok(std::env::temp_dir().join(format!("{user}-{name}")))
+    }
+  }
+}
@@ -1,6 +1,154 @@
 use std::cmp::max;
+
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Width used if the width of the terminal is unknown, for example if output is piped.
+pub const DEFAULT_TERMINAL_WIDTH: usize = 80;
+
+/// Returns the number of columns of the terminal. Falls back to the environment variable
+/// COLUMNS and then to [`DEFAULT_TERMINAL_WIDTH`].
+pub fn terminal_width() -> usize {
+  if let Some((terminal_size::Width(width), _)) = terminal_size::terminal_size() {
+    return width as usize;
+  }
+
+  std::env::var("COLUMNS")
+    .ok()
+    .and_then(|columns| columns.trim().parse().ok())
+    .filter(|columns: &usize| *columns > 0)
+    .unwrap_or(DEFAULT_TERMINAL_WIDTH)
+}
+/// Spaces between columns of tables without borders.
+pub const DEFAULT_RIM_SPACES: usize = 2;
+/// Columns are not shrunk below this width to fit a table into its maximum width, so titles
+/// stay readable.
+const MIN_SHRUNK_COLUMN_WIDTH: usize = 16;
+const ELLIPSIS: char = '…';
+const ANSI_ESCAPE: char = '\x1b';
+const ANSI_RESET: &str = "\x1b[0m";
+
+/// Horizontal position of text within its column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Alignment {
+  #[default]
+  Left,
+  Right,
+  Center,
+}
+
+/// What happens to text which is wider than its column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Overflow {
+  /// Cuts the text and marks the cut with an ellipsis.
+  #[default]
+  Truncate,
+  /// Breaks the text into several lines, at spaces if possible.
+  Wrap,
+}
+
+/// Alignment and width of one column of a text table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColumnLayout {
+  alignment: Alignment,
+  max_width: Option<usize>,
+  /// Whether the column may be narrowed to fit the table into its maximum width.
+  shrinkable: bool,
+}
+
+/// Layout of a text table: columns, maximum width, overflow of wide text and borders.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableStyle {
+  rim_spaces: usize,
+  /// Layout by column index. Columns without a layout use [`ColumnLayout::default`].
+  columns: Vec<ColumnLayout>,
+  max_table_width: Option<usize>,
+  overflow: Overflow,
+  borders: bool,
+}
+
+impl ColumnLayout {
+  /// Column with param alignment, without maximum width and which may be shrunk.
+  pub fn new(alignment: Alignment) -> Self {
+    ColumnLayout {
+      alignment,
+      max_width: None,
+      shrinkable: true,
+    }
+  }
+
+  pub fn with_max_width(self, max_width: Option<usize>) -> Self {
+    ColumnLayout { max_width, ..self }
+  }
+
+  /// Returns this layout which keeps the width of the column if param shrinkable is false even
+  /// if the table is wider than its maximum width. Useful for moments and durations.
+  pub fn with_shrinkable(self, shrinkable: bool) -> Self {
+    ColumnLayout { shrinkable, ..self }
+  }
+}
+
+impl Default for ColumnLayout {
+  fn default() -> Self {
+    ColumnLayout::new(Alignment::Left)
+  }
+}
+
+impl TableStyle {
+  /// Table without borders whose columns are separated by param rim_spaces spaces.
+  pub fn new(rim_spaces: usize) -> Self {
+    TableStyle {
+      rim_spaces,
+      columns: Vec::new(),
+      max_table_width: None,
+      overflow: Overflow::Truncate,
+      borders: false,
+    }
+  }
+
+  pub fn with_columns(self, columns: Vec<ColumnLayout>) -> Self {
+    TableStyle { columns, ..self }
+  }
+
+  /// Shrinks the widest shrinkable columns until the table fits into param max_table_width.
+  pub fn with_max_table_width(self, max_table_width: Option<usize>) -> Self {
+    TableStyle {
+      max_table_width,
+      ..self
+    }
+  }
+
+  pub fn with_overflow(self, overflow: Overflow) -> Self {
+    TableStyle { overflow, ..self }
+  }
+
+  /// Draws box-drawing lines around every cell and below the first row as header.
+  pub fn with_borders(self, borders: bool) -> Self {
+    TableStyle { borders, ..self }
+  }
+
+  fn column(&self, index: usize) -> ColumnLayout {
+    self.columns.get(index).copied().unwrap_or_default()
+  }
+
+  /// Width taken by spaces and lines between and around columns.
+  fn separators_width(&self, number_columns: usize) -> usize {
+    if self.borders {
+      3 * number_columns + 1
+    } else {
+      self.rim_spaces * number_columns.saturating_sub(1)
+    }
+  }
+}
+
+impl Default for TableStyle {
+  fn default() -> Self {
+    TableStyle::new(DEFAULT_RIM_SPACES)
+  }
+}
+
 /// Formats vectors of string into one text in which every column has the
-/// same width.
+/// same width. Widths are measured as shown in a terminal, so wide characters like emoji and
+/// Japanese text take 2 columns. Lines end without trailing spaces.
 /// # Panics
 /// Assumes that every inner string vector has the same length
 /// # Example
@@ -12,8 +160,8 @@ use std::cmp::max;
 ///   vec!["X".repeat(2), "t".repeat(5)],
 /// ];
 /// let table = format_to_text_table(&test_data, 2);
-/// let expected = "XXXXXXXXXX  YYYYYYYYYYYYYYYYYYYY  
-/// XX          ttttt                 \n";
+/// let expected = "XXXXXXXXXX  YYYYYYYYYYYYYYYYYYYY
+/// XX          ttttt\n";
 /// assert_eq!(table, expected);
 ///
 /// // Output with println!(table);
@@ -21,37 +169,322 @@ use std::cmp::max;
 /// //XX          ttttt
 /// ```
 pub fn format_to_text_table(to_format: &[Vec<String>], rim_spaces: usize) -> String {
+  format_table(to_format, &TableStyle::new(rim_spaces))
+}
+
+/// Formats rows into a text table with the alignment, widths and borders of param style.
+/// # Panics
+/// Assumes that every inner string vector has the same length
+/// # Example
+/// ```
+/// use stamp_member::format_utils::{format_table, Alignment, ColumnLayout, TableStyle};
+///
+/// let rows = [
+///   vec!["Title".to_string(), "Active".to_string()],
+///   vec!["会議 📅".to_string(), "1:30".to_string()],
+/// ];
+/// let style = TableStyle::default()
+///   .with_columns(vec![ColumnLayout::default(), ColumnLayout::new(Alignment::Right)])
+///   .with_borders(true);
+///
+/// let expected = "\
+/// ┌─────────┬────────┐
+/// │ Title   │ Active │
+/// ├─────────┼────────┤
+/// │ 会議 📅 │   1:30 │
+/// └─────────┴────────┘
+/// ";
+/// assert_eq!(expected, format_table(&rows, &style));
+/// ```
+pub fn format_table(to_format: &[Vec<String>], style: &TableStyle) -> String {
+  if to_format.is_empty() {
+    return String::new();
+  }
   let mut column_width_vec = calc_max_column_width_for(to_format);
+  for (index, width) in column_width_vec.iter_mut().enumerate() {
+    if let Some(max_width) = style.column(index).max_width {
+      *width = (*width).min(max_width.max(1));
+    }
+  }
+  if let Some(max_table_width) = style.max_table_width {
+    shrink_to_fit(&mut column_width_vec, style, max_table_width);
+  }
 
+  let mut padded_widths = column_width_vec.clone();
   let mut table =
-    prepare_empty_table_enough_capacity(&mut column_width_vec, rim_spaces, to_format.len());
-  create_table_with_padding(&mut table, to_format, &column_width_vec);
+    prepare_empty_table_enough_capacity(&mut padded_widths, style.rim_spaces, to_format.len());
+  if style.borders {
+    table.push_str(&border_line(&column_width_vec, ['┌', '┬', '┐']));
+  }
+  for (index, row) in to_format.iter().enumerate() {
+    create_row_with_padding(&mut table, row, &column_width_vec, style);
+    if style.borders && index == 0 && to_format.len() > 1 {
+      table.push_str(&border_line(&column_width_vec, ['├', '┼', '┤']));
+    }
+  }
+  if style.borders {
+    table.push_str(&border_line(&column_width_vec, ['└', '┴', '┘']));
+  }
 
   table
 }
 
-fn create_table_with_padding(
+/// Narrows the widest shrinkable column by one until the table fits into param max_table_width.
+/// Nothing is shrunk if the table would not fit even with every shrinkable column at
+/// [`MIN_SHRUNK_COLUMN_WIDTH`], because cut text would not keep it from overflowing anyway.
+fn shrink_to_fit(column_width_vec: &mut [usize], style: &TableStyle, max_table_width: usize) {
+  let separators = style.separators_width(column_width_vec.len());
+  let narrowest: usize = column_width_vec
+    .iter()
+    .enumerate()
+    .map(|(index, width)| {
+      if style.column(index).shrinkable {
+        (*width).min(MIN_SHRUNK_COLUMN_WIDTH)
+      } else {
+        *width
+      }
+    })
+    .sum();
+  if narrowest + separators > max_table_width {
+    return;
+  }
+  while column_width_vec.iter().sum::<usize>() + separators > max_table_width {
+    let widest = column_width_vec
+      .iter()
+      .enumerate()
+      .filter(|(index, width)| style.column(*index).shrinkable && **width > MIN_SHRUNK_COLUMN_WIDTH)
+      .max_by_key(|(index, width)| (**width, std::cmp::Reverse(*index)))
+      .map(|(index, _)| index);
+    match widest {
+      Some(index) => column_width_vec[index] -= 1,
+      None => break,
+    }
+  }
+}
+
+/// Adds one line per line of the highest cell of the row. Cells wider than their column are
+/// truncated or wrapped.
+fn create_row_with_padding(
   table: &mut String,
-  to_format: &[Vec<String>],
-  column_width_vec: &Vec<usize>,
+  row: &[String],
+  column_width_vec: &[usize],
+  style: &TableStyle,
 ) {
-  for row in to_format {
-    for (column, content) in row.iter().enumerate() {
-      let current_column_len = content.chars().count();
-      let width_pad_to = column_width_vec[column];
-      let spaces_to_add = width_pad_to - current_column_len;
-      let padding = " ".repeat(spaces_to_add);
+  let cell_lines: Vec<Vec<String>> = row
+    .iter()
+    .zip(column_width_vec)
+    .map(|(content, width)| match style.overflow {
+      Overflow::Truncate => vec![truncate_to_width(content, *width)],
+      Overflow::Wrap => wrap_to_width(content, *width),
+    })
+    .collect();
+  let height = cell_lines.iter().map(Vec::len).max().unwrap_or(1);
 
-      table.push_str(content);
-      table.push_str(&padding);
+  for line_index in 0..height {
+    let cells: Vec<String> = cell_lines
+      .iter()
+      .enumerate()
+      .map(|(column, lines)| {
+        let content = lines.get(line_index).map_or("", String::as_str);
+        pad_to_width(
+          content,
+          column_width_vec[column],
+          style.column(column).alignment,
+        )
+      })
+      .collect();
+    if style.borders {
+      table.push_str(&format!("│ {} │", cells.join(" │ ")));
+    } else {
+      let line = cells.join(&" ".repeat(style.rim_spaces));
+      table.push_str(line.trim_end());
     }
     table.push('\n');
   }
 }
+
+fn pad_to_width(content: &str, width: usize, alignment: Alignment) -> String {
+  let spaces_to_add = width.saturating_sub(display_width(content));
+  let (left, right) = match alignment {
+    Alignment::Left => (0, spaces_to_add),
+    Alignment::Right => (spaces_to_add, 0),
+    Alignment::Center => (spaces_to_add / 2, spaces_to_add - spaces_to_add / 2),
+  };
+  format!("{}{content}{}", " ".repeat(left), " ".repeat(right))
+}
+
+fn border_line(column_width_vec: &[usize], [left, middle, right]: [char; 3]) -> String {
+  let segments: Vec<String> = column_width_vec
+    .iter()
+    .map(|width| "─".repeat(width + 2))
+    .collect();
+  format!("{left}{}{right}\n", segments.join(&middle.to_string()))
+}
+
+/// Returns the number of terminal columns the text takes. Wide characters like emoji and CJK
+/// take 2 columns, combining marks none. ANSI escape sequences like colours take no space.
+/// # Example
+/// ```
+/// use stamp_member::format_utils::display_width;
+///
+/// assert_eq!(4, display_width("会議"));
+/// assert_eq!(2, display_width("\x1b[31mhi\x1b[0m"));
+/// ```
+pub fn display_width(text: &str) -> usize {
+  if !text.contains(ANSI_ESCAPE) {
+    return text.width();
+  }
+  visible_parts(text)
+    .filter_map(|part| match part {
+      TextPart::Visible(visible) => Some(visible.width()),
+      TextPart::Escape(_) => None,
+    })
+    .sum()
+}
+
+/// Returns the text cut to param width terminal columns. A cut is marked with an ellipsis.
+/// # Example
+/// ```
+/// use stamp_member::format_utils::truncate_to_width;
+///
+/// assert_eq!("会…", truncate_to_width("会議の時間", 4));
+/// assert_eq!("short", truncate_to_width("short", 10));
+/// ```
+pub fn truncate_to_width(text: &str, width: usize) -> String {
+  if display_width(text) <= width {
+    return text.to_string();
+  }
+  if width == 0 {
+    return String::new();
+  }
+
+  let mut cut = String::new();
+  let mut used = 0;
+  let mut is_cut = false;
+  // Escape sequences after the cut are kept, so colours are still reset.
+  for part in visible_parts(text) {
+    match part {
+      TextPart::Escape(escape) => cut.push_str(escape),
+      TextPart::Visible(_) if is_cut => (),
+      TextPart::Visible(visible) => {
+        for letter in visible.chars() {
+          let letter_width = letter.width().unwrap_or(0);
+          if used + letter_width > width - 1 {
+            cut.push(ELLIPSIS);
+            is_cut = true;
+            break;
+          }
+          used += letter_width;
+          cut.push(letter);
+        }
+      }
+    }
+  }
+  cut
+}
+
+/// Breaks the text into lines of at most param width terminal columns. Lines are broken at
+/// spaces. Words wider than a line are broken anywhere but within escape sequences. Colours
+/// and other styles go on over the break, see [`carry_styles`].
+fn wrap_to_width(text: &str, width: usize) -> Vec<String> {
+  let width = width.max(1);
+  let mut lines: Vec<String> = Vec::new();
+  let mut line = String::new();
+  for word in text.split(' ').filter(|word| !word.is_empty()) {
+    let line_width = display_width(&line);
+    let word_width = display_width(word);
+    if !line.is_empty() && line_width + 1 + word_width <= width {
+      line.push(' ');
+      line.push_str(word);
+      continue;
+    }
+    if !line.is_empty() {
+      lines.push(std::mem::take(&mut line));
+    }
+    if word_width <= width {
+      line.push_str(word);
+      continue;
+    }
+    for part in visible_parts(word) {
+      match part {
+        TextPart::Escape(escape) => line.push_str(escape),
+        TextPart::Visible(visible) => {
+          for letter in visible.chars() {
+            let line_width = display_width(&line);
+            if line_width + letter.width().unwrap_or(0) > width && line_width > 0 {
+              lines.push(std::mem::take(&mut line));
+            }
+            line.push(letter);
+          }
+        }
+      }
+    }
+  }
+  if !line.is_empty() || lines.is_empty() {
+    lines.push(line);
+  }
+  carry_styles(lines)
+}
+
+/// Ends every line on which a style like a colour is still active with a reset and starts the
+/// next line with that style again, so it does not run into the cells next to the line.
+fn carry_styles(lines: Vec<String>) -> Vec<String> {
+  let mut active = String::new();
+  lines
+    .into_iter()
+    .map(|line| {
+      let mut styled = format!("{active}{line}");
+      for part in visible_parts(&line) {
+        match part {
+          TextPart::Escape(ANSI_RESET | "\x1b[m") => active.clear(),
+          TextPart::Escape(escape) if escape.ends_with('m') => active.push_str(escape),
+          _ => (),
+        }
+      }
+      if !active.is_empty() {
+        styled.push_str(ANSI_RESET);
+      }
+      styled
+    })
+    .collect()
+}
+
+/// Part of a text which is either shown or an ANSI escape sequence like a colour.
+enum TextPart<'a> {
+  Visible(&'a str),
+  Escape(&'a str),
+}
+
+/// Splits text into shown parts and ANSI escape sequences of the form ESC [ ... letter.
+fn visible_parts(text: &str) -> impl Iterator<Item = TextPart<'_>> {
+  let mut rest = text;
+  std::iter::from_fn(move || {
+    if rest.is_empty() {
+      return None;
+    }
+    if let Some(sequence) = rest.strip_prefix("\x1b[") {
+      let end = sequence
+        .find(|letter: char| ('@'..='~').contains(&letter))
+        .map_or(rest.len(), |at| at + 3);
+      let (escape, after) = rest.split_at(end);
+      rest = after;
+      return Some(TextPart::Escape(escape));
+    }
+    // Starts searching after the first letter which may be an escape without [.
+    let first_len = rest.chars().next().map_or(0, char::len_utf8);
+    let end = rest[first_len..]
+      .find(ANSI_ESCAPE)
+      .map_or(rest.len(), |at| at + first_len);
+    let (visible, after) = rest.split_at(end);
+    rest = after;
+    Some(TextPart::Visible(visible))
+  })
+}
+
 /// Adds rim_spaces as number to every row of given vec to account for spacing to right for a column.
 /// Creates empty string for enough capacity so no reallocation on heap is needed later.
 fn prepare_empty_table_enough_capacity(
-  column_width_vec: &mut Vec<usize>,
+  column_width_vec: &mut [usize],
   rim_spaces: usize,
   number_rows: usize,
 ) -> String {
@@ -69,6 +502,7 @@ fn prepare_empty_table_enough_capacity(
 }
 /// Calculates a vec with same length as a row of to_format 2d vec. Each index of the returned vec
 /// contains the max column width of the respective columns of every row of to_format vec param.
+/// Widths are measured with [`display_width`].
 /// # Errors
 /// Assumes every row of param to_format has the same len, number of columns.
 /// # Example
@@ -93,13 +527,71 @@ fn calc_max_column_width_for(to_format: &[Vec<String>]) -> Vec<usize> {
         row.len(),
         "One row is not as long as the others."
       );
-      let column_width = row[index].chars().count();
+      let column_width = display_width(&row[index]);
       max_width[index] = max(column_width, max_width[index]);
     }
   }
 
   max_width
 }
+/// Formats rows into comma separated values. Fields with commas, quotes or line breaks are
+/// quoted and quotes inside them are doubled.
+/// # Example
+/// ```
+/// use stamp_member::format_utils::format_to_csv;
+///
+/// let rows = [
+///   vec!["Title".to_string(), "Hours".to_string()],
+///   vec!["Meeting, weekly".to_string(), "1.50".to_string()],
+/// ];
+///
+/// assert_eq!("Title,Hours\n\"Meeting, weekly\",1.50\n", format_to_csv(&rows));
+/// ```
+pub fn format_to_csv(rows: &[Vec<String>]) -> String {
+  let mut csv = String::new();
+  for row in rows {
+    let fields: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+    csv.push_str(&fields.join(","));
+    csv.push('\n');
+  }
+  csv
+}
+
+/// Formats rows into a Markdown table. The first row is taken as header. Pipes inside fields
+/// are escaped.
+/// # Example
+/// ```
+/// use stamp_member::format_utils::format_to_markdown_table;
+///
+/// let rows = [
+///   vec!["Day".to_string(), "Billed".to_string()],
+///   vec!["2022-10-20".to_string(), "1.50".to_string()],
+/// ];
+///
+/// let expected = "| Day | Billed |\n| --- | --- |\n| 2022-10-20 | 1.50 |\n";
+/// assert_eq!(expected, format_to_markdown_table(&rows));
+/// ```
+pub fn format_to_markdown_table(rows: &[Vec<String>]) -> String {
+  let mut markdown = String::new();
+  for (index, row) in rows.iter().enumerate() {
+    let fields: Vec<String> = row.iter().map(|field| field.replace('|', "\\|")).collect();
+    markdown.push_str(&format!("| {} |\n", fields.join(" | ")));
+    if index == 0 {
+      let separators = vec!["---"; row.len()];
+      markdown.push_str(&format!("| {} |\n", separators.join(" | ")));
+    }
+  }
+  markdown
+}
+
+fn csv_field(field: &str) -> String {
+  if field.contains([',', '"', '\n', '\r']) {
+    format!("\"{}\"", field.replace('"', "\"\""))
+  } else {
+    field.to_string()
+  }
+}
+
 /// Returns text which is at least 2 digits wide. It is padded with leading zeros if needed.
 /// # Example
 /// ```
@@ -165,6 +657,104 @@ mod testing {
       .collect()
   }
 
+  #[test]
+  fn should_align_by_display_width_without_trailing_spaces() {
+    let input = [
+      to_string_vec(&["Title", "Active"]),
+      to_string_vec(&["会議", "1:30:00"]),
+      to_string_vec(&["Tea 🍵", "5:00"]),
+    ];
+    let style = TableStyle::default().with_columns(vec![
+      ColumnLayout::new(Alignment::Center),
+      ColumnLayout::new(Alignment::Right),
+    ]);
+
+    let actual = format_table(&input, &style);
+
+    assert_eq!(
+      "Title    Active\n 会議   1:30:00\nTea 🍵     5:00\n",
+      actual
+    );
+  }
+
+  #[test]
+  fn should_shrink_only_shrinkable_columns_to_fit_width() {
+    let input = [
+      to_string_vec(&["A very long title of a meeting", "01:30:00"]),
+      to_string_vec(&["Short", "00:05:00"]),
+    ];
+    let style = TableStyle::default()
+      .with_columns(vec![
+        ColumnLayout::default(),
+        ColumnLayout::new(Alignment::Right).with_shrinkable(false),
+      ])
+      .with_max_table_width(Some(28));
+
+    let actual = format_table(&input, &style);
+
+    assert_eq!(
+      "A very long title…  01:30:00\nShort               00:05:00\n",
+      actual
+    );
+  }
+
+  #[test]
+  fn should_not_shrink_if_table_overflows_anyway() {
+    let input = [to_string_vec(&[
+      "A very long title of a meeting",
+      "2022-10-20 09:00:00",
+    ])];
+    let style = TableStyle::default()
+      .with_columns(vec![
+        ColumnLayout::default(),
+        ColumnLayout::default().with_shrinkable(false),
+      ])
+      .with_max_table_width(Some(30));
+
+    let actual = format_table(&input, &style);
+
+    assert_eq!(
+      "A very long title of a meeting  2022-10-20 09:00:00\n",
+      actual
+    );
+  }
+
+  #[test]
+  fn should_wrap_at_spaces_and_break_long_words() {
+    assert_eq!(
+      vec!["Weekly", "team", "meeting"],
+      wrap_to_width("Weekly team meeting", 7)
+    );
+    assert_eq!(vec!["会議の", "時間"], wrap_to_width("会議の時間", 6));
+    assert_eq!(vec![""], wrap_to_width("", 5));
+  }
+
+  #[test]
+  fn should_style_every_wrapped_line_on_its_own() {
+    assert_eq!(
+      vec!["\x1b[32mWeekly\x1b[0m", "\x1b[32mteam\x1b[0m"],
+      wrap_to_width("\x1b[32mWeekly team\x1b[0m", 7)
+    );
+    assert_eq!(
+      vec!["\x1b[1;31mabc\x1b[0m", "\x1b[1;31mdef\x1b[0m"],
+      wrap_to_width("\x1b[1;31mabcdef\x1b[0m", 3)
+    );
+  }
+
+  #[test]
+  fn should_keep_colours_when_truncating() {
+    assert_eq!(
+      "\x1b[32mRunn…\x1b[0m",
+      truncate_to_width("\x1b[32mRunning\x1b[0m", 5)
+    );
+  }
+
+  #[test]
+  fn should_double_quotes_inside_csv_field() {
+    assert_eq!("\"Say \"\"hi\"\"\"", csv_field("Say \"hi\""));
+    assert_eq!("plain", csv_field("plain"));
+  }
+
   #[test]
   fn should_preallocate_enough_space_for_string() {
     let mut columns = vec![5, 6, 4];
@@ -0,0 +1,261 @@
+//! Commands of the user which run when time stamps or stop watches change. They are set in the
+//! hooks section of the config file as one command or a list of commands per event like:
+//! ```toml
+//! [hooks]
+//! create = "notify-send \"Started $STAMP_MEMBER_TITLE\""
+//! finish = ["~/bin/post-to-chat.sh", "loginctl lock-session"]
+//! expired = "paplay /usr/share/sounds/freedesktop/stereo/complete.oga"
+//! ```
+//! Events are create, pause, resume, finish and expired. Commands run in the shell in the
+//! background without input or output and get the entity via environment variables like
+//! STAMP_MEMBER_TITLE, see [`hook_environment`].
+use std::{
+  fmt::Display,
+  process::{Command, Stdio},
+  thread,
+};
+
+use chrono::Duration;
+use serde::Deserialize;
+
+use crate::time_entities::{
+  time_entities_controller::{EntityEvent, EntityEventKind, EntityKind, TimeEntitiesController},
+  TimeEntity,
+};
+
+const ENV_PREFIX: &str = "STAMP_MEMBER_";
+
+/// Commands per event.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Hooks {
+  #[serde(default)]
+  create: HookCommands,
+  #[serde(default)]
+  pause: HookCommands,
+  #[serde(default)]
+  resume: HookCommands,
+  #[serde(default)]
+  finish: HookCommands,
+  #[serde(default)]
+  expired: HookCommands,
+}
+
+/// One command or a list of commands.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+enum HookCommands {
+  One(String),
+  Many(Vec<String>),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct HookConfigError(String);
+
+/// Part of the config file which is about hooks. Other sections are ignored.
+#[derive(Deserialize, Default)]
+struct ConfigFile {
+  #[serde(default)]
+  hooks: Hooks,
+}
+
+impl Default for HookCommands {
+  fn default() -> Self {
+    HookCommands::Many(Vec::new())
+  }
+}
+
+impl HookCommands {
+  fn as_slice(&self) -> &[String] {
+    match self {
+      HookCommands::One(command) => std::slice::from_ref(command),
+      HookCommands::Many(commands) => commands,
+    }
+  }
+}
+
+impl Hooks {
+  /// Returns the hooks of the hooks section of the config file. No hooks if there is no section.
+  /// # Errors
+  /// If the config is no valid TOML or the hooks section has unknown events.
+  pub fn from_config(config: &str) -> Result<Self, HookConfigError> {
+    let config: ConfigFile =
+      toml::from_str(config).map_err(|error| HookConfigError(error.to_string()))?;
+    Ok(config.hooks)
+  }
+
+  pub fn commands(&self, event: EntityEventKind) -> &[String] {
+    match event {
+      EntityEventKind::Created => self.create.as_slice(),
+      EntityEventKind::Paused => self.pause.as_slice(),
+      EntityEventKind::Resumed => self.resume.as_slice(),
+      EntityEventKind::Finished => self.finish.as_slice(),
+      EntityEventKind::Expired => self.expired.as_slice(),
+    }
+  }
+
+  /// Starts the commands of every event without waiting for them. Failures to start a command
+  /// are printed to stderr.
+  pub fn run(&self, events: &[EntityEvent], entities: &TimeEntitiesController) {
+    for event in events {
+      let commands = self.commands(event.event);
+      if commands.is_empty() {
+        continue;
+      }
+      let environment = hook_environment(event, entities);
+      for command in commands {
+        match shell_command(command)
+          .envs(environment.iter().map(|(key, value)| (key, value)))
+          .stdin(Stdio::null())
+          .stdout(Stdio::null())
+          .stderr(Stdio::null())
+          .spawn()
+        {
+          // Waited for in the background so no zombie processes are left in long running modes.
+          Ok(mut child) => {
+            thread::spawn(move || child.wait());
+          }
+          Err(error) => eprintln!("Hook \"{command}\" could not be started: {error}"),
+        }
+      }
+    }
+  }
+}
+
+fn shell_command(command: &str) -> Command {
+  if cfg!(windows) {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+  } else {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+  }
+}
+
+/// Returns the environment variables of a hook about the event:
+/// STAMP_MEMBER_EVENT, STAMP_MEMBER_KIND (time_stamp or stop_watch), STAMP_MEMBER_TITLE,
+/// STAMP_MEMBER_STARTED in RFC 3339, STAMP_MEMBER_ACTIVE_SECONDS and if given
+/// STAMP_MEMBER_PROJECT. Stop watches also have STAMP_MEMBER_REMAINING_SECONDS and once
+/// expired STAMP_MEMBER_OVERRUN_SECONDS.
+pub fn hook_environment(
+  event: &EntityEvent,
+  entities: &TimeEntitiesController,
+) -> Vec<(String, String)> {
+  let kind = match event.kind {
+    EntityKind::TimeStamp => "time_stamp",
+    EntityKind::StopWatch => "stop_watch",
+  };
+  let mut environment = vec![
+    ("EVENT", event.event.name().to_string()),
+    ("KIND", kind.to_string()),
+    ("TITLE", event.title.clone()),
+  ];
+
+  let clock = entities.clock();
+  if let Some(time_stamp) = entities.time_stamp_of(event.kind, &event.title) {
+    environment.push(("STARTED", time_stamp.get_started().to_rfc3339()));
+    environment.push((
+      "ACTIVE_SECONDS",
+      time_stamp
+        .get_active_duration(clock)
+        .num_seconds()
+        .to_string(),
+    ));
+    if let Some(project) = time_stamp.get_project() {
+      environment.push(("PROJECT", project.to_string()));
+    }
+  }
+  let remaining = match event.kind {
+    EntityKind::TimeStamp => None,
+    EntityKind::StopWatch => entities
+      .stop_watches()
+      .iter()
+      .find(|stop_watch| stop_watch.get_title() == event.title)
+      .and_then(|stop_watch| stop_watch.get_remaining_duration(clock)),
+  };
+  if let Some(remaining) = remaining {
+    environment.push(("REMAINING_SECONDS", remaining.num_seconds().to_string()));
+    if remaining <= Duration::zero() {
+      environment.push(("OVERRUN_SECONDS", (-remaining).num_seconds().to_string()));
+    }
+  }
+
+  environment
+    .into_iter()
+    .map(|(key, value)| (format!("{ENV_PREFIX}{key}"), value))
+    .collect()
+}
+
+impl Display for HookConfigError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "Invalid hooks in config file: {}", self.0)
+  }
+}
+
+impl std::error::Error for HookConfigError {}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+
+  use chrono::{TimeZone, Utc};
+
+  use super::*;
+  use crate::{
+    chrono_utility::clock::FixedClock,
+    time_entities::{stop_watch::StopWatch, time_stamp::TimeStamp},
+  };
+
+  #[test]
+  fn should_read_one_or_many_commands_per_event() {
+    let config = "[theme]\nheader = \"bold\"\n\n[hooks]\nexpired = \"paplay bell.oga\"\n\
+                  finish = [\"a\", \"b\"]\n";
+
+    let hooks = Hooks::from_config(config).unwrap();
+
+    assert_eq!(
+      ["paplay bell.oga"],
+      hooks.commands(EntityEventKind::Expired)
+    );
+    assert_eq!(["a", "b"], hooks.commands(EntityEventKind::Finished));
+    assert!(hooks.commands(EntityEventKind::Created).is_empty());
+    assert!(Hooks::from_config("[hooks]\nstop = \"x\"\n").is_err());
+  }
+
+  #[test]
+  fn should_describe_expired_stop_watch_in_environment() {
+    let started = Utc.ymd(2022, 10, 20).and_hms(9, 0, 0);
+    let entities = TimeEntitiesController::new(
+      Vec::new(),
+      vec![StopWatch::new(
+        TimeStamp::with_started("Tea", started).with_project(Some("Breaks")),
+        Duration::minutes(4),
+      )],
+    )
+    .with_clock(Arc::new(FixedClock::new(started + Duration::minutes(5))));
+    let event = EntityEvent {
+      event: EntityEventKind::Expired,
+      kind: EntityKind::StopWatch,
+      title: "Tea".to_string(),
+    };
+
+    let environment = hook_environment(&event, &entities);
+
+    let expected: Vec<(String, String)> = [
+      ("STAMP_MEMBER_EVENT", "expired"),
+      ("STAMP_MEMBER_KIND", "stop_watch"),
+      ("STAMP_MEMBER_TITLE", "Tea"),
+      ("STAMP_MEMBER_STARTED", "2022-10-20T09:00:00+00:00"),
+      ("STAMP_MEMBER_ACTIVE_SECONDS", "300"),
+      ("STAMP_MEMBER_PROJECT", "Breaks"),
+      ("STAMP_MEMBER_REMAINING_SECONDS", "-60"),
+      ("STAMP_MEMBER_OVERRUN_SECONDS", "60"),
+    ]
+    .iter()
+    .map(|(key, value)| (key.to_string(), value.to_string()))
+    .collect();
+    assert_eq!(expected, environment);
+  }
+}
@@ -1,8 +1,17 @@
 pub mod app_command_impl;
 pub mod app_data_access;
 pub mod args_parser;
+pub mod billing;
 pub mod chrono_utility;
+pub mod completion;
+pub mod config;
+pub mod daemon;
 pub mod data_access;
 pub mod format_utils;
+pub mod hooks;
 pub mod macros;
+pub mod profile;
+pub mod report;
+pub mod status;
+pub mod theme;
 pub mod time_entities;
@@ -1,8 +1,9 @@
-use std::fmt::Display;
+use std::{fmt::Display, io, path::PathBuf};
 
 use clap::Parser;
-use stamp_member::args_parser::AppCommand;
-use stamp_member::{app_command_impl, app_data_access, data_access};
+use stamp_member::args_parser::{AppCommand, CliArgs};
+use stamp_member::chrono_utility::clock;
+use stamp_member::{app_command_impl, app_data_access, completion, data_access, profile::Profile};
 
 fn main() {
   normal_app_run();
@@ -23,15 +24,145 @@ fn initial_with_fake_dev_data() {
 }
 
 fn normal_app_run() {
-  let cli_args = AppCommand::parse();
+  let cli_args = CliArgs::parse();
+  let clock = match clock::clock_from_env() {
+    Ok(clock) => clock,
+    Err(error) => exit_with_err_message(&format!("{}: {error}", clock::NOW_ENV_VAR)),
+  };
+  // The profile is passed on via the environment, so the data access and the hooks and daemons
+  // started from here use it too.
+  if let Some(profile) = &cli_args.profile {
+    profile.select();
+  }
+  // Invalid settings are fixed via set and unset, so they are not required to be valid.
+  if let AppCommand::Config(args) = &cli_args.command {
+    match app_command_impl::config::manage_config(args) {
+      Ok(message) => println!("{message}"),
+      Err(error) => exit_with_err_message(&error),
+    }
+    return;
+  }
+  let config = match app_command_impl::load_config() {
+    Ok(config) => config,
+    Err(error) => exit_with_err_message(&error),
+  };
+  if let Some(profile) = config.parsed("profile.name", Profile::parse) {
+    profile.select();
+  }
+  if let Some(path) = config.parsed("data.path", |path| Ok::<_, ()>(PathBuf::from(path))) {
+    data_access::paths::select_data_path(&path);
+  }
+  // Titles are completed on every tab, so they are listed without loading display or theme,
+  // but from the data file of the configured profile and path.
+  if let AppCommand::CompleteTitles(args) = &cli_args.command {
+    match app_command_impl::complete_titles(args, &clock) {
+      Ok(titles) if titles.is_empty() => (),
+      Ok(titles) => println!("{titles}"),
+      Err(error) => exit_with_err_message(&error),
+    }
+    return;
+  }
+  let display = app_command_impl::date_time_display(&cli_args.display, &config);
+  // Status runs in prompts and bars on every redraw, so it does not load the theme.
+  if let AppCommand::Status(args) = &cli_args.command {
+    match app_command_impl::show_status(args, &config, &display, &clock) {
+      Ok(line) => println!("{line}"),
+      Err(error) => exit_with_err_message(&error),
+    }
+    return;
+  }
+  let theme = match app_command_impl::load_theme(
+    app_command_impl::color_choice(&cli_args.display, &config),
+    &config,
+  ) {
+    Ok(theme) => theme,
+    Err(error) => exit_with_err_message(&error),
+  };
 
-  match cli_args {
-    AppCommand::All => match app_command_impl::show_all_items() {
-      Ok(table) => println!("{table}"),
+  match cli_args.command {
+    AppCommand::All(args) => {
+      match app_command_impl::show_all_items(&args, &config, &display, &theme, &clock) {
+        Ok(table) => println!("{table}"),
+        Err(error) => exit_with_err_message(&error),
+      }
+    }
+    AppCommand::Live(args) => {
+      if let Err(error) =
+        app_command_impl::live::show_live(&args, &config, &display, &theme, &clock)
+      {
+        exit_with_err_message(&error)
+      }
+    }
+    AppCommand::Tui => {
+      if let Err(error) = app_command_impl::tui::run_tui(&config, &display, &theme, &clock) {
+        exit_with_err_message(&error)
+      }
+    }
+    #[cfg(unix)]
+    AppCommand::Daemon(args) => {
+      match app_command_impl::daemon::manage_daemon(&args, &config, &clock) {
+        Ok(message) => println!("{message}"),
+        Err(error) => exit_with_err_message(&error),
+      }
+    }
+    AppCommand::Serve(args) => {
+      if let Err(error) = app_command_impl::serve::serve(&args, &config, &clock) {
+        exit_with_err_message(&error)
+      }
+    }
+    AppCommand::Status(_) | AppCommand::CompleteTitles(_) | AppCommand::Config(_) => {
+      unreachable!("Handled before the theme is loaded")
+    }
+    AppCommand::Completions(args) => {
+      if let Err(error) = completion::write_completions(args.shell, &mut io::stdout()) {
+        exit_with_err_message(&error)
+      }
+    }
+    AppCommand::Pomodoro(args) => {
+      match app_command_impl::pomodoro::run_pomodoro(&args, &config, &display, &clock) {
+        Ok(message) => println!("{message}"),
+        Err(error) => exit_with_err_message(&error),
+      }
+    }
+    AppCommand::Time(args) => match app_command_impl::new_time_stamp_from_args(&args, &*clock) {
+      Ok(time_stamp) => match app_command_impl::add_time_stamp(time_stamp, &config, &clock) {
+        Ok(_) => println!("Time stamp created and created"),
+        Err(error) => exit_with_err_message(&error),
+      },
+      Err(error) => exit_with_err_message(&error),
+    },
+    AppCommand::Stop(args) => match app_command_impl::stop(&args, &config, &clock) {
+      Ok(message) => println!("{message}"),
+      Err(error) => exit_with_err_message(&error),
+    },
+    AppCommand::Resume(args) => match app_command_impl::resume(&args, &config, &clock) {
+      Ok(message) => println!("{message}"),
+      Err(error) => exit_with_err_message(&error),
+    },
+    AppCommand::Switch(args) => match app_command_impl::switch_to(&args, &config, &clock) {
+      Ok(message) => println!("{message}"),
       Err(error) => exit_with_err_message(&error),
     },
-    AppCommand::Time(args) => match app_command_impl::add_time_stamp_by_title(&args.name) {
-      Ok(_) => println!("Time stamp created and created"),
+    AppCommand::Report(args) => {
+      match app_command_impl::create_report(&args, &config, &display, &theme, &clock) {
+        Ok(report) => println!("{report}"),
+        Err(error) => exit_with_err_message(&error),
+      }
+    }
+    AppCommand::Timesheet(args) => {
+      match app_command_impl::create_timesheet(&args, &config, &display, &clock) {
+        Ok(timesheet) => println!("{timesheet}"),
+        Err(error) => exit_with_err_message(&error),
+      }
+    }
+    AppCommand::Profile(args) => {
+      match app_command_impl::profile::manage_profile(&args, &config, &clock) {
+        Ok(message) => println!("{message}"),
+        Err(error) => exit_with_err_message(&error),
+      }
+    }
+    AppCommand::Rate(args) => match app_command_impl::manage_project_rate(&args, &config, &clock) {
+      Ok(message) => println!("{message}"),
       Err(error) => exit_with_err_message(&error),
     },
     #[cfg(debug_assertions)]
@@ -44,7 +175,7 @@ fn normal_app_run() {
 /// Shows given messages as error to user and exits the program as failed via
 /// returned error code. Is used to react to errors not recoverable
 /// in cli without panic in production.
-fn exit_with_err_message<T: Display>(message: &T) {
+fn exit_with_err_message<T: Display>(message: &T) -> ! {
   eprintln!("Error: {}", message);
   std::process::exit(1);
 }
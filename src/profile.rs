@@ -0,0 +1,136 @@
+//! Separate data sets like work, personal or client-x. Every profile has its own data file, its
+//! own daemon and its own config file, whose settings win over the user config file. The
+//! default profile uses the data file and the user config file as they were before profiles.
+//!
+//! A profile is selected via `--profile`, the environment variable `STAMP_MEMBER_PROFILE` or
+//! `profile.name` in a config file, for example in the project config file of a client's
+//! directory.
+use std::fmt::Display;
+
+use crate::time_entities::time_entities_controller::EntityOperationError;
+
+pub const PROFILE_ENV_VAR: &str = "STAMP_MEMBER_PROFILE";
+pub const DEFAULT_PROFILE_NAME: &str = "default";
+const MAX_NAME_LEN: usize = 64;
+
+/// Name of a profile. It is used in file names, so it only has lowercase letters, digits, `-`
+/// and `_`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Profile(String);
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ProfileError {
+  InvalidName(String),
+  NotFound(String),
+  AlreadyExists(String),
+  /// The default profile has the data of users without profiles and is never deleted.
+  DeleteDefault,
+  /// Deleting it would delete this many time stamps and stop watches.
+  NotEmpty(String, usize),
+  /// The daemon of the profile runs and would save its entries again.
+  DaemonRunning(String),
+  SameProfile,
+  Move(EntityOperationError),
+}
+
+impl Profile {
+  /// # Errors
+  /// If the name is empty, too long or has other characters than lowercase letters, digits,
+  /// `-` and `_`.
+  pub fn parse(name: &str) -> Result<Self, ProfileError> {
+    let name = name.trim();
+    let is_valid = !name.is_empty()
+      && name.len() <= MAX_NAME_LEN
+      && name
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if is_valid {
+      Ok(Profile(name.to_string()))
+    } else {
+      Err(ProfileError::InvalidName(name.to_string()))
+    }
+  }
+
+  /// Returns the profile of the environment variable [`PROFILE_ENV_VAR`] or the default one if
+  /// it is not set.
+  /// # Errors
+  /// If the variable has an invalid name.
+  pub fn selected() -> Result<Self, ProfileError> {
+    match std::env::var(PROFILE_ENV_VAR) {
+      Ok(name) if !name.is_empty() => Profile::parse(&name),
+      _ => Ok(Profile::default()),
+    }
+  }
+
+  /// Makes this the selected profile of this process and of the processes it starts, like
+  /// hooks and daemons.
+  pub fn select(&self) {
+    std::env::set_var(PROFILE_ENV_VAR, &self.0);
+  }
+
+  pub fn name(&self) -> &str {
+    &self.0
+  }
+
+  pub fn is_default(&self) -> bool {
+    self.0 == DEFAULT_PROFILE_NAME
+  }
+}
+
+impl Default for Profile {
+  fn default() -> Self {
+    Profile(DEFAULT_PROFILE_NAME.to_string())
+  }
+}
+
+impl Display for Profile {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.0)
+  }
+}
+
+impl Display for ProfileError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      ProfileError::InvalidName(name) => write!(
+        f,
+        "\"{name}\" is not a profile name, use up to {MAX_NAME_LEN} lowercase letters, digits, \
+         - and _"
+      ),
+      ProfileError::NotFound(name) => write!(f, "Profile \"{name}\" does not exist"),
+      ProfileError::AlreadyExists(name) => write!(f, "Profile \"{name}\" already exists"),
+      ProfileError::DeleteDefault => write!(f, "The default profile can not be deleted"),
+      ProfileError::NotEmpty(name, entries) => write!(
+        f,
+        "Profile \"{name}\" has time stamps or stop watches ({entries} in total), delete them \
+         with it via --force"
+      ),
+      ProfileError::DaemonRunning(name) => write!(
+        f,
+        "The daemon of profile \"{name}\" runs, stop it first via \"--profile {name} daemon \
+         --stop\""
+      ),
+      ProfileError::SameProfile => write!(f, "Source and target are the same profile"),
+      ProfileError::Move(error) => write!(f, "{error}"),
+    }
+  }
+}
+
+impl std::error::Error for ProfileError {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_accept_names_usable_as_file_names() {
+    assert_eq!("client-x", Profile::parse(" client-x ").unwrap().name());
+    assert!(Profile::parse("default").unwrap().is_default());
+    for invalid in ["", "Work", "../work", "client x", &"a".repeat(65)] {
+      assert_eq!(
+        Err(ProfileError::InvalidName(invalid.trim().to_string())),
+        Profile::parse(invalid)
+      );
+    }
+  }
+}
@@ -0,0 +1,782 @@
+//! Summaries of the active time of time stamps and stop watches over a range of days.
+//! Active time is split at midnight in the time zone of the report. Time tracked over night
+//! therefore counts partly for both days.
+pub mod chart;
+pub mod timesheet;
+
+use std::{collections::BTreeMap, fmt::Display};
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use serde::Serialize;
+
+use crate::{
+  billing::{Amounts, HourlyRate, ProjectRates},
+  chrono_utility::{
+    clock::Clock,
+    date_time_display::{DateTimeDisplay, DisplayZone},
+    parsing::TimeParseError,
+    DateDifference,
+  },
+  format_utils,
+  time_entities::{time_stamp::TimeStamp, TimeEntity},
+};
+
+/// Format of days in reports like 2022-10-20.
+pub const DAY_FORMAT: &str = "%Y-%m-%d";
+/// Group of time stamps without a project.
+pub const NO_PROJECT: &str = "(no project)";
+/// Group of time stamps without any tag.
+pub const NO_TAG: &str = "(no tag)";
+const TOTAL: &str = "Total";
+const SECS_PER_HOUR: f64 = 3600.0;
+
+/// Days from the first to the last day, both included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReportRange {
+  first_day: NaiveDate,
+  last_day: NaiveDate,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ReportRangeError {
+  InvalidDay(TimeParseError),
+  FirstAfterLast {
+    first_day: NaiveDate,
+    last_day: NaiveDate,
+  },
+}
+
+/// By what the active time is summed up in a report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReportGrouping {
+  /// Title of time stamp or stop watch
+  #[default]
+  Entity,
+  Project,
+  /// A time stamp with several tags counts for every of its tags.
+  Tag,
+  Day,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReportOutput {
+  #[default]
+  Table,
+  Json,
+  Csv,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct UnknownReportOption {
+  name: String,
+  expected: &'static [&'static str],
+}
+
+/// Active time of one time stamp on one day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportEntry {
+  pub day: NaiveDate,
+  pub title: String,
+  pub project: Option<String>,
+  pub tags: Vec<String>,
+  pub active: Duration,
+  pub billable: bool,
+  /// Rate of the time stamp itself or of its project.
+  pub rate: Option<HourlyRate>,
+  /// Work cycles of pomodoros completed on the day.
+  pub pomodoros: u32,
+}
+
+/// Active time of one group like a project or a day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportRow {
+  pub key: String,
+  pub active: Duration,
+  /// Part of the active time which is billable.
+  pub billable: Duration,
+  /// Money for the billable time with a known rate.
+  pub amounts: Amounts,
+  pub pomodoros: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+  range: ReportRange,
+  entries: Vec<ReportEntry>,
+}
+
+#[derive(Serialize)]
+struct JsonReport<'a> {
+  from: String,
+  to: String,
+  group_by: &'a str,
+  rows: Vec<JsonReportRow>,
+  total_seconds: i64,
+  total_hours: f64,
+  total_billable_seconds: i64,
+  total_amounts: BTreeMap<String, String>,
+  total_pomodoros: u32,
+}
+
+#[derive(Serialize)]
+struct JsonReportRow {
+  key: String,
+  active_seconds: i64,
+  active_hours: f64,
+  billable_seconds: i64,
+  /// Amounts rounded to cents by currency as text, so no precision is lost.
+  amounts: BTreeMap<String, String>,
+  pomodoros: u32,
+}
+
+impl ReportRange {
+  /// # Errors
+  /// If the first day lies after the last day.
+  pub fn new(first_day: NaiveDate, last_day: NaiveDate) -> Result<Self, ReportRangeError> {
+    if first_day > last_day {
+      Err(ReportRangeError::FirstAfterLast {
+        first_day,
+        last_day,
+      })
+    } else {
+      Ok(ReportRange {
+        first_day,
+        last_day,
+      })
+    }
+  }
+
+  pub fn single_day(day: NaiveDate) -> Self {
+    ReportRange {
+      first_day: day,
+      last_day: day,
+    }
+  }
+
+  /// Returns the week from Monday to Sunday in which the given day lies.
+  pub fn week_of(day: NaiveDate) -> Self {
+    let monday = day - Duration::days(day.weekday().num_days_from_monday() as i64);
+    ReportRange {
+      first_day: monday,
+      last_day: monday + Duration::days(6),
+    }
+  }
+
+  pub fn first_day(&self) -> NaiveDate {
+    self.first_day
+  }
+
+  pub fn last_day(&self) -> NaiveDate {
+    self.last_day
+  }
+
+  pub fn days(&self) -> impl Iterator<Item = NaiveDate> {
+    let last_day = self.last_day;
+    std::iter::successors(Some(self.first_day), |day| day.succ_opt())
+      .take_while(move |day| *day <= last_day)
+  }
+}
+
+impl ReportGrouping {
+  pub const NAMES: [&'static str; 4] = ["entity", "project", "tag", "day"];
+
+  /// # Errors
+  /// If the name is not one of [`ReportGrouping::NAMES`].
+  pub fn from_name(name: &str) -> Result<Self, UnknownReportOption> {
+    match name.trim().to_lowercase().as_str() {
+      "entity" => Ok(ReportGrouping::Entity),
+      "project" => Ok(ReportGrouping::Project),
+      "tag" => Ok(ReportGrouping::Tag),
+      "day" => Ok(ReportGrouping::Day),
+      _ => Err(UnknownReportOption::new(name, &Self::NAMES)),
+    }
+  }
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      ReportGrouping::Entity => Self::NAMES[0],
+      ReportGrouping::Project => Self::NAMES[1],
+      ReportGrouping::Tag => Self::NAMES[2],
+      ReportGrouping::Day => Self::NAMES[3],
+    }
+  }
+
+  fn header(&self) -> &'static str {
+    match self {
+      ReportGrouping::Entity => "Title",
+      ReportGrouping::Project => "Project",
+      ReportGrouping::Tag => "Tag",
+      ReportGrouping::Day => "Day",
+    }
+  }
+}
+
+impl ReportOutput {
+  pub const NAMES: [&'static str; 3] = ["table", "json", "csv"];
+
+  /// # Errors
+  /// If the name is not one of [`ReportOutput::NAMES`].
+  pub fn from_name(name: &str) -> Result<Self, UnknownReportOption> {
+    match name.trim().to_lowercase().as_str() {
+      "table" => Ok(ReportOutput::Table),
+      "json" => Ok(ReportOutput::Json),
+      "csv" => Ok(ReportOutput::Csv),
+      _ => Err(UnknownReportOption::new(name, &Self::NAMES)),
+    }
+  }
+}
+
+impl UnknownReportOption {
+  fn new(name: &str, expected: &'static [&'static str]) -> Self {
+    UnknownReportOption {
+      name: name.to_string(),
+      expected,
+    }
+  }
+}
+
+impl ReportEntry {
+  /// Returns the groups this entry counts for.
+  fn keys(&self, grouping: ReportGrouping) -> Vec<String> {
+    match grouping {
+      ReportGrouping::Entity => vec![self.title.clone()],
+      ReportGrouping::Project => {
+        vec![self.project.as_deref().unwrap_or(NO_PROJECT).to_string()]
+      }
+      ReportGrouping::Tag if self.tags.is_empty() => vec![NO_TAG.to_string()],
+      ReportGrouping::Tag => self.tags.clone(),
+      ReportGrouping::Day => vec![self.day.format(DAY_FORMAT).to_string()],
+    }
+  }
+
+  /// Returns the active time if it is billable.
+  pub fn billable_duration(&self) -> Duration {
+    if self.billable {
+      self.active
+    } else {
+      Duration::zero()
+    }
+  }
+
+  /// Returns the money for the active time. Nothing for non-billable time or without a rate.
+  pub fn amounts(&self) -> Amounts {
+    let mut amounts = Amounts::default();
+    if let (true, Some(rate)) = (self.billable, &self.rate) {
+      amounts.add_for(rate, self.active);
+    }
+    amounts
+  }
+}
+
+impl ReportRow {
+  fn empty(key: String) -> Self {
+    ReportRow {
+      key,
+      active: Duration::zero(),
+      billable: Duration::zero(),
+      amounts: Amounts::default(),
+      pomodoros: 0,
+    }
+  }
+
+  fn add(&mut self, entry: &ReportEntry) {
+    self.active = self.active + entry.active;
+    self.billable = self.billable + entry.billable_duration();
+    self.amounts.merge(&entry.amounts());
+    self.pomodoros += entry.pomodoros;
+  }
+}
+
+impl Report {
+  /// Collects the active time and completed pomodoros of the given time stamps per day within
+  /// the range. Days begin at midnight in the given time zone. Active time is counted up to now
+  /// of param clock for time stamps which are not finished.
+  pub fn create<'a>(
+    time_stamps: impl IntoIterator<Item = &'a TimeStamp>,
+    range: ReportRange,
+    zone: DisplayZone,
+    clock: &dyn Clock,
+  ) -> Self {
+    let range_start = zone.start_of_day(range.first_day);
+    let range_end = range
+      .last_day
+      .succ_opt()
+      .map_or(DateTime::<Utc>::MAX_UTC, |after_last| {
+        zone.start_of_day(after_last)
+      });
+
+    let mut entries = Vec::new();
+    for time_stamp in time_stamps {
+      // Active time and completed pomodoros by day.
+      let mut per_day: BTreeMap<NaiveDate, (Duration, u32)> = BTreeMap::new();
+      for (from, to) in time_stamp.get_active_intervals(clock) {
+        let mut cursor = from.max(range_start);
+        let to = to.min(range_end);
+        while cursor < to {
+          let day = zone.date_of(&cursor);
+          let next_day_start = day.succ_opt().map(|next_day| zone.start_of_day(next_day));
+          let piece_end = match next_day_start {
+            Some(next_day_start) if next_day_start > cursor => next_day_start.min(to),
+            _ => to,
+          };
+          let (active, _) = per_day.entry(day).or_insert_with(|| (Duration::zero(), 0));
+          *active = *active + (piece_end - cursor);
+          cursor = piece_end;
+        }
+      }
+      for completed in time_stamp.get_pomodoros() {
+        if (range_start..range_end).contains(completed) {
+          let day = zone.date_of(completed);
+          per_day
+            .entry(day)
+            .or_insert_with(|| (Duration::zero(), 0))
+            .1 += 1;
+        }
+      }
+
+      entries.extend(
+        per_day
+          .into_iter()
+          .map(|(day, (active, pomodoros))| ReportEntry {
+            day,
+            title: time_stamp.get_title().to_string(),
+            project: time_stamp.get_project().map(str::to_string),
+            tags: time_stamp.get_tags().to_vec(),
+            active,
+            billable: time_stamp.is_billable(),
+            rate: time_stamp.get_rate().cloned(),
+            pomodoros,
+          }),
+      );
+    }
+    entries.sort_by(|left, right| (left.day, &left.title).cmp(&(right.day, &right.title)));
+
+    Report { range, entries }
+  }
+
+  /// Returns this report in which entries without their own rate take the rate of their
+  /// project.
+  pub fn with_project_rates(self, rates: &ProjectRates) -> Self {
+    let entries = self
+      .entries
+      .into_iter()
+      .map(|entry| match (&entry.rate, &entry.project) {
+        (None, Some(project)) => ReportEntry {
+          rate: rates.get(project).cloned(),
+          ..entry
+        },
+        _ => entry,
+      })
+      .collect();
+    Report { entries, ..self }
+  }
+
+  pub fn range(&self) -> ReportRange {
+    self.range
+  }
+
+  pub fn entries(&self) -> &[ReportEntry] {
+    &self.entries
+  }
+
+  /// Sum of all active time in the range. Time of a time stamp with several tags is only
+  /// counted once.
+  pub fn total(&self) -> Duration {
+    self
+      .entries
+      .iter()
+      .fold(Duration::zero(), |sum, entry| sum + entry.active)
+  }
+
+  pub fn total_billable(&self) -> Duration {
+    self.entries.iter().fold(Duration::zero(), |sum, entry| {
+      sum + entry.billable_duration()
+    })
+  }
+
+  pub fn total_pomodoros(&self) -> u32 {
+    self.entries.iter().map(|entry| entry.pomodoros).sum()
+  }
+
+  /// Money for all billable time with a known rate by currency.
+  pub fn total_amounts(&self) -> Amounts {
+    let mut amounts = Amounts::default();
+    for entry in &self.entries {
+      amounts.merge(&entry.amounts());
+    }
+    amounts
+  }
+
+  /// Returns the active time and money per group sorted by the name of the group. Grouped by
+  /// day, every day of the range is returned even without any active time.
+  pub fn group_by(&self, grouping: ReportGrouping) -> Vec<ReportRow> {
+    let mut groups: BTreeMap<String, ReportRow> = BTreeMap::new();
+    if grouping == ReportGrouping::Day {
+      for day in self.range.days() {
+        let key = day.format(DAY_FORMAT).to_string();
+        groups.insert(key.clone(), ReportRow::empty(key));
+      }
+    }
+
+    for entry in &self.entries {
+      for key in entry.keys(grouping) {
+        groups
+          .entry(key.clone())
+          .or_insert_with(|| ReportRow::empty(key))
+          .add(entry);
+      }
+    }
+
+    groups.into_values().collect()
+  }
+
+  /// Returns the report in the given output. Durations in tables are shown in the duration
+  /// format of param display.
+  /// # Errors
+  /// If the report can not be serialized to json.
+  pub fn render(
+    &self,
+    grouping: ReportGrouping,
+    output: ReportOutput,
+    display: &DateTimeDisplay,
+  ) -> Result<String, serde_json::Error> {
+    match output {
+      ReportOutput::Table => Ok(self.to_text_table(grouping, display)),
+      ReportOutput::Json => self.to_json(grouping),
+      ReportOutput::Csv => Ok(self.to_csv(grouping)),
+    }
+  }
+
+  /// Returns a table with one row per group and the total in the last row. The column for
+  /// amounts is only shown if any time has a rate and the one for pomodoros if any were
+  /// completed.
+  pub fn to_text_table(&self, grouping: ReportGrouping, display: &DateTimeDisplay) -> String {
+    let format_duration =
+      |duration: Duration| display.format_duration(&DateDifference::from(duration));
+    let total_amounts = self.total_amounts();
+    let with_amounts = !total_amounts.is_empty();
+    let total_pomodoros = self.total_pomodoros();
+    let with_pomodoros = total_pomodoros > 0;
+    let to_columns =
+      |key: String, active: Duration, billable: Duration, amounts: &Amounts, pomodoros: u32| {
+        let mut columns = vec![key, format_duration(active), format_duration(billable)];
+        if with_amounts {
+          columns.push(amounts.to_string());
+        }
+        if with_pomodoros {
+          columns.push(pomodoros.to_string());
+        }
+        columns
+      };
+
+    let mut headers = vec![
+      grouping.header().to_string(),
+      "Active".to_string(),
+      "Billable".to_string(),
+    ];
+    if with_amounts {
+      headers.push("Amount".to_string());
+    }
+    if with_pomodoros {
+      headers.push("Pomodoros".to_string());
+    }
+    let mut rows = vec![headers];
+    rows.extend(self.group_by(grouping).into_iter().map(|row| {
+      to_columns(
+        row.key,
+        row.active,
+        row.billable,
+        &row.amounts,
+        row.pomodoros,
+      )
+    }));
+    rows.push(to_columns(
+      TOTAL.to_string(),
+      self.total(),
+      self.total_billable(),
+      &total_amounts,
+      total_pomodoros,
+    ));
+
+    format!(
+      "Report from {} to {}:\n{}",
+      self.range.first_day.format(DAY_FORMAT),
+      self.range.last_day.format(DAY_FORMAT),
+      format_utils::format_to_text_table(&rows, 2)
+    )
+  }
+
+  /// Returns the rows with active and billable time in seconds, the amounts and the completed
+  /// pomodoros.
+  /// # Errors
+  /// If the report can not be serialized.
+  pub fn to_json(&self, grouping: ReportGrouping) -> Result<String, serde_json::Error> {
+    let total = self.total();
+    let json_report = JsonReport {
+      from: self.range.first_day.format(DAY_FORMAT).to_string(),
+      to: self.range.last_day.format(DAY_FORMAT).to_string(),
+      group_by: grouping.name(),
+      rows: self
+        .group_by(grouping)
+        .into_iter()
+        .map(|row| JsonReportRow {
+          key: row.key,
+          active_seconds: row.active.num_seconds(),
+          active_hours: rounded_hours(row.active),
+          billable_seconds: row.billable.num_seconds(),
+          amounts: amounts_as_text(&row.amounts),
+          pomodoros: row.pomodoros,
+        })
+        .collect(),
+      total_seconds: total.num_seconds(),
+      total_hours: rounded_hours(total),
+      total_billable_seconds: self.total_billable().num_seconds(),
+      total_amounts: amounts_as_text(&self.total_amounts()),
+      total_pomodoros: self.total_pomodoros(),
+    };
+
+    serde_json::to_string_pretty(&json_report)
+  }
+
+  /// Returns one line per group with active time in seconds and in hours, billable time and
+  /// amounts. No total is included so the lines can be summed up in a spreadsheet.
+  pub fn to_csv(&self, grouping: ReportGrouping) -> String {
+    let mut rows = vec![vec![
+      grouping.name().to_string(),
+      "active_seconds".to_string(),
+      "active_hours".to_string(),
+      "billable_seconds".to_string(),
+      "amounts".to_string(),
+    ]];
+    rows.extend(self.group_by(grouping).into_iter().map(|row| {
+      vec![
+        row.key,
+        row.active.num_seconds().to_string(),
+        format!("{:.2}", rounded_hours(row.active)),
+        row.billable.num_seconds().to_string(),
+        row.amounts.to_string(),
+      ]
+    }));
+
+    format_utils::format_to_csv(&rows)
+  }
+}
+
+fn amounts_as_text(amounts: &Amounts) -> BTreeMap<String, String> {
+  amounts
+    .rounded()
+    .into_iter()
+    .map(|(currency, amount)| (currency, amount.to_string()))
+    .collect()
+}
+
+/// Hours with at most 2 decimal places like 1.75.
+fn rounded_hours(duration: Duration) -> f64 {
+  (duration.num_seconds() as f64 / SECS_PER_HOUR * 100.0).round() / 100.0
+}
+
+impl Display for ReportRangeError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      ReportRangeError::InvalidDay(error) => write!(f, "{error}"),
+      ReportRangeError::FirstAfterLast {
+        first_day,
+        last_day,
+      } => write!(
+        f,
+        "First day {} of report lies after its last day {}",
+        first_day.format(DAY_FORMAT),
+        last_day.format(DAY_FORMAT)
+      ),
+    }
+  }
+}
+
+impl std::error::Error for ReportRangeError {}
+
+impl From<TimeParseError> for ReportRangeError {
+  fn from(error: TimeParseError) -> Self {
+    ReportRangeError::InvalidDay(error)
+  }
+}
+
+impl Display for UnknownReportOption {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(
+      f,
+      "\"{}\" is not known, expected one of: {}",
+      self.name,
+      self.expected.join(", ")
+    )
+  }
+}
+
+impl std::error::Error for UnknownReportOption {}
+
+#[cfg(test)]
+mod tests {
+  use chrono::TimeZone;
+  use chrono_tz::Tz;
+
+  use super::*;
+  use crate::chrono_utility::clock::{FixedClock, ManualClock};
+
+  pub(super) const BERLIN: DisplayZone = DisplayZone::Named(Tz::Europe__Berlin);
+
+  fn day(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd(2022, 10, day)
+  }
+
+  /// Moment in Berlin summer time given in UTC.
+  pub(super) fn berlin(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+    Tz::Europe__Berlin
+      .ymd(2022, 10, day)
+      .and_hms(hour, minute, 0)
+      .with_timezone(&Utc)
+  }
+
+  /// Time stamp which ran from param started to param ended without pauses.
+  pub(super) fn finished(title: &str, started: DateTime<Utc>, ended: DateTime<Utc>) -> TimeStamp {
+    let mut time_stamp = TimeStamp::with_started(title, started);
+    time_stamp.finish(&FixedClock::new(ended)).unwrap();
+    time_stamp
+  }
+
+  #[test]
+  fn should_return_week_from_monday_to_sunday() {
+    // 2022-10-20 is a Thursday.
+    let week = ReportRange::week_of(day(20));
+
+    assert_eq!(day(17), week.first_day());
+    assert_eq!(day(23), week.last_day());
+    assert_eq!(7, week.days().count());
+  }
+
+  #[test]
+  fn should_split_active_time_at_local_midnight() {
+    let night_shift = finished("Night shift", berlin(20, 22, 0), berlin(21, 2, 30));
+    let range = ReportRange::new(day(20), day(21)).unwrap();
+
+    let report = Report::create(
+      [&night_shift],
+      range,
+      BERLIN,
+      &FixedClock::new(berlin(22, 0, 0)),
+    );
+
+    let actual: Vec<(String, Duration)> = report
+      .group_by(ReportGrouping::Day)
+      .into_iter()
+      .map(|row| (row.key, row.active))
+      .collect();
+
+    assert_eq!(
+      vec![
+        ("2022-10-20".to_string(), Duration::hours(2)),
+        ("2022-10-21".to_string(), Duration::minutes(150)),
+      ],
+      actual
+    );
+  }
+
+  #[test]
+  fn should_only_count_time_within_range_without_pauses() {
+    let mut with_pause = TimeStamp::with_started("Paused", berlin(20, 23, 0));
+    let clock = ManualClock::new(berlin(21, 1, 0));
+    with_pause.pause(&clock).unwrap();
+    clock.set(berlin(21, 9, 0));
+    with_pause.resume(&clock).unwrap();
+    clock.set(berlin(21, 10, 0));
+
+    let report = Report::create(
+      [&with_pause],
+      ReportRange::single_day(day(21)),
+      BERLIN,
+      &clock,
+    );
+
+    assert_eq!(Duration::hours(2), report.total());
+  }
+
+  #[test]
+  fn should_group_by_project_and_every_tag() {
+    let clock = FixedClock::new(berlin(21, 0, 0));
+    let coding = finished("Coding", berlin(20, 9, 0), berlin(20, 11, 0))
+      .with_project(Some("Shop"))
+      .with_tags(&["dev", "billable"]);
+    let meeting =
+      finished("Meeting", berlin(20, 14, 0), berlin(20, 14, 30)).with_tags(&["billable"]);
+    let report = Report::create(
+      [&coding, &meeting],
+      ReportRange::single_day(day(20)),
+      BERLIN,
+      &clock,
+    );
+
+    let by_project: Vec<(String, i64)> = report
+      .group_by(ReportGrouping::Project)
+      .into_iter()
+      .map(|row| (row.key, row.active.num_minutes()))
+      .collect();
+    let by_tag: Vec<(String, i64)> = report
+      .group_by(ReportGrouping::Tag)
+      .into_iter()
+      .map(|row| (row.key, row.active.num_minutes()))
+      .collect();
+
+    assert_eq!(
+      vec![(NO_PROJECT.to_string(), 30), ("Shop".to_string(), 120)],
+      by_project
+    );
+    assert_eq!(
+      vec![("billable".to_string(), 150), ("dev".to_string(), 120)],
+      by_tag
+    );
+    assert_eq!(Duration::minutes(150), report.total());
+  }
+
+  #[test]
+  fn should_output_csv_and_json_with_hours() {
+    let clock = FixedClock::new(berlin(21, 0, 0));
+    let coding = finished("Coding, backend", berlin(20, 9, 0), berlin(20, 10, 45));
+    let report = Report::create([&coding], ReportRange::single_day(day(20)), BERLIN, &clock);
+
+    assert_eq!(
+      "entity,active_seconds,active_hours,billable_seconds,amounts\n\"Coding, backend\",6300,1.75,6300,\n",
+      report.to_csv(ReportGrouping::Entity)
+    );
+    let json: serde_json::Value =
+      serde_json::from_str(&report.to_json(ReportGrouping::Entity).unwrap()).unwrap();
+    assert_eq!(6300, json["total_seconds"]);
+    assert_eq!("2022-10-20", json["from"]);
+    assert_eq!(1.75, json["rows"][0]["active_hours"]);
+  }
+
+  #[test]
+  fn should_bill_with_own_rate_before_project_rate() {
+    let clock = FixedClock::new(berlin(21, 0, 0));
+    let coding =
+      finished("Coding", berlin(20, 9, 0), berlin(20, 10, 30)).with_project(Some("Shop"));
+    let rush = finished("Rush", berlin(20, 11, 0), berlin(20, 11, 30))
+      .with_project(Some("Shop"))
+      .with_rate(Some(HourlyRate::parse("150 EUR").unwrap()));
+    let meeting = finished("Meeting", berlin(20, 14, 0), berlin(20, 15, 0))
+      .with_project(Some("Shop"))
+      .with_billable(false);
+    let rates = ProjectRates::from([("Shop".to_string(), HourlyRate::parse("90 EUR").unwrap())]);
+
+    let report = Report::create(
+      [&coding, &rush, &meeting],
+      ReportRange::single_day(day(20)),
+      BERLIN,
+      &clock,
+    )
+    .with_project_rates(&rates);
+
+    let shop = &report.group_by(ReportGrouping::Project)[0];
+    assert_eq!(Duration::hours(3), shop.active);
+    assert_eq!(Duration::hours(2), shop.billable);
+    // 1.5 hours for 90 EUR and 0.5 hours for 150 EUR.
+    assert_eq!("210.00 EUR", shop.amounts.to_string());
+  }
+}
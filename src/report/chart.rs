@@ -0,0 +1,329 @@
+//! Charts of reports drawn with unicode blocks for the terminal: horizontal bars per group and
+//! a heatmap of active time per day like the contribution graph of GitHub.
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Duration, NaiveDate};
+
+use crate::{
+  chrono_utility::{date_time_display::DateTimeDisplay, DateDifference},
+  format_utils,
+};
+
+use super::{Report, ReportGrouping, ReportRange, ReportRow};
+
+const FULL_BLOCK: char = '█';
+/// Blocks from one eighth to seven eighths of a character wide.
+const PARTIAL_BLOCKS: [char; 7] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+const EIGHTHS_PER_CHAR: i64 = 8;
+/// Cells of the heatmap from no active time to the most active time per day.
+const HEAT_LEVELS: [char; 5] = ['·', '░', '▒', '▓', '█'];
+/// ANSI 256 colours of the heat levels, from grey to bright green.
+const HEAT_COLORS: [u8; 5] = [240, 22, 28, 34, 46];
+/// ANSI 256 colour of bars.
+const BAR_COLOR: u8 = 33;
+const ANSI_RESET: &str = "\x1b[0m";
+const WEEKDAY_LABELS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+/// Labels of bars are cut to this many terminal columns.
+const MAX_LABEL_WIDTH: usize = 24;
+const MIN_BAR_WIDTH: usize = 10;
+const COLUMN_GAP: &str = "  ";
+/// Every week of the heatmap takes a cell and a space.
+const HEATMAP_CELL_WIDTH: usize = 2;
+const HEATMAP_LABEL_WIDTH: usize = 4;
+
+/// Width and colouring of charts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChartStyle {
+  width: usize,
+  colored: bool,
+}
+
+impl ChartStyle {
+  /// Charts fit into param width characters and are not coloured.
+  pub fn new(width: usize) -> Self {
+    ChartStyle {
+      width,
+      colored: false,
+    }
+  }
+
+  /// Returns this style which colours charts with ANSI escape codes if param colored is true.
+  pub fn with_color(self, colored: bool) -> Self {
+    ChartStyle { colored, ..self }
+  }
+
+  fn paint(&self, text: &str, color: u8) -> String {
+    if self.colored {
+      format!("\x1b[38;5;{color}m{text}{ANSI_RESET}")
+    } else {
+      text.to_string()
+    }
+  }
+}
+
+impl Report {
+  /// Returns a bar per group of param grouping and a heatmap of the active time per day.
+  pub fn to_chart(
+    &self,
+    grouping: ReportGrouping,
+    display: &DateTimeDisplay,
+    style: &ChartStyle,
+  ) -> String {
+    format!(
+      "Report from {} to {}:\n\n{}\n{}",
+      self.range.first_day.format(super::DAY_FORMAT),
+      self.range.last_day.format(super::DAY_FORMAT),
+      bar_chart(&self.group_by(grouping), display, style),
+      heatmap(self, display, style)
+    )
+  }
+}
+
+/// Returns one line per row with a bar as long relative to the longest active time as the
+/// active time of the row. Bars are drawn with an accuracy of an eighth of a character.
+pub fn bar_chart(rows: &[ReportRow], display: &DateTimeDisplay, style: &ChartStyle) -> String {
+  let max_secs = rows
+    .iter()
+    .map(|row| row.active.num_seconds())
+    .max()
+    .unwrap_or(0);
+  if max_secs <= 0 {
+    return "No active time\n".to_string();
+  }
+
+  let labels: Vec<String> = rows
+    .iter()
+    .map(|row| format_utils::truncate_to_width(&row.key, MAX_LABEL_WIDTH))
+    .collect();
+  let values: Vec<String> = rows
+    .iter()
+    .map(|row| display.format_duration(&DateDifference::from(row.active)))
+    .collect();
+  let label_width = labels
+    .iter()
+    .map(|label| format_utils::display_width(label))
+    .max()
+    .unwrap_or(0);
+  let value_width = values
+    .iter()
+    .map(|value| value.chars().count())
+    .max()
+    .unwrap_or(0);
+  let bar_width = style
+    .width
+    .saturating_sub(label_width + value_width + 2 * COLUMN_GAP.len())
+    .max(MIN_BAR_WIDTH);
+
+  let mut chart = String::new();
+  for ((row, label), value) in rows.iter().zip(&labels).zip(&values) {
+    let secs = row.active.num_seconds().max(0);
+    // Any active time is visible as at least an eighth of a character.
+    let eighths = (secs * bar_width as i64 * EIGHTHS_PER_CHAR / max_secs).max(secs.min(1));
+    let bar = bar_of_eighths(eighths);
+    let padding = " ".repeat(bar_width.saturating_sub(bar.chars().count()));
+    let label_padding = " ".repeat(label_width - format_utils::display_width(label));
+    chart.push_str(&format!(
+      "{label}{label_padding}{COLUMN_GAP}{}{padding}{COLUMN_GAP}{value:>value_width$}\n",
+      style.paint(&bar, BAR_COLOR)
+    ));
+  }
+  chart
+}
+
+fn bar_of_eighths(eighths: i64) -> String {
+  let full = (eighths / EIGHTHS_PER_CHAR) as usize;
+  let rest = (eighths % EIGHTHS_PER_CHAR) as usize;
+  let mut bar = FULL_BLOCK.to_string().repeat(full);
+  if rest > 0 {
+    bar.push(PARTIAL_BLOCKS[rest - 1]);
+  }
+  bar
+}
+
+/// Returns a grid with a row per weekday and a column per week. The darker a cell the more
+/// active time the day has compared to the most active day. If not all weeks fit into the
+/// width of param style, only the last weeks are shown.
+pub fn heatmap(report: &Report, display: &DateTimeDisplay, style: &ChartStyle) -> String {
+  let mut per_day: BTreeMap<NaiveDate, Duration> = BTreeMap::new();
+  for entry in report.entries() {
+    let active = per_day.entry(entry.day).or_insert_with(Duration::zero);
+    *active = *active + entry.active;
+  }
+  let max_secs = per_day
+    .values()
+    .map(Duration::num_seconds)
+    .max()
+    .unwrap_or(0);
+
+  let range = report.range();
+  let weeks = visible_weeks(&range, style.width);
+  let mut grid = month_header(&weeks);
+  for (weekday, label) in WEEKDAY_LABELS.iter().enumerate() {
+    grid.push_str(&format!("{label:<HEATMAP_LABEL_WIDTH$}"));
+    for monday in &weeks {
+      let day = *monday + Duration::days(weekday as i64);
+      let is_in_range = range.first_day <= day && day <= range.last_day;
+      if is_in_range {
+        let secs = per_day.get(&day).map_or(0, Duration::num_seconds);
+        let level = heat_level(secs, max_secs);
+        grid.push_str(&style.paint(&HEAT_LEVELS[level].to_string(), HEAT_COLORS[level]));
+      } else {
+        grid.push(' ');
+      }
+      grid.push(' ');
+    }
+    grid.push('\n');
+  }
+
+  let legend: Vec<String> = HEAT_LEVELS
+    .iter()
+    .zip(HEAT_COLORS)
+    .map(|(level, color)| style.paint(&level.to_string(), color))
+    .collect();
+  grid.push_str(&format!(
+    "Less {} More, most per day: {}\n",
+    legend.join(" "),
+    display.format_duration(&DateDifference::new(max_secs))
+  ));
+  grid
+}
+
+/// Returns the Mondays of the weeks of the range which fit into the width.
+fn visible_weeks(range: &ReportRange, width: usize) -> Vec<NaiveDate> {
+  let first_monday = ReportRange::week_of(range.first_day).first_day;
+  let all_weeks: Vec<NaiveDate> = std::iter::successors(Some(first_monday), |monday| {
+    Some(*monday + Duration::days(7)).filter(|next| *next <= range.last_day)
+  })
+  .collect();
+  let fitting = (width.saturating_sub(HEATMAP_LABEL_WIDTH) / HEATMAP_CELL_WIDTH).max(1);
+
+  all_weeks[all_weeks.len().saturating_sub(fitting)..].to_vec()
+}
+
+/// Returns a line with the short name of the month above the first week of every month.
+fn month_header(weeks: &[NaiveDate]) -> String {
+  let mut header: Vec<char> = vec![' '; HEATMAP_LABEL_WIDTH + weeks.len() * HEATMAP_CELL_WIDTH];
+  let mut last_month = None;
+  let mut free_from = 0;
+  for (index, monday) in weeks.iter().enumerate() {
+    let sunday = *monday + Duration::days(6);
+    let month = (sunday.year(), sunday.month());
+    let position = HEATMAP_LABEL_WIDTH + index * HEATMAP_CELL_WIDTH;
+    if last_month != Some(month) && position >= free_from {
+      let name = sunday.format("%b").to_string();
+      for (offset, letter) in name.chars().enumerate() {
+        let column = position + offset;
+        if column >= header.len() {
+          header.resize(column + 1, ' ');
+        }
+        header[column] = letter;
+      }
+      free_from = position + name.chars().count() + 1;
+      last_month = Some(month);
+    }
+  }
+
+  let mut header: String = header.into_iter().collect();
+  header.truncate(header.trim_end().len());
+  header.push('\n');
+  header
+}
+
+/// Returns 0 for no active time and 1 to 4 for up to a quarter, a half, three quarters or all
+/// of the most active time.
+fn heat_level(secs: i64, max_secs: i64) -> usize {
+  if secs <= 0 || max_secs <= 0 {
+    return 0;
+  }
+  let quarters = (secs * 4 + max_secs - 1) / max_secs;
+  quarters.clamp(1, 4) as usize
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono::{TimeZone, Utc};
+  use chrono_tz::Tz;
+
+  use super::*;
+  use crate::{
+    chrono_utility::{clock::FixedClock, date_time_display::DisplayZone},
+    time_entities::time_stamp::TimeStamp,
+  };
+
+  fn row(key: &str, minutes: i64) -> ReportRow {
+    ReportRow {
+      active: Duration::minutes(minutes),
+      ..ReportRow::empty(key.to_string())
+    }
+  }
+
+  #[test]
+  fn should_draw_bars_relative_to_longest() {
+    let rows = [row("Shop", 60), row("Blog", 15)];
+    // 4 for labels, 8 for values and 4 for gaps leave 16 characters for bars.
+    let style = ChartStyle::new(32);
+
+    let actual = bar_chart(&rows, &DateTimeDisplay::default(), &style);
+
+    assert_eq!(
+      "Shop  ████████████████  01:00:00\nBlog  ████              00:15:00\n",
+      actual
+    );
+  }
+
+  #[test]
+  fn should_draw_partial_blocks_and_cut_long_labels() {
+    assert_eq!("██▌", bar_of_eighths(20));
+    assert_eq!("▏", bar_of_eighths(1));
+    assert_eq!(
+      "Very long title of a ti…",
+      format_utils::truncate_to_width("Very long title of a time stamp", MAX_LABEL_WIDTH)
+    );
+  }
+
+  #[test]
+  fn should_shade_days_of_week_in_heatmap() {
+    // 2022-10-17 is a Monday.
+    let started = Utc.ymd(2022, 10, 18).and_hms(8, 0, 0);
+    let mut long_day = TimeStamp::with_started("Long", started);
+    long_day
+      .finish(&FixedClock::new(started + Duration::hours(8)))
+      .unwrap();
+    let mut short_day = TimeStamp::with_started("Short", started + Duration::days(2));
+    short_day
+      .finish(&FixedClock::new(
+        started + Duration::days(2) + Duration::hours(1),
+      ))
+      .unwrap();
+    let report = Report::create(
+      [&long_day, &short_day],
+      ReportRange::week_of(NaiveDate::from_ymd(2022, 10, 20)),
+      DisplayZone::Named(Tz::UTC),
+      &FixedClock::new(started + Duration::days(7)),
+    );
+
+    let actual = heatmap(&report, &DateTimeDisplay::default(), &ChartStyle::new(80));
+
+    let expected = "    Oct
+Mon ·
+Tue █
+Wed ·
+Thu ░
+Fri ·
+Sat ·
+Sun ·
+Less · ░ ▒ ▓ █ More, most per day: 08:00:00
+";
+    let actual_trimmed: Vec<&str> = actual.lines().map(str::trim_end).collect();
+    assert_eq!(expected.lines().collect::<Vec<&str>>(), actual_trimmed);
+  }
+
+  #[test]
+  fn should_only_color_if_asked() {
+    let plain = ChartStyle::new(80);
+    let colored = plain.with_color(true);
+
+    assert_eq!("█", plain.paint("█", BAR_COLOR));
+    assert_eq!("\x1b[38;5;33m█\x1b[0m", colored.paint("█", BAR_COLOR));
+  }
+}
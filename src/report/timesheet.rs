@@ -0,0 +1,435 @@
+//! Billable time per day and project for invoices. Tracked time of every row is rounded to a
+//! step like 6 or 15 minutes and can be raised to a minimum billed time. Non-billable time is
+//! listed, but nothing is billed for it.
+use std::collections::BTreeMap;
+
+use chrono::{Duration, NaiveDate};
+
+use crate::{
+  billing::{Amounts, HourlyRate},
+  chrono_utility::{date_time_display::DateTimeDisplay, DateDifference},
+  format_utils,
+};
+
+use super::{Report, ReportRange, UnknownReportOption, DAY_FORMAT, NO_PROJECT, TOTAL};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RoundingMode {
+  /// Half a step or more is rounded up, less is rounded down.
+  #[default]
+  Nearest,
+  Up,
+  Down,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimesheetOutput {
+  #[default]
+  Table,
+  Csv,
+  Markdown,
+}
+
+/// How tracked time is turned into billed time. Without a step and minimum the tracked time is
+/// billed as it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rounding {
+  step: Option<Duration>,
+  mode: RoundingMode,
+  minimum: Option<Duration>,
+}
+
+/// Non-billable time is shown with this text instead of a rate.
+const NON_BILLABLE: &str = "non-billable";
+
+/// Time tracked and billed for one project on one day. Time of the project with another rate
+/// or non-billable time is in a row of its own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimesheetRow {
+  pub day: NaiveDate,
+  pub project: Option<String>,
+  /// Titles of the time stamps which contributed to this row.
+  pub titles: Vec<String>,
+  pub tracked: Duration,
+  /// Rounded tracked time. It is zero for non-billable time.
+  pub billed: Duration,
+  pub is_billable: bool,
+  pub rate: Option<HourlyRate>,
+  /// Money for the billed time. Empty without a rate.
+  pub amounts: Amounts,
+}
+
+type RowKey = (NaiveDate, Option<String>, bool, Option<HourlyRate>);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Timesheet {
+  range: ReportRange,
+  rows: Vec<TimesheetRow>,
+}
+
+impl RoundingMode {
+  pub const NAMES: [&'static str; 3] = ["nearest", "up", "down"];
+
+  /// # Errors
+  /// If the name is not one of [`RoundingMode::NAMES`].
+  pub fn from_name(name: &str) -> Result<Self, UnknownReportOption> {
+    match name.trim().to_lowercase().as_str() {
+      "nearest" => Ok(RoundingMode::Nearest),
+      "up" => Ok(RoundingMode::Up),
+      "down" => Ok(RoundingMode::Down),
+      _ => Err(UnknownReportOption::new(name, &Self::NAMES)),
+    }
+  }
+}
+
+impl TimesheetOutput {
+  pub const NAMES: [&'static str; 3] = ["table", "csv", "markdown"];
+
+  /// # Errors
+  /// If the name is not one of [`TimesheetOutput::NAMES`].
+  pub fn from_name(name: &str) -> Result<Self, UnknownReportOption> {
+    match name.trim().to_lowercase().as_str() {
+      "table" => Ok(TimesheetOutput::Table),
+      "csv" => Ok(TimesheetOutput::Csv),
+      "markdown" | "md" => Ok(TimesheetOutput::Markdown),
+      _ => Err(UnknownReportOption::new(name, &Self::NAMES)),
+    }
+  }
+}
+
+impl Rounding {
+  /// Rounds to multiples of param step. A step of zero or less means no rounding.
+  pub fn new(step: Duration, mode: RoundingMode) -> Self {
+    Rounding {
+      step: Some(step).filter(|step| *step > Duration::zero()),
+      mode,
+      minimum: None,
+    }
+  }
+
+  /// Returns this rounding which bills at least param minimum for any tracked time.
+  pub fn with_minimum(self, minimum: Duration) -> Self {
+    Rounding {
+      minimum: Some(minimum).filter(|minimum| *minimum > Duration::zero()),
+      ..self
+    }
+  }
+
+  /// Returns the billed time for the tracked time. Nothing tracked means nothing billed, even
+  /// with a minimum.
+  /// # Example
+  /// ```
+  /// use chrono::Duration;
+  /// use stamp_member::report::timesheet::{Rounding, RoundingMode};
+  ///
+  /// let rounding = Rounding::new(Duration::minutes(15), RoundingMode::Up);
+  ///
+  /// assert_eq!(Duration::minutes(30), rounding.apply(Duration::minutes(16)));
+  /// ```
+  pub fn apply(&self, tracked: Duration) -> Duration {
+    let tracked_secs = tracked.num_seconds();
+    if tracked_secs <= 0 {
+      return Duration::zero();
+    }
+
+    let billed_secs = match self.step.map(|step| step.num_seconds()) {
+      Some(step_secs) if step_secs > 0 => {
+        let steps = match self.mode {
+          RoundingMode::Nearest => (tracked_secs + step_secs / 2) / step_secs,
+          RoundingMode::Up => (tracked_secs + step_secs - 1) / step_secs,
+          RoundingMode::Down => tracked_secs / step_secs,
+        };
+        steps * step_secs
+      }
+      _ => tracked_secs,
+    };
+    let minimum_secs = self.minimum.map_or(0, |minimum| minimum.num_seconds());
+
+    Duration::seconds(billed_secs.max(minimum_secs))
+  }
+}
+
+impl Timesheet {
+  /// Creates one row per day and project from the entries of the report. Rounding is applied to
+  /// every row on its own and amounts are calculated from the rounded time.
+  pub fn from_report(report: &Report, rounding: &Rounding) -> Self {
+    let mut per_day_and_project: BTreeMap<RowKey, (Vec<String>, Duration)> = BTreeMap::new();
+    for entry in report.entries() {
+      let key = (
+        entry.day,
+        entry.project.clone(),
+        !entry.billable,
+        entry.rate.clone(),
+      );
+      let (titles, tracked) = per_day_and_project
+        .entry(key)
+        .or_insert_with(|| (Vec::new(), Duration::zero()));
+      if !titles.contains(&entry.title) {
+        titles.push(entry.title.clone());
+      }
+      *tracked = *tracked + entry.active;
+    }
+
+    let rows = per_day_and_project
+      .into_iter()
+      .filter(|(_, (_, tracked))| *tracked > Duration::zero())
+      .map(
+        |((day, project, is_non_billable, rate), (titles, tracked))| {
+          let billed = if is_non_billable {
+            Duration::zero()
+          } else {
+            rounding.apply(tracked)
+          };
+          let mut amounts = Amounts::default();
+          if let (false, Some(rate)) = (is_non_billable, &rate) {
+            amounts.add_for(rate, billed);
+          }
+
+          TimesheetRow {
+            day,
+            project,
+            titles,
+            tracked,
+            billed,
+            is_billable: !is_non_billable,
+            rate,
+            amounts,
+          }
+        },
+      )
+      .collect();
+
+    Timesheet {
+      range: report.range(),
+      rows,
+    }
+  }
+
+  /// Returns this timesheet with only the rows of the given project.
+  pub fn for_project(self, project: &str) -> Self {
+    let rows = self
+      .rows
+      .into_iter()
+      .filter(|row| row.project.as_deref() == Some(project.trim()))
+      .collect();
+    Timesheet { rows, ..self }
+  }
+
+  pub fn range(&self) -> ReportRange {
+    self.range
+  }
+
+  pub fn rows(&self) -> &[TimesheetRow] {
+    &self.rows
+  }
+
+  pub fn tracked_total(&self) -> Duration {
+    self
+      .rows
+      .iter()
+      .fold(Duration::zero(), |sum, row| sum + row.tracked)
+  }
+
+  pub fn amounts_total(&self) -> Amounts {
+    let mut amounts = Amounts::default();
+    for row in &self.rows {
+      amounts.merge(&row.amounts);
+    }
+    amounts
+  }
+
+  /// Sum of the rounded rows.
+  pub fn billed_total(&self) -> Duration {
+    self
+      .rows
+      .iter()
+      .fold(Duration::zero(), |sum, row| sum + row.billed)
+  }
+
+  /// Returns the timesheet in the given output with a total in the last row. Durations are shown
+  /// in the duration format of param display and billed time in decimal hours as well.
+  pub fn render(&self, output: TimesheetOutput, display: &DateTimeDisplay) -> String {
+    let rows = self.to_text_rows(display);
+    match output {
+      TimesheetOutput::Table => format!(
+        "Timesheet from {} to {}:\n{}",
+        self.range.first_day().format(DAY_FORMAT),
+        self.range.last_day().format(DAY_FORMAT),
+        format_utils::format_to_text_table(&rows, 2)
+      ),
+      TimesheetOutput::Csv => format_utils::format_to_csv(&rows),
+      TimesheetOutput::Markdown => format_utils::format_to_markdown_table(&rows),
+    }
+  }
+
+  fn to_text_rows(&self, display: &DateTimeDisplay) -> Vec<Vec<String>> {
+    let format_duration =
+      |duration: Duration| display.format_duration(&DateDifference::from(duration));
+    let mut rows = vec![[
+      "Day",
+      "Project",
+      "Titles",
+      "Tracked",
+      "Billed",
+      "Billed hours",
+      "Rate",
+      "Amount",
+    ]
+    .iter()
+    .map(|header| header.to_string())
+    .collect()];
+
+    for row in &self.rows {
+      rows.push(vec![
+        row.day.format(DAY_FORMAT).to_string(),
+        row.project.as_deref().unwrap_or(NO_PROJECT).to_string(),
+        row.titles.join(", "),
+        format_duration(row.tracked),
+        format_duration(row.billed),
+        decimal_hours(row.billed),
+        match (row.is_billable, &row.rate) {
+          (false, _) => NON_BILLABLE.to_string(),
+          (true, Some(rate)) => rate.to_string(),
+          (true, None) => String::new(),
+        },
+        row.amounts.to_string(),
+      ]);
+    }
+
+    rows.push(vec![
+      TOTAL.to_string(),
+      String::new(),
+      String::new(),
+      format_duration(self.tracked_total()),
+      format_duration(self.billed_total()),
+      decimal_hours(self.billed_total()),
+      String::new(),
+      self.amounts_total().to_string(),
+    ]);
+    rows
+  }
+}
+
+/// Hours with 2 decimal places like 1.75.
+fn decimal_hours(duration: Duration) -> String {
+  format!("{:.2}", super::rounded_hours(duration))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    billing::ProjectRates,
+    chrono_utility::clock::FixedClock,
+    report::tests::{berlin, finished, BERLIN},
+  };
+
+  fn sample_timesheet(rounding: &Rounding) -> Timesheet {
+    let time_stamps = [
+      finished("Coding", berlin(20, 9, 0), berlin(20, 9, 50)).with_project(Some("Shop")),
+      finished("Review", berlin(20, 10, 0), berlin(20, 10, 7)).with_project(Some("Shop")),
+      finished("Call", berlin(20, 11, 0), berlin(20, 11, 4)),
+      finished("Coding", berlin(21, 9, 0), berlin(21, 9, 20)).with_project(Some("Shop")),
+      finished("Standup", berlin(21, 10, 0), berlin(21, 10, 15))
+        .with_project(Some("Shop"))
+        .with_billable(false),
+    ];
+    let range = ReportRange::new(
+      NaiveDate::from_ymd(2022, 10, 20),
+      NaiveDate::from_ymd(2022, 10, 21),
+    )
+    .unwrap();
+    let rates = ProjectRates::from([("Shop".to_string(), HourlyRate::parse("80 EUR").unwrap())]);
+    let report = Report::create(
+      &time_stamps,
+      range,
+      BERLIN,
+      &FixedClock::new(berlin(22, 0, 0)),
+    )
+    .with_project_rates(&rates);
+
+    Timesheet::from_report(&report, rounding)
+  }
+
+  #[test]
+  fn should_round_to_steps() {
+    let tracked = Duration::minutes(7) + Duration::seconds(30);
+    let step = Duration::minutes(6);
+
+    assert_eq!(
+      Duration::minutes(6),
+      Rounding::new(step, RoundingMode::Nearest).apply(tracked)
+    );
+    assert_eq!(
+      Duration::minutes(12),
+      Rounding::new(step, RoundingMode::Up).apply(tracked)
+    );
+    assert_eq!(
+      Duration::minutes(6),
+      Rounding::new(step, RoundingMode::Down).apply(tracked)
+    );
+    assert_eq!(
+      Duration::minutes(12),
+      Rounding::new(step, RoundingMode::Nearest).apply(Duration::minutes(9))
+    );
+  }
+
+  #[test]
+  fn should_bill_minimum_only_for_tracked_time() {
+    let rounding =
+      Rounding::new(Duration::minutes(6), RoundingMode::Down).with_minimum(Duration::minutes(15));
+
+    assert_eq!(Duration::minutes(15), rounding.apply(Duration::minutes(4)));
+    assert_eq!(Duration::zero(), rounding.apply(Duration::zero()));
+    assert_eq!(Duration::minutes(18), rounding.apply(Duration::minutes(20)));
+  }
+
+  #[test]
+  fn should_round_every_day_and_project_on_its_own() {
+    let timesheet = sample_timesheet(&Rounding::new(Duration::minutes(15), RoundingMode::Up));
+
+    let actual: Vec<(String, Option<String>, i64, i64)> = timesheet
+      .rows()
+      .iter()
+      .map(|row| {
+        (
+          row.day.format(DAY_FORMAT).to_string(),
+          row.project.clone(),
+          row.tracked.num_minutes(),
+          row.billed.num_minutes(),
+        )
+      })
+      .collect();
+
+    assert_eq!(
+      vec![
+        ("2022-10-20".to_string(), None, 4, 15),
+        ("2022-10-20".to_string(), Some("Shop".to_string()), 57, 60),
+        ("2022-10-21".to_string(), Some("Shop".to_string()), 20, 30),
+        ("2022-10-21".to_string(), Some("Shop".to_string()), 15, 0),
+      ],
+      actual
+    );
+    assert_eq!(Duration::minutes(96), timesheet.tracked_total());
+    assert_eq!(Duration::minutes(105), timesheet.billed_total());
+  }
+
+  #[test]
+  fn should_render_markdown_with_total() {
+    let timesheet = sample_timesheet(&Rounding::new(Duration::minutes(6), RoundingMode::Nearest))
+      .for_project("Shop");
+    let display = DateTimeDisplay::default();
+
+    let actual = timesheet.render(TimesheetOutput::Markdown, &display);
+
+    let expected = "\
+| Day | Project | Titles | Tracked | Billed | Billed hours | Rate | Amount |
+| --- | --- | --- | --- | --- | --- | --- | --- |
+| 2022-10-20 | Shop | Coding, Review | 00:57:00 | 01:00:00 | 1.00 | 80.00 EUR | 80.00 EUR |
+| 2022-10-21 | Shop | Coding | 00:20:00 | 00:18:00 | 0.30 | 80.00 EUR | 24.00 EUR |
+| 2022-10-21 | Shop | Standup | 00:15:00 | 00:00:00 | 0.00 | non-billable |  |
+| Total |  |  | 01:32:00 | 01:18:00 | 1.30 |  | 104.00 EUR |
+";
+    assert_eq!(expected, actual);
+  }
+}
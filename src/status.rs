@@ -0,0 +1,463 @@
+//! One line about the running entry for shell prompts and status bars. The line is rendered
+//! from a template like `{title} {elapsed:short}` with the placeholders:
+//! - `{title}`, `{kind}` (time stamp or stop watch) and `{project}`
+//! - `{elapsed}` active time and `{remaining}` time left of a stop watch, both in the duration
+//!   format of the display or in a given one like `{elapsed:short}` (1:05), `{elapsed:compact}`,
+//!   `{elapsed:clock}`, `{elapsed:human}` or `{elapsed:decimal}`
+//! - `{count}` number of running entries
+//!
+//! Braces are written as `{{` and `}}`. The running entry is the one started or resumed last.
+use std::fmt::Display;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+use crate::{
+  chrono_utility::{date_time_display::DateTimeDisplay, DateDifference, DifferenceFormat},
+  time_entities::{
+    listing::{EntityState, ListedEntity},
+    time_entities_controller::{EntityKind, TimeEntitiesController},
+    TimeEntity,
+  },
+};
+
+pub const DEFAULT_TEMPLATE: &str = "{title} {elapsed:short}";
+
+/// Parsed template of the status line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusTemplate {
+  parts: Vec<TemplatePart>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TemplatePart {
+  Text(String),
+  Title,
+  Kind,
+  Project,
+  Count,
+  Elapsed(DurationStyle),
+  Remaining(DurationStyle),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DurationStyle {
+  /// Duration format of the display.
+  Display,
+  /// Hours and minutes like 1:05.
+  Short,
+  Format(DifferenceFormat),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusOutput {
+  /// The line only.
+  Text,
+  /// Object for waybar and i3blocks with the line, a tooltip and a class.
+  Json,
+}
+
+/// Ready-made configuration to show the status line in a prompt or bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusSnippet {
+  Bash,
+  Zsh,
+  Tmux,
+  Waybar,
+  I3blocks,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum StatusError {
+  UnknownPlaceholder(String),
+  UnknownDurationFormat(String),
+  /// A brace is opened and not closed or closed and not opened.
+  UnbalancedBrace,
+  UnknownOutput(String),
+  UnknownSnippet(String),
+}
+
+/// Time stamp or stop watch which counts time now.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunningEntry {
+  pub kind: EntityKind,
+  pub title: String,
+  pub project: Option<String>,
+  pub active: Duration,
+  /// Time left of a stop watch. Negative once it is expired.
+  pub remaining: Option<Duration>,
+  /// Moment at which it was started or resumed last.
+  since: DateTime<Utc>,
+}
+
+/// All running entries with the one started or resumed last first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Status {
+  running: Vec<RunningEntry>,
+}
+
+#[derive(Serialize)]
+struct JsonStatus {
+  /// Read by waybar.
+  text: String,
+  tooltip: String,
+  class: &'static str,
+  /// Read by i3blocks.
+  full_text: String,
+  short_text: String,
+}
+
+impl StatusTemplate {
+  /// # Errors
+  /// If a placeholder or duration format is unknown or a brace is not balanced.
+  pub fn parse(template: &str) -> Result<Self, StatusError> {
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(character) = chars.next() {
+      match character {
+        '{' if chars.peek() == Some(&'{') => {
+          chars.next();
+          text.push('{');
+        }
+        '}' if chars.peek() == Some(&'}') => {
+          chars.next();
+          text.push('}');
+        }
+        '{' => {
+          let mut placeholder = String::new();
+          loop {
+            match chars.next() {
+              Some('}') => break,
+              Some(character) => placeholder.push(character),
+              None => return Err(StatusError::UnbalancedBrace),
+            }
+          }
+          if !text.is_empty() {
+            parts.push(TemplatePart::Text(std::mem::take(&mut text)));
+          }
+          parts.push(TemplatePart::from_placeholder(&placeholder)?);
+        }
+        '}' => return Err(StatusError::UnbalancedBrace),
+        character => text.push(character),
+      }
+    }
+    if !text.is_empty() {
+      parts.push(TemplatePart::Text(text));
+    }
+    Ok(StatusTemplate { parts })
+  }
+
+  /// Returns the line about the running entry or None if nothing runs.
+  pub fn render(&self, status: &Status, display: &DateTimeDisplay) -> Option<String> {
+    let current = status.current()?;
+    let duration = |duration: Duration, style: DurationStyle| {
+      let difference = DateDifference::from(duration);
+      match style {
+        DurationStyle::Display => display.format_duration(&difference),
+        DurationStyle::Short => short_text(duration),
+        DurationStyle::Format(format) => difference.format(format),
+      }
+    };
+
+    let line = self
+      .parts
+      .iter()
+      .map(|part| match part {
+        TemplatePart::Text(text) => text.clone(),
+        TemplatePart::Title => current.title.clone(),
+        TemplatePart::Kind => current.kind.name().to_lowercase(),
+        TemplatePart::Project => current.project.clone().unwrap_or_default(),
+        TemplatePart::Count => status.running.len().to_string(),
+        TemplatePart::Elapsed(style) => duration(current.active, *style),
+        TemplatePart::Remaining(style) => current
+          .remaining
+          .map(|remaining| duration(remaining, *style))
+          .unwrap_or_default(),
+      })
+      .collect();
+    Some(line)
+  }
+}
+
+impl TemplatePart {
+  fn from_placeholder(placeholder: &str) -> Result<Self, StatusError> {
+    let (name, format) = match placeholder.split_once(':') {
+      Some((name, format)) => (name.trim(), Some(format.trim())),
+      None => (placeholder.trim(), None),
+    };
+    let style = match format {
+      None => DurationStyle::Display,
+      Some("short") => DurationStyle::Short,
+      Some(format) => DurationStyle::Format(
+        DifferenceFormat::from_name(format)
+          .map_err(|_| StatusError::UnknownDurationFormat(format.to_string()))?,
+      ),
+    };
+
+    match (name, format) {
+      ("elapsed", _) => Ok(TemplatePart::Elapsed(style)),
+      ("remaining", _) => Ok(TemplatePart::Remaining(style)),
+      ("title", None) => Ok(TemplatePart::Title),
+      ("kind", None) => Ok(TemplatePart::Kind),
+      ("project", None) => Ok(TemplatePart::Project),
+      ("count", None) => Ok(TemplatePart::Count),
+      _ => Err(StatusError::UnknownPlaceholder(placeholder.to_string())),
+    }
+  }
+}
+
+/// Hours and minutes like 1:05. Seconds are cut, so the text changes once a minute.
+fn short_text(duration: Duration) -> String {
+  let sign = if duration < Duration::zero() { "-" } else { "" };
+  let minutes = duration.num_minutes().unsigned_abs();
+  format!("{sign}{}:{:02}", minutes / 60, minutes % 60)
+}
+
+impl Status {
+  /// Collects the running time stamps and stop watches. Expired stop watches still count time
+  /// and are included.
+  pub fn of(entities: &TimeEntitiesController) -> Self {
+    let time_stamps = entities
+      .time_stamps()
+      .iter()
+      .filter_map(|time_stamp| RunningEntry::of(time_stamp, EntityKind::TimeStamp, entities));
+    let stop_watches = entities
+      .stop_watches()
+      .iter()
+      .filter_map(|stop_watch| RunningEntry::of(stop_watch, EntityKind::StopWatch, entities));
+    let mut running: Vec<RunningEntry> = time_stamps.chain(stop_watches).collect();
+    running.sort_by_key(|entry| std::cmp::Reverse(entry.since));
+    Status { running }
+  }
+
+  pub fn current(&self) -> Option<&RunningEntry> {
+    self.running.first()
+  }
+
+  pub fn running(&self) -> &[RunningEntry] {
+    &self.running
+  }
+
+  /// Returns the line and a tooltip with all running entries as JSON for waybar and i3blocks.
+  /// The class is running or idle, so bars can style both.
+  /// # Errors
+  /// If the status can not be serialized.
+  pub fn to_json(
+    &self,
+    template: &StatusTemplate,
+    idle: &str,
+    display: &DateTimeDisplay,
+  ) -> Result<String, serde_json::Error> {
+    let line = template.render(self, display);
+    let tooltip: Vec<String> = self
+      .running
+      .iter()
+      .map(|entry| {
+        let active = display.format_duration(&DateDifference::from(entry.active));
+        format!("{} {active}", entry.title)
+      })
+      .collect();
+    let json_status = JsonStatus {
+      class: if line.is_some() { "running" } else { "idle" },
+      text: line.clone().unwrap_or_else(|| idle.to_string()),
+      tooltip: tooltip.join("\n"),
+      full_text: line.unwrap_or_else(|| idle.to_string()),
+      short_text: self
+        .current()
+        .map_or_else(|| idle.to_string(), |entry| entry.title.clone()),
+    };
+    serde_json::to_string(&json_status)
+  }
+}
+
+impl RunningEntry {
+  fn of<T: ListedEntity + TimeEntity>(
+    entity: &T,
+    kind: EntityKind,
+    entities: &TimeEntitiesController,
+  ) -> Option<Self> {
+    let clock = entities.clock();
+    if !matches!(
+      EntityState::of(entity, clock),
+      EntityState::Running | EntityState::Expired
+    ) {
+      return None;
+    }
+    let time_stamp = entity.time_stamp();
+    let since = time_stamp
+      .get_active_intervals(clock)
+      .last()
+      .map_or(time_stamp.get_started(), |(from, _)| *from);
+    Some(RunningEntry {
+      kind,
+      title: entity.get_title().to_string(),
+      project: time_stamp.get_project().map(str::to_string),
+      active: time_stamp.get_active_duration(clock),
+      remaining: entity.remaining(clock),
+      since,
+    })
+  }
+}
+
+impl StatusOutput {
+  pub const NAMES: [&'static str; 2] = ["text", "json"];
+
+  /// # Errors
+  /// If the name is not one of [`StatusOutput::NAMES`].
+  pub fn from_name(name: &str) -> Result<Self, StatusError> {
+    match name.trim().to_lowercase().as_str() {
+      "text" => Ok(StatusOutput::Text),
+      "json" => Ok(StatusOutput::Json),
+      _ => Err(StatusError::UnknownOutput(name.to_string())),
+    }
+  }
+}
+
+impl StatusSnippet {
+  pub const NAMES: [&'static str; 5] = ["bash", "zsh", "tmux", "waybar", "i3blocks"];
+
+  /// # Errors
+  /// If the name is not one of [`StatusSnippet::NAMES`].
+  pub fn from_name(name: &str) -> Result<Self, StatusError> {
+    match name.trim().to_lowercase().as_str() {
+      "bash" => Ok(StatusSnippet::Bash),
+      "zsh" => Ok(StatusSnippet::Zsh),
+      "tmux" => Ok(StatusSnippet::Tmux),
+      "waybar" => Ok(StatusSnippet::Waybar),
+      "i3blocks" => Ok(StatusSnippet::I3blocks),
+      _ => Err(StatusError::UnknownSnippet(name.to_string())),
+    }
+  }
+
+  /// Returns the configuration to paste into the config file of the shell or bar.
+  pub fn text(&self) -> &'static str {
+    match self {
+      StatusSnippet::Bash => {
+        "# ~/.bashrc\n\
+         PS1='$(stamp_member status --template \"[{title} {elapsed:short}] \" 2>/dev/null)'\"$PS1\""
+      }
+      StatusSnippet::Zsh => {
+        "# ~/.zshrc\n\
+         setopt PROMPT_SUBST\n\
+         PROMPT='$(stamp_member status --template \"[{title} {elapsed:short}] \" 2>/dev/null)'\"$PROMPT\""
+      }
+      StatusSnippet::Tmux => {
+        "# ~/.tmux.conf\n\
+         set -g status-interval 15\n\
+         set -g status-right '#(stamp_member status --idle \"-\") | %H:%M'"
+      }
+      StatusSnippet::Waybar => {
+        "// modules of ~/.config/waybar/config\n\
+         \"custom/stamp_member\": {\n  \
+           \"exec\": \"stamp_member status --output json\",\n  \
+           \"return-type\": \"json\",\n  \
+           \"interval\": 15\n\
+         }"
+      }
+      StatusSnippet::I3blocks => {
+        "# ~/.config/i3blocks/config\n\
+         [stamp_member]\n\
+         command=stamp_member status --output json\n\
+         format=json\n\
+         interval=15"
+      }
+    }
+  }
+}
+
+impl Display for StatusError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      StatusError::UnknownPlaceholder(placeholder) => write!(
+        f,
+        "Unknown placeholder {{{placeholder}}}, expected title, kind, project, count, elapsed or \
+         remaining"
+      ),
+      StatusError::UnknownDurationFormat(format) => write!(
+        f,
+        "Unknown duration format \"{format}\", expected short or one of: {}",
+        DifferenceFormat::NAMES.join(", ")
+      ),
+      StatusError::UnbalancedBrace => {
+        write!(
+          f,
+          "Unbalanced brace in template, write {{{{ and }}}} for braces"
+        )
+      }
+      StatusError::UnknownOutput(name) => write!(
+        f,
+        "Unknown output \"{name}\", expected one of: {}",
+        StatusOutput::NAMES.join(", ")
+      ),
+      StatusError::UnknownSnippet(name) => write!(
+        f,
+        "Unknown snippet \"{name}\", expected one of: {}",
+        StatusSnippet::NAMES.join(", ")
+      ),
+    }
+  }
+}
+
+impl std::error::Error for StatusError {}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+
+  use chrono::TimeZone;
+
+  use super::*;
+  use crate::{
+    chrono_utility::clock::FixedClock,
+    time_entities::{stop_watch::StopWatch, time_stamp::TimeStamp},
+  };
+
+  #[test]
+  fn should_render_entry_started_last_with_template() {
+    let started = Utc.ymd(2022, 10, 20).and_hms(9, 0, 0);
+    let entities = TimeEntitiesController::new(
+      vec![TimeStamp::with_started("Coding", started).with_project(Some("Acme"))],
+      vec![StopWatch::new(
+        TimeStamp::with_started("Tea", started + Duration::minutes(70)),
+        Duration::minutes(4),
+      )],
+    )
+    .with_clock(Arc::new(FixedClock::new(started + Duration::minutes(75))));
+    let status = Status::of(&entities);
+    let display = DateTimeDisplay::default();
+
+    let template = StatusTemplate::parse("{{{kind}}} {title} {remaining:short} ({count})").unwrap();
+    assert_eq!(
+      Some("{stop watch} Tea -0:01 (2)".to_string()),
+      template.render(&status, &display)
+    );
+    let template = StatusTemplate::parse(DEFAULT_TEMPLATE).unwrap();
+    let idle = Status::of(&TimeEntitiesController::empty());
+    assert_eq!(None, template.render(&idle, &display));
+  }
+
+  #[test]
+  fn should_reject_unknown_placeholders_and_unbalanced_braces() {
+    assert_eq!(
+      Err(StatusError::UnknownPlaceholder("tilte".to_string())),
+      StatusTemplate::parse("{tilte}")
+    );
+    assert_eq!(
+      Err(StatusError::UnknownDurationFormat("long".to_string())),
+      StatusTemplate::parse("{elapsed:long}")
+    );
+    assert_eq!(
+      Err(StatusError::UnknownPlaceholder("title:short".to_string())),
+      StatusTemplate::parse("{title:short}")
+    );
+    assert_eq!(
+      Err(StatusError::UnbalancedBrace),
+      StatusTemplate::parse("{title")
+    );
+    assert_eq!(
+      Err(StatusError::UnbalancedBrace),
+      StatusTemplate::parse("title}")
+    );
+  }
+}
@@ -0,0 +1,333 @@
+//! Colours of terminal output. Whether to colour is chosen via --color, the environment variable
+//! NO_COLOR and whether output goes to a terminal. Which colours are used can be changed in the
+//! theme section of the config file like:
+//! ```toml
+//! [theme]
+//! running = "bold green"
+//! paused = "yellow"
+//! finished = "dim"
+//! expired = "bright-red"
+//! header = "bold underline"
+//! ```
+use std::fmt::Display;
+
+use serde::Deserialize;
+
+use crate::time_entities::listing::EntityState;
+
+/// Any non empty value turns off colours unless they are asked for via --color=always.
+pub const NO_COLOR_ENV_VAR: &str = "NO_COLOR";
+const ANSI_RESET: &str = "\x1b[0m";
+const ATTRIBUTE_NAMES: [(&str, u8); 4] = [("bold", 1), ("dim", 2), ("italic", 3), ("underline", 4)];
+const COLOR_NAMES: [&str; 8] = [
+  "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+const BRIGHT_PREFIX: &str = "bright-";
+/// ANSI code of black text. The other colours follow in the order of [`COLOR_NAMES`].
+const FOREGROUND_BASE: u8 = 30;
+const BRIGHT_FOREGROUND_BASE: u8 = 90;
+
+/// Whether output is coloured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorChoice {
+  /// Colours only if output goes to a terminal and NO_COLOR is not set.
+  #[default]
+  Auto,
+  Always,
+  Never,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct UnknownColorChoice(String);
+
+/// ANSI text style like bold green. A plain style leaves text as it is.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Style {
+  /// Select graphic rendition codes joined by ; like 1;32.
+  codes: String,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct StyleParseError(String);
+
+/// Styles of the states of time stamps and stop watches and of table headers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+  running: Style,
+  paused: Style,
+  finished: Style,
+  expired: Style,
+  header: Style,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ThemeConfigError {
+  InvalidToml(String),
+  /// Style of a key of the theme section can not be parsed.
+  InvalidStyle {
+    key: &'static str,
+    error: StyleParseError,
+  },
+}
+
+/// Part of the config file which is about colours. Other sections are ignored.
+#[derive(Deserialize, Default)]
+struct ConfigFile {
+  #[serde(default)]
+  theme: ThemeSection,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct ThemeSection {
+  running: Option<String>,
+  paused: Option<String>,
+  finished: Option<String>,
+  expired: Option<String>,
+  header: Option<String>,
+}
+
+impl ColorChoice {
+  pub const NAMES: [&'static str; 3] = ["auto", "always", "never"];
+
+  /// # Errors
+  /// If the name is not one of [`ColorChoice::NAMES`].
+  pub fn from_name(name: &str) -> Result<Self, UnknownColorChoice> {
+    match name.trim().to_lowercase().as_str() {
+      "auto" => Ok(ColorChoice::Auto),
+      "always" => Ok(ColorChoice::Always),
+      "never" => Ok(ColorChoice::Never),
+      _ => Err(UnknownColorChoice(name.to_string())),
+    }
+  }
+
+  /// Returns whether to colour output. Always and never win over param no_color, the value of
+  /// the environment variable NO_COLOR.
+  pub fn should_color(&self, is_terminal: bool, no_color: Option<&str>) -> bool {
+    match self {
+      ColorChoice::Always => true,
+      ColorChoice::Never => false,
+      ColorChoice::Auto => is_terminal && no_color.is_none_or(str::is_empty),
+    }
+  }
+}
+
+impl Style {
+  /// Parses words separated by spaces: bold, dim, italic, underline, the colours black, red,
+  /// green, yellow, blue, magenta, cyan and white, those with the prefix bright- and numbers
+  /// from 0 to 255 as colour of the 256 colour palette.
+  /// # Errors
+  /// If a word is none of the above.
+  /// # Example
+  /// ```
+  /// use stamp_member::theme::Style;
+  ///
+  /// let style = Style::parse("bold bright-green").unwrap();
+  ///
+  /// assert_eq!("\x1b[1;92mRunning\x1b[0m", style.paint("Running"));
+  /// ```
+  pub fn parse(input: &str) -> Result<Self, StyleParseError> {
+    let codes = input
+      .split_whitespace()
+      .map(|word| style_code(&word.to_lowercase()).ok_or_else(|| StyleParseError(word.to_string())))
+      .collect::<Result<Vec<String>, StyleParseError>>()?;
+
+    Ok(Style {
+      codes: codes.join(";"),
+    })
+  }
+
+  /// Select graphic rendition codes of this style joined by ; like 1;32. Empty if plain.
+  pub(crate) fn codes(&self) -> &str {
+    &self.codes
+  }
+
+  pub fn is_plain(&self) -> bool {
+    self.codes.is_empty()
+  }
+
+  /// Returns the text wrapped into the ANSI escape codes of this style. The style is reset at
+  /// the end of the text.
+  pub fn paint(&self, text: &str) -> String {
+    if self.is_plain() || text.is_empty() {
+      text.to_string()
+    } else {
+      format!("\x1b[{}m{text}{ANSI_RESET}", self.codes)
+    }
+  }
+}
+
+fn style_code(word: &str) -> Option<String> {
+  if let Some((_, code)) = ATTRIBUTE_NAMES.iter().find(|(name, _)| *name == word) {
+    return Some(code.to_string());
+  }
+  if let Ok(palette_index) = word.parse::<u8>() {
+    return Some(format!("38;5;{palette_index}"));
+  }
+  let (base, color_name) = match word.strip_prefix(BRIGHT_PREFIX) {
+    Some(color_name) => (BRIGHT_FOREGROUND_BASE, color_name),
+    None => (FOREGROUND_BASE, word),
+  };
+  COLOR_NAMES
+    .iter()
+    .position(|name| *name == color_name)
+    .map(|offset| (base + offset as u8).to_string())
+}
+
+impl Theme {
+  /// Theme without any colours.
+  pub fn plain() -> Self {
+    Theme {
+      running: Style::default(),
+      paused: Style::default(),
+      finished: Style::default(),
+      expired: Style::default(),
+      header: Style::default(),
+    }
+  }
+
+  /// Returns the default theme with the styles of the theme section of the config file.
+  /// # Errors
+  /// If the config is no valid TOML, the theme section has unknown keys or a style is invalid.
+  pub fn from_config(config: &str) -> Result<Self, ThemeConfigError> {
+    let config: ConfigFile =
+      toml::from_str(config).map_err(|error| ThemeConfigError::InvalidToml(error.to_string()))?;
+    let mut theme = Theme::default();
+    let section = config.theme;
+    for (key, value, style) in [
+      ("running", section.running, &mut theme.running),
+      ("paused", section.paused, &mut theme.paused),
+      ("finished", section.finished, &mut theme.finished),
+      ("expired", section.expired, &mut theme.expired),
+      ("header", section.header, &mut theme.header),
+    ] {
+      if let Some(value) = value {
+        *style =
+          Style::parse(&value).map_err(|error| ThemeConfigError::InvalidStyle { key, error })?;
+      }
+    }
+
+    Ok(theme)
+  }
+
+  /// Returns whether any style of this theme colours text.
+  pub fn is_colored(&self) -> bool {
+    [
+      &self.running,
+      &self.paused,
+      &self.finished,
+      &self.expired,
+      &self.header,
+    ]
+    .iter()
+    .any(|style| !style.is_plain())
+  }
+
+  pub fn state(&self, state: EntityState) -> &Style {
+    match state {
+      EntityState::Running => &self.running,
+      EntityState::Paused => &self.paused,
+      EntityState::Finished => &self.finished,
+      EntityState::Expired => &self.expired,
+    }
+  }
+
+  pub fn header(&self) -> &Style {
+    &self.header
+  }
+}
+
+/// Running is green, paused yellow, finished dimmed, expired red and headers are bold.
+impl Default for Theme {
+  fn default() -> Self {
+    let style = |codes: &str| Style {
+      codes: codes.to_string(),
+    };
+    Theme {
+      running: style("32"),
+      paused: style("33"),
+      finished: style("2"),
+      expired: style("31"),
+      header: style("1"),
+    }
+  }
+}
+
+impl Display for UnknownColorChoice {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(
+      f,
+      "\"{}\" is not a color choice, expected one of: {}",
+      self.0,
+      ColorChoice::NAMES.join(", ")
+    )
+  }
+}
+
+impl std::error::Error for UnknownColorChoice {}
+
+impl Display for StyleParseError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(
+      f,
+      "\"{}\" is not a style, expected bold, dim, italic, underline, a colour like green or \
+       bright-green or a number from 0 to 255",
+      self.0
+    )
+  }
+}
+
+impl std::error::Error for StyleParseError {}
+
+impl Display for ThemeConfigError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      ThemeConfigError::InvalidToml(error) => write!(f, "Invalid config file: {error}"),
+      ThemeConfigError::InvalidStyle { key, error } => write!(f, "theme.{key}: {error}"),
+    }
+  }
+}
+
+impl std::error::Error for ThemeConfigError {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_only_color_auto_on_terminal_without_no_color() {
+    assert!(ColorChoice::Auto.should_color(true, None));
+    assert!(ColorChoice::Auto.should_color(true, Some("")));
+    assert!(!ColorChoice::Auto.should_color(true, Some("1")));
+    assert!(!ColorChoice::Auto.should_color(false, None));
+    assert!(ColorChoice::Always.should_color(false, Some("1")));
+    assert!(!ColorChoice::Never.should_color(true, None));
+  }
+
+  #[test]
+  fn should_override_default_styles_from_theme_section() {
+    let config = "[theme]\nrunning = \"bold 208\"\nheader = \"\"\n\n[other]\nkey = 1\n";
+
+    let theme = Theme::from_config(config).unwrap();
+
+    assert_eq!(
+      "\x1b[1;38;5;208mx\x1b[0m",
+      theme.state(EntityState::Running).paint("x")
+    );
+    assert_eq!("x", theme.header().paint("x"));
+    assert_eq!(Theme::default().paused, theme.paused);
+  }
+
+  #[test]
+  fn should_point_at_invalid_style_key() {
+    let actual = Theme::from_config("[theme]\nexpired = \"blurple\"\n");
+
+    assert_eq!(
+      Err(ThemeConfigError::InvalidStyle {
+        key: "expired",
+        error: StyleParseError("blurple".to_string())
+      }),
+      actual
+    );
+  }
+}
@@ -1,6 +1,9 @@
+pub mod listing;
+pub mod pomodoro;
+pub mod running_policy;
 pub mod stop_watch;
 pub mod time_entities_controller;
 pub mod time_stamp;
-trait TimeEntity {
+pub(crate) trait TimeEntity {
   fn get_title(&self) -> &str;
 }
@@ -0,0 +1,776 @@
+//! Chooses which time stamps and stop watches are listed, in which order and with which
+//! columns.
+use std::{cmp::Ordering, fmt::Display};
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use regex::Regex;
+use serde::Serialize;
+
+use crate::{
+  chrono_utility::{
+    clock::Clock,
+    date_time_display::DateTimeDisplay,
+    parsing::{self, TimeParseError},
+    DateDifference,
+  },
+  format_utils::{self, Alignment, ColumnLayout, TableStyle},
+  theme::Theme,
+};
+
+use super::{
+  stop_watch::StopWatch, time_entities_controller::EntityKind, time_stamp::TimeStamp, TimeEntity,
+};
+
+const ASCENDING_SUFFIX: &str = ":asc";
+const DESCENDING_SUFFIX: &str = ":desc";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+  Title,
+  Started,
+  Ended,
+  IsPaused,
+  LastPaused,
+  Elapsed,
+  PausedTotal,
+  Active,
+  Remaining,
+  Project,
+  Tags,
+  State,
+}
+
+/// Whether a time stamp or stop watch is still counting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntityState {
+  Running,
+  Paused,
+  Finished,
+  /// Stop watch whose count down is over.
+  Expired,
+}
+
+/// Condition a time stamp or stop watch must meet to be listed.
+#[derive(Debug, Clone)]
+pub enum Filter {
+  State(EntityState),
+  Tag(String),
+  Project(String),
+  StartedAfter(DateTime<Utc>),
+  StartedBefore(DateTime<Utc>),
+  Title(String),
+  TitleMatches(Regex),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum FilterParseError {
+  /// Expression is not like key=value, key~regex, key>value or key<value.
+  InvalidExpression(String),
+  UnknownKey(String),
+  UnknownState(String),
+  InvalidMoment(TimeParseError),
+  InvalidRegex(String),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct UnknownColumnError(String);
+
+/// How the list is printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ListOutput {
+  #[default]
+  Table,
+  /// Array of [`JsonEntity`] like the HTTP API answers.
+  Json,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct UnknownListOutputError(String);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortKey {
+  column: Column,
+  descending: bool,
+}
+
+/// Filters, order, limit and columns of a list of time stamps or stop watches.
+#[derive(Debug, Clone, Default)]
+pub struct ListOptions {
+  filters: Vec<Filter>,
+  sort: Option<SortKey>,
+  limit: Option<usize>,
+  /// Columns chosen by the user. Empty means the default columns.
+  columns: Vec<Column>,
+  /// Width, overflow and borders of the table. Alignment of columns is taken from the columns.
+  table_style: TableStyle,
+  /// Colours of the header and of rows by state. None shows no colours.
+  theme: Option<Theme>,
+}
+
+/// Value of a column which is compared for sorting.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum SortValue {
+  Text(String),
+  Moment(Option<DateTime<Utc>>),
+  Length(Option<Duration>),
+  Flag(bool),
+}
+
+/// Time stamp or stop watch as one row of a list.
+pub(crate) trait ListedEntity {
+  fn time_stamp(&self) -> &TimeStamp;
+
+  /// Time left of a count down. None for time stamps.
+  fn remaining(&self, _clock: &dyn Clock) -> Option<Duration> {
+    None
+  }
+}
+
+impl ListedEntity for TimeStamp {
+  fn time_stamp(&self) -> &TimeStamp {
+    self
+  }
+}
+
+impl ListedEntity for StopWatch {
+  fn time_stamp(&self) -> &TimeStamp {
+    self.get_time_stamp()
+  }
+
+  fn remaining(&self, clock: &dyn Clock) -> Option<Duration> {
+    self.get_remaining_duration(clock)
+  }
+}
+
+impl Column {
+  pub const NAMES: [&'static str; 12] = [
+    "title",
+    "started",
+    "ended",
+    "is-paused",
+    "last-paused",
+    "elapsed",
+    "paused-total",
+    "active",
+    "remaining",
+    "project",
+    "tags",
+    "state",
+  ];
+  const ALL: [Column; 12] = [
+    Column::Title,
+    Column::Started,
+    Column::Ended,
+    Column::IsPaused,
+    Column::LastPaused,
+    Column::Elapsed,
+    Column::PausedTotal,
+    Column::Active,
+    Column::Remaining,
+    Column::Project,
+    Column::Tags,
+    Column::State,
+  ];
+  /// Columns of time stamps if the user chose none.
+  pub const TIME_STAMP_DEFAULTS: [Column; 8] = [
+    Column::Title,
+    Column::Started,
+    Column::Ended,
+    Column::IsPaused,
+    Column::LastPaused,
+    Column::Elapsed,
+    Column::PausedTotal,
+    Column::Active,
+  ];
+  /// Columns of stop watches if the user chose none.
+  pub const STOP_WATCH_DEFAULTS: [Column; 9] = [
+    Column::Title,
+    Column::Started,
+    Column::Ended,
+    Column::IsPaused,
+    Column::LastPaused,
+    Column::Elapsed,
+    Column::PausedTotal,
+    Column::Active,
+    Column::Remaining,
+  ];
+
+  /// # Errors
+  /// If the name is not one of [`Column::NAMES`].
+  pub fn from_name(name: &str) -> Result<Self, UnknownColumnError> {
+    let name = name.trim().to_lowercase().replace('_', "-");
+    Column::NAMES
+      .iter()
+      .position(|known| *known == name)
+      .map(|index| Column::ALL[index])
+      .ok_or(UnknownColumnError(name))
+  }
+
+  /// Durations are aligned to the right. Only columns of free text like titles are shrunk to
+  /// fit the table into the terminal.
+  pub fn layout(&self) -> ColumnLayout {
+    match self {
+      Column::Title | Column::Project | Column::Tags => ColumnLayout::new(Alignment::Left),
+      Column::Elapsed | Column::PausedTotal | Column::Active | Column::Remaining => {
+        ColumnLayout::new(Alignment::Right).with_shrinkable(false)
+      }
+      _ => ColumnLayout::new(Alignment::Left).with_shrinkable(false),
+    }
+  }
+
+  pub fn header(&self) -> &'static str {
+    match self {
+      Column::Title => "Title",
+      Column::Started => "Started at",
+      Column::Ended => "Ended at",
+      Column::IsPaused => "Is paused",
+      Column::LastPaused => "Last time paused",
+      Column::Elapsed => "Elapsed",
+      Column::PausedTotal => "Paused total",
+      Column::Active => "Active",
+      Column::Remaining => "Remaining",
+      Column::Project => "Project",
+      Column::Tags => "Tags",
+      Column::State => "State",
+    }
+  }
+}
+
+impl EntityState {
+  pub const NAMES: [&'static str; 4] = ["running", "paused", "finished", "expired"];
+
+  /// Returns the state of a time stamp or stop watch. A finished one is never expired.
+  pub(crate) fn of<T: ListedEntity + ?Sized>(entity: &T, clock: &dyn Clock) -> Self {
+    let time_stamp = entity.time_stamp();
+    if time_stamp.get_ended().is_some() {
+      EntityState::Finished
+    } else if time_stamp.is_paused() {
+      EntityState::Paused
+    } else if matches!(entity.remaining(clock), Some(remaining) if remaining <= Duration::zero()) {
+      EntityState::Expired
+    } else {
+      EntityState::Running
+    }
+  }
+
+  fn from_name(name: &str) -> Result<Self, FilterParseError> {
+    match name.trim().to_lowercase().as_str() {
+      "running" => Ok(EntityState::Running),
+      "paused" => Ok(EntityState::Paused),
+      "finished" => Ok(EntityState::Finished),
+      "expired" => Ok(EntityState::Expired),
+      _ => Err(FilterParseError::UnknownState(name.to_string())),
+    }
+  }
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      EntityState::Running => Self::NAMES[0],
+      EntityState::Paused => Self::NAMES[1],
+      EntityState::Finished => Self::NAMES[2],
+      EntityState::Expired => Self::NAMES[3],
+    }
+  }
+}
+
+impl Filter {
+  /// Parses expressions like state=running, tag=billable, project=Shop, started>yesterday,
+  /// started<2022-10-01, title=Meeting or title~^Meet as regular expression. Moments are
+  /// interpreted like [`parsing::parse_moment`] does relative to param now.
+  /// # Errors
+  /// If the expression has an unknown key or an invalid value.
+  pub fn parse<Tz: TimeZone>(
+    expression: &str,
+    now: &DateTime<Tz>,
+  ) -> Result<Self, FilterParseError> {
+    let operator_at = expression
+      .find(['=', '~', '>', '<'])
+      .ok_or_else(|| FilterParseError::InvalidExpression(expression.to_string()))?;
+    let key = expression[..operator_at].trim().to_lowercase();
+    let operator = &expression[operator_at..operator_at + 1];
+    let value = expression[operator_at + 1..].trim();
+
+    match (key.as_str(), operator) {
+      ("state", "=") => Ok(Filter::State(EntityState::from_name(value)?)),
+      ("tag", "=") => Ok(Filter::Tag(value.to_string())),
+      ("project", "=") => Ok(Filter::Project(value.to_string())),
+      ("started", ">") => Ok(Filter::StartedAfter(parse_filter_moment(value, now)?)),
+      ("started", "<") => Ok(Filter::StartedBefore(parse_filter_moment(value, now)?)),
+      ("title", "=") => Ok(Filter::Title(value.to_string())),
+      ("title", "~") => Regex::new(value)
+        .map(Filter::TitleMatches)
+        .map_err(|error| FilterParseError::InvalidRegex(error.to_string())),
+      ("state" | "tag" | "project" | "started" | "title", _) => {
+        Err(FilterParseError::InvalidExpression(expression.to_string()))
+      }
+      _ => Err(FilterParseError::UnknownKey(key)),
+    }
+  }
+
+  pub(crate) fn matches<T: ListedEntity + ?Sized>(&self, entity: &T, clock: &dyn Clock) -> bool {
+    let time_stamp = entity.time_stamp();
+    match self {
+      Filter::State(state) => EntityState::of(entity, clock) == *state,
+      Filter::Tag(tag) => time_stamp.get_tags().iter().any(|has| has == tag),
+      Filter::Project(project) => time_stamp.get_project() == Some(project.as_str()),
+      Filter::StartedAfter(moment) => time_stamp.get_started() > *moment,
+      Filter::StartedBefore(moment) => time_stamp.get_started() < *moment,
+      Filter::Title(title) => time_stamp.get_title() == title,
+      Filter::TitleMatches(regex) => regex.is_match(time_stamp.get_title()),
+    }
+  }
+}
+
+fn parse_filter_moment<Tz: TimeZone>(
+  value: &str,
+  now: &DateTime<Tz>,
+) -> Result<DateTime<Utc>, FilterParseError> {
+  parsing::parse_moment(value, now).map_err(FilterParseError::InvalidMoment)
+}
+
+impl SortKey {
+  /// Parses a column name optionally followed by :asc or :desc like active:desc.
+  /// # Errors
+  /// If the column is unknown.
+  pub fn parse(input: &str) -> Result<Self, UnknownColumnError> {
+    let input = input.trim().to_lowercase();
+    let (name, descending) = if let Some(name) = input.strip_suffix(DESCENDING_SUFFIX) {
+      (name, true)
+    } else if let Some(name) = input.strip_suffix(ASCENDING_SUFFIX) {
+      (name, false)
+    } else {
+      (input.as_str(), false)
+    };
+
+    Ok(SortKey {
+      column: Column::from_name(name)?,
+      descending,
+    })
+  }
+
+  fn compare<T: ListedEntity>(&self, left: &T, right: &T, clock: &dyn Clock) -> Ordering {
+    let ordering = sort_value(left, self.column, clock).cmp(&sort_value(right, self.column, clock));
+    if self.descending {
+      ordering.reverse()
+    } else {
+      ordering
+    }
+  }
+}
+
+impl ListOutput {
+  pub const NAMES: [&'static str; 2] = ["table", "json"];
+
+  /// # Errors
+  /// If the name is not one of [`ListOutput::NAMES`].
+  pub fn from_name(name: &str) -> Result<Self, UnknownListOutputError> {
+    match name.trim().to_lowercase().as_str() {
+      "table" => Ok(ListOutput::Table),
+      "json" => Ok(ListOutput::Json),
+      _ => Err(UnknownListOutputError(name.to_string())),
+    }
+  }
+}
+
+impl ListOptions {
+  pub fn new() -> Self {
+    Default::default()
+  }
+
+  /// Only entries which match all filters are listed.
+  pub fn with_filters(self, filters: Vec<Filter>) -> Self {
+    ListOptions { filters, ..self }
+  }
+
+  pub fn with_sort(self, sort: Option<SortKey>) -> Self {
+    ListOptions { sort, ..self }
+  }
+
+  /// Lists at most param limit entries after filtering and sorting.
+  pub fn with_limit(self, limit: Option<usize>) -> Self {
+    ListOptions { limit, ..self }
+  }
+
+  /// Lists the given columns in the given order. No columns means the default columns.
+  pub fn with_columns(self, columns: Vec<Column>) -> Self {
+    ListOptions { columns, ..self }
+  }
+
+  pub fn with_table_style(self, table_style: TableStyle) -> Self {
+    ListOptions {
+      table_style,
+      ..self
+    }
+  }
+
+  /// Colours rows by the state of their entity and the header with the styles of param theme.
+  pub fn with_theme(self, theme: Theme) -> Self {
+    ListOptions {
+      theme: Some(theme),
+      ..self
+    }
+  }
+
+  /// Returns the entities which match all filters in the chosen order up to the limit.
+  /// Without a sort key the order of creation is kept.
+  pub(crate) fn select<'a, T: ListedEntity>(
+    &self,
+    entities: &'a [T],
+    clock: &dyn Clock,
+  ) -> Vec<&'a T> {
+    let mut selected: Vec<&T> = entities
+      .iter()
+      .filter(|entity| {
+        self
+          .filters
+          .iter()
+          .all(|filter| filter.matches(*entity, clock))
+      })
+      .collect();
+    if let Some(sort) = &self.sort {
+      selected.sort_by(|left, right| sort.compare(*left, *right, clock));
+    }
+    if let Some(limit) = self.limit {
+      selected.truncate(limit);
+    }
+    selected
+  }
+
+  /// Returns the chosen columns or param defaults if none are chosen.
+  fn columns_or<'a>(&'a self, defaults: &'a [Column]) -> &'a [Column] {
+    if self.columns.is_empty() {
+      defaults
+    } else {
+      &self.columns
+    }
+  }
+
+  /// Returns a table with a header and a row for every entity with the chosen columns or
+  /// param default_columns. Every column is laid out as its [`Column::layout`] within the width
+  /// and borders of the table style.
+  pub(crate) fn to_text_table<T: ListedEntity>(
+    &self,
+    entities: &[&T],
+    default_columns: &[Column],
+    display: &DateTimeDisplay,
+    clock: &dyn Clock,
+  ) -> String {
+    let columns = self.columns_or(default_columns);
+    let plain = Theme::plain();
+    let theme = self.theme.as_ref().unwrap_or(&plain);
+    let mut rows = vec![columns
+      .iter()
+      .map(|column| theme.header().paint(column.header()))
+      .collect::<Vec<String>>()];
+    rows.extend(entities.iter().map(|entity| {
+      let style = theme.state(EntityState::of(*entity, clock));
+      cells(*entity, columns, display, clock)
+        .iter()
+        .map(|cell| style.paint(cell))
+        .collect()
+    }));
+
+    let style = self
+      .table_style
+      .clone()
+      .with_columns(columns.iter().map(Column::layout).collect());
+    format_utils::format_table(&rows, &style)
+  }
+}
+
+/// Returns the text of the given columns of the entity. Moments are shown in the time zone and
+/// format of param display. Durations are counted up to now of param clock.
+pub(crate) fn cells<T: ListedEntity + ?Sized>(
+  entity: &T,
+  columns: &[Column],
+  display: &DateTimeDisplay,
+  clock: &dyn Clock,
+) -> Vec<String> {
+  columns
+    .iter()
+    .map(|column| cell(entity, *column, display, clock))
+    .collect()
+}
+
+/// Time stamp or stop watch as JSON like the HTTP API and `all --output json` show it. Durations
+/// are given in seconds like in the JSON of reports.
+#[derive(Debug, Serialize)]
+pub struct JsonEntity {
+  kind: EntityKind,
+  title: String,
+  state: &'static str,
+  started: DateTime<Utc>,
+  ended: Option<DateTime<Utc>>,
+  project: Option<String>,
+  tags: Vec<String>,
+  note: Option<String>,
+  active_seconds: i64,
+  paused_seconds: i64,
+  /// Only given for stop watches. Negative once expired.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  remaining_seconds: Option<i64>,
+}
+
+impl JsonEntity {
+  pub(crate) fn of<T: ListedEntity + ?Sized>(
+    entity: &T,
+    kind: EntityKind,
+    clock: &dyn Clock,
+  ) -> Self {
+    let time_stamp = entity.time_stamp();
+    JsonEntity {
+      kind,
+      title: time_stamp.get_title().to_string(),
+      state: EntityState::of(entity, clock).name(),
+      started: time_stamp.get_started(),
+      ended: time_stamp.get_ended(),
+      project: time_stamp.get_project().map(str::to_string),
+      tags: time_stamp.get_tags().to_vec(),
+      note: time_stamp.get_note().map(str::to_string),
+      active_seconds: time_stamp.get_active_duration(clock).num_seconds(),
+      paused_seconds: time_stamp.get_paused_duration(clock).num_seconds(),
+      remaining_seconds: entity
+        .remaining(clock)
+        .map(|remaining| remaining.num_seconds()),
+    }
+  }
+}
+
+fn cell<T: ListedEntity + ?Sized>(
+  entity: &T,
+  column: Column,
+  display: &DateTimeDisplay,
+  clock: &dyn Clock,
+) -> String {
+  let time_stamp = entity.time_stamp();
+  let moment_text = |moment: Option<DateTime<Utc>>| match moment {
+    Some(moment) => display.format(&moment),
+    None => TimeStamp::NOT_AVAILABLE.to_string(),
+  };
+  let duration_text = |duration: Duration| display.format_duration(&DateDifference::from(duration));
+
+  match column {
+    Column::Title => time_stamp.get_title().to_string(),
+    Column::Started => display.format(&time_stamp.get_started()),
+    Column::Ended => moment_text(time_stamp.get_ended()),
+    Column::IsPaused => if time_stamp.is_paused() { "yes" } else { "no" }.to_string(),
+    Column::LastPaused => moment_text(time_stamp.get_last_paused()),
+    Column::Elapsed => duration_text(time_stamp.get_elapsed_duration(clock)),
+    Column::PausedTotal => duration_text(time_stamp.get_paused_duration(clock)),
+    Column::Active => duration_text(time_stamp.get_active_duration(clock)),
+    Column::Remaining => match entity.remaining(clock) {
+      Some(remaining) if remaining <= Duration::zero() => {
+        let overrun = -DateDifference::from(remaining);
+        format!(
+          "{} {}",
+          StopWatch::EXPIRED,
+          display.format_duration(&overrun)
+        )
+      }
+      Some(remaining) => duration_text(remaining),
+      None => TimeStamp::NOT_AVAILABLE.to_string(),
+    },
+    Column::Project => time_stamp
+      .get_project()
+      .unwrap_or(TimeStamp::NOT_AVAILABLE)
+      .to_string(),
+    Column::Tags => time_stamp.get_tags().join(", "),
+    Column::State => EntityState::of(entity, clock).name().to_string(),
+  }
+}
+
+fn sort_value<T: ListedEntity + ?Sized>(
+  entity: &T,
+  column: Column,
+  clock: &dyn Clock,
+) -> SortValue {
+  let time_stamp = entity.time_stamp();
+  match column {
+    Column::Title => SortValue::Text(time_stamp.get_title().to_lowercase()),
+    Column::Started => SortValue::Moment(Some(time_stamp.get_started())),
+    Column::Ended => SortValue::Moment(time_stamp.get_ended()),
+    Column::IsPaused => SortValue::Flag(time_stamp.is_paused()),
+    Column::LastPaused => SortValue::Moment(time_stamp.get_last_paused()),
+    Column::Elapsed => SortValue::Length(Some(time_stamp.get_elapsed_duration(clock))),
+    Column::PausedTotal => SortValue::Length(Some(time_stamp.get_paused_duration(clock))),
+    Column::Active => SortValue::Length(Some(time_stamp.get_active_duration(clock))),
+    Column::Remaining => SortValue::Length(entity.remaining(clock)),
+    Column::Project => SortValue::Text(time_stamp.get_project().unwrap_or_default().to_lowercase()),
+    Column::Tags => SortValue::Text(time_stamp.get_tags().join(",").to_lowercase()),
+    Column::State => SortValue::Text(EntityState::of(entity, clock).name().to_string()),
+  }
+}
+
+impl Display for FilterParseError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      FilterParseError::InvalidExpression(expression) => write!(
+        f,
+        "\"{expression}\" is not a filter like state=running, tag=billable, project=Shop, \
+         started>yesterday, started<2022-10-01, title=Meeting or title~^Meet"
+      ),
+      FilterParseError::UnknownKey(key) => write!(
+        f,
+        "\"{key}\" can not be filtered, expected one of: state, tag, project, started, title"
+      ),
+      FilterParseError::UnknownState(state) => write!(
+        f,
+        "\"{state}\" is not a state, expected one of: {}",
+        EntityState::NAMES.join(", ")
+      ),
+      FilterParseError::InvalidMoment(error) => write!(f, "{error}"),
+      FilterParseError::InvalidRegex(error) => write!(f, "{error}"),
+    }
+  }
+}
+
+impl std::error::Error for FilterParseError {}
+
+impl Display for UnknownColumnError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(
+      f,
+      "\"{}\" is not a column, expected one of: {}",
+      self.0,
+      Column::NAMES.join(", ")
+    )
+  }
+}
+
+impl std::error::Error for UnknownColumnError {}
+
+impl Display for UnknownListOutputError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(
+      f,
+      "\"{}\" is not an output, expected one of: {}",
+      self.0,
+      ListOutput::NAMES.join(", ")
+    )
+  }
+}
+
+impl std::error::Error for UnknownListOutputError {}
+
+#[cfg(test)]
+mod tests {
+  use chrono::TimeZone;
+
+  use super::*;
+  use crate::chrono_utility::clock::{FixedClock, ManualClock};
+
+  fn now() -> DateTime<Utc> {
+    Utc.ymd(2022, 10, 20).and_hms(12, 0, 0)
+  }
+
+  fn sample_time_stamps(clock: &ManualClock) -> Vec<TimeStamp> {
+    let mut paused = TimeStamp::with_started("Meeting", now() - Duration::hours(3))
+      .with_project(Some("Shop"))
+      .with_tags(&["billable"]);
+    clock.set(now() - Duration::hours(2));
+    paused.pause(clock).unwrap();
+    let mut finished = TimeStamp::with_started("Coding", now() - Duration::hours(5));
+    clock.set(now() - Duration::hours(1));
+    finished.finish(clock).unwrap();
+    let running = TimeStamp::with_started("Meetup", now() - Duration::minutes(30));
+    clock.set(now());
+
+    vec![paused, finished, running]
+  }
+
+  fn titles(selected: &[&TimeStamp]) -> Vec<String> {
+    selected
+      .iter()
+      .map(|time_stamp| time_stamp.get_title().to_string())
+      .collect()
+  }
+
+  #[test]
+  fn should_only_select_matching_all_filters() {
+    let clock = ManualClock::new(now());
+    let time_stamps = sample_time_stamps(&clock);
+    let options = |expressions: &[&str]| {
+      let filters = expressions
+        .iter()
+        .map(|expression| Filter::parse(expression, &now()).unwrap())
+        .collect();
+      ListOptions::new().with_filters(filters)
+    };
+
+    assert_eq!(
+      vec!["Meetup"],
+      titles(&options(&["state=running"]).select(&time_stamps, &clock))
+    );
+    assert_eq!(
+      vec!["Meeting"],
+      titles(&options(&["tag=billable", "project=Shop"]).select(&time_stamps, &clock))
+    );
+    assert_eq!(
+      vec!["Meeting", "Meetup"],
+      titles(&options(&["title~^Meet"]).select(&time_stamps, &clock))
+    );
+    assert_eq!(
+      vec!["Meeting", "Meetup"],
+      titles(&options(&["started>4h ago"]).select(&time_stamps, &clock))
+    );
+    assert_eq!(
+      vec!["Coding"],
+      titles(&options(&["started<4h ago"]).select(&time_stamps, &clock))
+    );
+  }
+
+  #[test]
+  fn should_sort_by_typed_value_and_limit() {
+    let clock = ManualClock::new(now());
+    let time_stamps = sample_time_stamps(&clock);
+    let options = ListOptions::new()
+      .with_sort(Some(SortKey::parse("active:desc").unwrap()))
+      .with_limit(Some(2));
+
+    // Active: Coding 4h, Meeting 1h, Meetup 30m
+    assert_eq!(
+      vec!["Coding", "Meeting"],
+      titles(&options.select(&time_stamps, &clock))
+    );
+  }
+
+  #[test]
+  fn should_reject_invalid_filters_and_columns() {
+    assert_eq!(
+      Some(FilterParseError::UnknownKey("colour".to_string())),
+      Filter::parse("colour=red", &now()).err()
+    );
+    assert_eq!(
+      Some(FilterParseError::UnknownState("sleeping".to_string())),
+      Filter::parse("state=sleeping", &now()).err()
+    );
+    assert!(matches!(
+      Filter::parse("title~(", &now()),
+      Err(FilterParseError::InvalidRegex(_))
+    ));
+    assert_eq!(
+      Err(UnknownColumnError("colour".to_string())),
+      SortKey::parse("colour:desc")
+    );
+  }
+
+  #[test]
+  fn should_show_chosen_columns_in_order() {
+    let time_stamp = TimeStamp::with_started("Coding", now() - Duration::minutes(90))
+      .with_project(Some("Shop"))
+      .with_tags(&["dev", "billable"]);
+    let columns = [Column::Active, Column::Title, Column::Tags, Column::State];
+
+    let actual = cells(
+      &time_stamp,
+      &columns,
+      &DateTimeDisplay::default(),
+      &FixedClock::new(now()),
+    );
+
+    assert_eq!(
+      vec!["01:30:00", "Coding", "dev, billable", "running"],
+      actual
+    );
+  }
+}
@@ -0,0 +1,151 @@
+//! Work and breaks in turns. The work of a task is counted down by a stop watch with the title of
+//! the task. When it expires, the completed cycle is noted on its time stamp, which is paused
+//! during the following break. Every break is a stop watch of its own titled like the task with
+//! the suffix " break". When the break expires, the task resumes for the next work cycle.
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+const BREAK_SUFFIX: &str = " break";
+
+/// Lengths of work and breaks of a pomodoro.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PomodoroSettings {
+  /// Seconds of one work cycle.
+  work: i64,
+  /// Seconds of a short break.
+  short_break: i64,
+  /// Seconds of a long break.
+  long_break: i64,
+  /// After this many work cycles the break is long.
+  long_break_every: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakLength {
+  Short,
+  Long,
+}
+
+/// Part a stop watch plays in a pomodoro.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "phase", rename_all = "snake_case")]
+pub enum PomodoroPhase {
+  /// Counts down the work of the task.
+  Work(PomodoroSettings),
+  /// Counts down a break after work on the task.
+  Break { task: String, length: BreakLength },
+}
+
+/// What a pomodoro is at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PomodoroState {
+  Work,
+  Break(BreakLength),
+  /// The task is finished.
+  Over,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PomodoroStatus {
+  pub state: PomodoroState,
+  /// Number of completed work cycles.
+  pub completed: usize,
+  /// Time left of the current work cycle or break.
+  pub remaining: Duration,
+}
+
+impl PomodoroSettings {
+  /// A long break every 0 cycles is taken as every cycle.
+  pub fn new(
+    work: Duration,
+    short_break: Duration,
+    long_break: Duration,
+    long_break_every: u32,
+  ) -> Self {
+    PomodoroSettings {
+      work: work.num_seconds(),
+      short_break: short_break.num_seconds(),
+      long_break: long_break.num_seconds(),
+      long_break_every: long_break_every.max(1),
+    }
+  }
+
+  /// Every length is at least a second, so work and breaks can not follow each other endlessly
+  /// at the same moment.
+  pub fn work(&self) -> Duration {
+    Duration::seconds(self.work.max(1))
+  }
+
+  pub fn short_break(&self) -> Duration {
+    Duration::seconds(self.short_break.max(1))
+  }
+
+  pub fn long_break(&self) -> Duration {
+    Duration::seconds(self.long_break.max(1))
+  }
+
+  pub fn long_break_every(&self) -> u32 {
+    self.long_break_every
+  }
+
+  /// Returns the break after the given number of completed work cycles.
+  pub fn break_after(&self, completed: usize) -> (BreakLength, Duration) {
+    if completed > 0 && completed.is_multiple_of(self.long_break_every as usize) {
+      (BreakLength::Long, self.long_break())
+    } else {
+      (BreakLength::Short, self.short_break())
+    }
+  }
+}
+
+/// 25 minutes of work, 5 minutes short breaks and 15 minutes long breaks after every 4 cycles.
+impl Default for PomodoroSettings {
+  fn default() -> Self {
+    PomodoroSettings::new(
+      Duration::minutes(25),
+      Duration::minutes(5),
+      Duration::minutes(15),
+      4,
+    )
+  }
+}
+
+impl BreakLength {
+  pub fn name(&self) -> &'static str {
+    match self {
+      BreakLength::Short => "Short break",
+      BreakLength::Long => "Long break",
+    }
+  }
+}
+
+/// Returns the title of the stop watch of the breaks of a task.
+pub fn break_title(task: &str) -> String {
+  format!("{task}{BREAK_SUFFIX}")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_take_long_break_after_every_nth_cycle() {
+    let settings = PomodoroSettings::new(
+      Duration::minutes(25),
+      Duration::minutes(5),
+      Duration::minutes(20),
+      2,
+    );
+
+    assert_eq!(
+      (BreakLength::Short, Duration::minutes(5)),
+      settings.break_after(1)
+    );
+    assert_eq!(
+      (BreakLength::Long, Duration::minutes(20)),
+      settings.break_after(2)
+    );
+    assert_eq!(BreakLength::Short, settings.break_after(3).0);
+  }
+}
@@ -0,0 +1,112 @@
+//! How many time stamps may run at once. It is set in the tracking section of the config file:
+//! ```toml
+//! [tracking]
+//! policy = "auto-pause"
+//! ```
+//! Stop watches are timers and are not affected.
+use std::fmt::Display;
+
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RunningPolicy {
+  /// Any number of time stamps run at once.
+  #[default]
+  Concurrent,
+  /// Starting or resuming a time stamp pauses the others which run.
+  AutoPause,
+  /// A time stamp is not started or resumed while another one runs or ran in the meantime.
+  Refuse,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PolicyConfigError {
+  InvalidToml(String),
+  UnknownPolicy(String),
+}
+
+/// Part of the config file which is about tracking. Other sections are ignored.
+#[derive(Deserialize, Default)]
+struct ConfigFile {
+  #[serde(default)]
+  tracking: TrackingSection,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct TrackingSection {
+  policy: Option<String>,
+}
+
+impl RunningPolicy {
+  pub const NAMES: [&'static str; 3] = ["concurrent", "auto-pause", "refuse"];
+
+  /// # Errors
+  /// If the name is not one of [`RunningPolicy::NAMES`].
+  pub fn from_name(name: &str) -> Result<Self, PolicyConfigError> {
+    match name.trim().to_lowercase().as_str() {
+      "concurrent" => Ok(RunningPolicy::Concurrent),
+      "auto-pause" => Ok(RunningPolicy::AutoPause),
+      "refuse" => Ok(RunningPolicy::Refuse),
+      _ => Err(PolicyConfigError::UnknownPolicy(name.to_string())),
+    }
+  }
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      RunningPolicy::Concurrent => Self::NAMES[0],
+      RunningPolicy::AutoPause => Self::NAMES[1],
+      RunningPolicy::Refuse => Self::NAMES[2],
+    }
+  }
+
+  /// Returns the policy of the tracking section of the config file. Concurrent if none is set.
+  /// # Errors
+  /// If the config is no valid TOML or the policy is unknown.
+  pub fn from_config(config: &str) -> Result<Self, PolicyConfigError> {
+    let config: ConfigFile =
+      toml::from_str(config).map_err(|error| PolicyConfigError::InvalidToml(error.to_string()))?;
+    match config.tracking.policy {
+      Some(name) => Self::from_name(&name),
+      None => Ok(RunningPolicy::default()),
+    }
+  }
+}
+
+impl Display for PolicyConfigError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      PolicyConfigError::InvalidToml(error) => write!(f, "Invalid config file: {error}"),
+      PolicyConfigError::UnknownPolicy(name) => write!(
+        f,
+        "tracking.policy: Unknown policy \"{name}\", expected one of: {}",
+        RunningPolicy::NAMES.join(", ")
+      ),
+    }
+  }
+}
+
+impl std::error::Error for PolicyConfigError {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_read_policy_of_tracking_section() {
+    assert_eq!(
+      Ok(RunningPolicy::AutoPause),
+      RunningPolicy::from_config(
+        "[theme]\nheader = \"bold\"\n\n[tracking]\npolicy = \"auto-pause\"\n"
+      )
+    );
+    assert_eq!(
+      Ok(RunningPolicy::Concurrent),
+      RunningPolicy::from_config("")
+    );
+    assert_eq!(
+      Err(PolicyConfigError::UnknownPolicy("single".to_string())),
+      RunningPolicy::from_config("[tracking]\npolicy = \"single\"\n")
+    );
+  }
+}
@@ -1,9 +1,211 @@
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 
-use super::time_stamp::TimeStamp;
+use crate::{
+  chrono_utility::{clock::Clock, date_time_display::DateTimeDisplay},
+  format_utils,
+};
 
-#[derive(Serialize, Deserialize)]
+use super::{
+  listing::{self, Column},
+  pomodoro::{PomodoroPhase, PomodoroSettings},
+  time_stamp::TimeStamp,
+  TimeEntity,
+};
+
+/// Time stamp which counts down from a given duration. Only active time, not paused time, is
+/// used up from the count down.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct StopWatch {
   time_stamp: TimeStamp,
+  /// Length of the count down in seconds.
   count_down: Option<usize>,
+  /// Whether the expiry was noted already, so hooks run only once per stop watch.
+  #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+  expiry_recorded: bool,
+  /// Part of a pomodoro this stop watch plays if any.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pomodoro: Option<PomodoroPhase>,
+}
+
+impl StopWatch {
+  /// Used if a stop watch has no time left. Followed by the time over the count down.
+  pub(crate) const EXPIRED: &'static str = "Expired";
+
+  pub fn new(time_stamp: TimeStamp, count_down: Duration) -> Self {
+    StopWatch {
+      time_stamp,
+      count_down: Some(count_down.num_seconds().max(0) as usize),
+      expiry_recorded: false,
+      pomodoro: None,
+    }
+  }
+
+  /// Returns this stop watch as the given part of a pomodoro.
+  pub fn with_pomodoro(self, pomodoro: PomodoroPhase) -> Self {
+    StopWatch {
+      pomodoro: Some(pomodoro),
+      ..self
+    }
+  }
+
+  pub fn get_pomodoro(&self) -> Option<&PomodoroPhase> {
+    self.pomodoro.as_ref()
+  }
+
+  /// Lengthens the count down, so an expired stop watch runs again and can expire again.
+  pub(crate) fn extend_count_down(&mut self, by: Duration) {
+    let count_down = self.count_down.unwrap_or_default() as i64 + by.num_seconds();
+    self.count_down = Some(count_down.max(0) as usize);
+    self.expiry_recorded = false;
+  }
+
+  /// Runs the finished work stop watch of a pomodoro again for a full work cycle of param
+  /// settings. The time since it was finished counts as paused, completed cycles are kept.
+  pub(crate) fn restart_pomodoro(&mut self, settings: PomodoroSettings, clock: &dyn Clock) {
+    self.time_stamp.reopen();
+    let _ = self.time_stamp.resume(clock);
+    let remaining = self
+      .get_remaining_duration(clock)
+      .unwrap_or_else(Duration::zero);
+    self.extend_count_down(settings.work() - remaining);
+    self.pomodoro = Some(PomodoroPhase::Work(settings));
+  }
+
+  pub fn get_time_stamp(&self) -> &TimeStamp {
+    &self.time_stamp
+  }
+
+  pub fn get_time_stamp_mut(&mut self) -> &mut TimeStamp {
+    &mut self.time_stamp
+  }
+
+  /// Returns the time left until the count down is over. It is negative if the count down is
+  /// over. None if the stop watch has no count down.
+  pub fn get_remaining_duration(&self, clock: &dyn Clock) -> Option<Duration> {
+    self.count_down.map(|count_down| {
+      Duration::seconds(count_down as i64) - self.time_stamp.get_active_duration(clock)
+    })
+  }
+
+  pub fn is_expired(&self, clock: &dyn Clock) -> bool {
+    matches!(self.get_remaining_duration(clock), Some(remaining) if remaining <= Duration::zero())
+  }
+
+  /// Returns the moment at which the count down was used up or None if it is not over. It is
+  /// found in the active time, as paused time does not use up the count down.
+  pub fn get_expired_at(&self, clock: &dyn Clock) -> Option<DateTime<Utc>> {
+    let remaining = self.get_remaining_duration(clock)?;
+    if remaining > Duration::zero() {
+      return None;
+    }
+    let mut over = -remaining;
+    for (from, to) in self
+      .time_stamp
+      .get_active_intervals(clock)
+      .into_iter()
+      .rev()
+    {
+      if to - from >= over {
+        return Some(to - over);
+      }
+      over = over - (to - from);
+    }
+    Some(self.time_stamp.get_started())
+  }
+
+  /// Returns true once if the stop watch is expired and not finished. Returns false after that,
+  /// also in later runs as this is saved with the stop watch.
+  pub(crate) fn record_expiry(&mut self, clock: &dyn Clock) -> bool {
+    let is_new =
+      !self.expiry_recorded && self.time_stamp.get_ended().is_none() && self.is_expired(clock);
+    self.expiry_recorded |= is_new;
+    is_new
+  }
+
+  pub fn create_text_table_from_stop_watches(
+    data: &[StopWatch],
+    display: &DateTimeDisplay,
+    clock: &dyn Clock,
+  ) -> String {
+    let mut text_data: Vec<Vec<String>> = data
+      .iter()
+      .map(|stop_watch| stop_watch.to_str_vec(display, clock))
+      .collect();
+    let headers = Column::STOP_WATCH_DEFAULTS
+      .iter()
+      .map(|column| column.header().to_string())
+      .collect();
+
+    text_data.insert(0, headers);
+    format_utils::format_to_text_table(&text_data, 2)
+  }
+
+  /// Outputs the same columns as a time stamp and in addition:
+  /// - Remaining: Time left until the count down is over. Expired with the time over the count
+  ///   down if it is over. N/A if there is no count down.
+  fn to_str_vec(&self, display: &DateTimeDisplay, clock: &dyn Clock) -> Vec<String> {
+    listing::cells(self, &Column::STOP_WATCH_DEFAULTS, display, clock)
+  }
+}
+
+impl TimeEntity for StopWatch {
+  fn get_title(&self) -> &str {
+    self.time_stamp.get_title()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono::{TimeZone, Utc};
+
+  use super::*;
+  use crate::chrono_utility::{self, clock::ManualClock, date_time_display::DisplayZone};
+
+  fn setup_tea_watch() -> (StopWatch, ManualClock) {
+    let started = Utc.ymd(2022, 10, 20).and_hms(9, 0, 0);
+    let stop_watch = StopWatch::new(
+      TimeStamp::with_started("Tea", started),
+      chrono_utility::duration_with_hms(0, 4, 0),
+    );
+
+    (stop_watch, ManualClock::new(started))
+  }
+
+  #[test]
+  fn should_show_remaining_count_down_as_last_column() {
+    let (stop_watch, clock) = setup_tea_watch();
+    let display = DateTimeDisplay::new(DisplayZone::Named(chrono_tz::UTC), Default::default());
+    clock.advance(Duration::minutes(1));
+
+    let actual = stop_watch.to_str_vec(&display, &clock);
+
+    assert!(!stop_watch.is_expired(&clock));
+    assert_eq!(Some("00:03:00"), actual.last().map(String::as_str));
+  }
+
+  #[test]
+  fn should_show_overrun_after_expired() {
+    let (stop_watch, clock) = setup_tea_watch();
+    let display = DateTimeDisplay::new(DisplayZone::Named(chrono_tz::UTC), Default::default());
+    clock.advance(Duration::minutes(6));
+
+    let actual = stop_watch.to_str_vec(&display, &clock);
+
+    assert!(stop_watch.is_expired(&clock));
+    assert_eq!(Some("Expired 00:02:00"), actual.last().map(String::as_str));
+  }
+
+  #[test]
+  fn should_not_use_up_count_down_while_paused() {
+    let (mut stop_watch, clock) = setup_tea_watch();
+    clock.advance(Duration::minutes(1));
+    stop_watch.time_stamp.pause(&clock).unwrap();
+    clock.advance(Duration::minutes(10));
+
+    assert_eq!(
+      Some(Duration::minutes(3)),
+      stop_watch.get_remaining_duration(&clock)
+    );
+  }
 }
@@ -1,9 +1,30 @@
-use std::fmt::Display;
+use std::{fmt::Display, sync::Arc};
 
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 
-use super::{stop_watch::StopWatch, time_stamp::TimeStamp, TimeEntity};
+use crate::{
+  billing::{HourlyRate, ProjectRates},
+  chrono_utility::{
+    clock::{Clock, FixedClock, SystemClock},
+    date_time_display::{DateTimeDisplay, DisplayZone},
+  },
+  report::{Report, ReportRange},
+};
 
+use super::{
+  listing::{Column, JsonEntity, ListOptions},
+  pomodoro::{self, PomodoroPhase, PomodoroSettings, PomodoroState, PomodoroStatus},
+  running_policy::RunningPolicy,
+  stop_watch::StopWatch,
+  time_stamp::{
+    time_stamp_errors::{ResumeError, StopError},
+    TimeStamp,
+  },
+  TimeEntity,
+};
+
+#[derive(Debug)]
 pub struct DuplicateTitleError;
 
 const DUPLICATE_ADDED_TIME_ERROR_MSG: &str = "Title already exists on another time stamp";
@@ -13,27 +34,138 @@ impl Display for DuplicateTitleError {
   }
 }
 
+/// Whether an operation is about a time stamp or a stop watch. Titles are unique per kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityKind {
+  TimeStamp,
+  StopWatch,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum EntityOperationError {
+  NotFound(EntityKind, String),
+  /// Another entity of the same kind has the title.
+  DuplicateTitle(String),
+  BlankTitle,
+  AlreadyPaused,
+  NotPaused,
+  AlreadyFinished,
+  /// The running policy refuses it because the time stamp with the title would run at the same
+  /// time.
+  OtherRunning(String),
+  /// The stop watch with the title is not the work of a pomodoro.
+  NoPomodoro(String),
+}
+
+/// Change of a time stamp or stop watch which hooks react to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntityEventKind {
+  Created,
+  Paused,
+  Resumed,
+  Finished,
+  Expired,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntityEvent {
+  pub event: EntityEventKind,
+  pub kind: EntityKind,
+  pub title: String,
+}
+
+/// What happened to the target of a switch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SwitchTarget {
+  Started,
+  Resumed,
+  AlreadyRunning,
+}
+
+/// Changes of a switch to another time stamp.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SwitchOutcome {
+  pub title: String,
+  pub target: SwitchTarget,
+  /// Titles of the time stamps which were running before.
+  pub stopped: Vec<String>,
+  /// Whether those were finished instead of paused.
+  pub finished: bool,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct TimeEntitiesController {
   time_stamps: Vec<TimeStamp>,
   stop_watches: Vec<StopWatch>,
+  /// Hourly rates by project name. Used for time stamps without a rate of their own.
+  #[serde(default, skip_serializing_if = "ProjectRates::is_empty")]
+  project_rates: ProjectRates,
+  /// Source of the current moment for all operations on time stamps and stop watches.
+  #[serde(skip, default = "system_clock")]
+  clock: Arc<dyn Clock>,
+  /// How many time stamps may run at once.
+  #[serde(skip)]
+  policy: RunningPolicy,
+  /// Changes since the last call of [`TimeEntitiesController::take_events`].
+  #[serde(skip)]
+  events: Vec<EntityEvent>,
+}
+
+fn system_clock() -> Arc<dyn Clock> {
+  Arc::new(SystemClock)
 }
+
 #[allow(dead_code)]
 impl TimeEntitiesController {
   pub fn empty() -> Self {
-    TimeEntitiesController {
-      time_stamps: Vec::new(),
-      stop_watches: Vec::new(),
-    }
+    TimeEntitiesController::new(Vec::new(), Vec::new())
   }
 
   pub fn new(time_stamps: Vec<TimeStamp>, stop_watches: Vec<StopWatch>) -> Self {
     TimeEntitiesController {
       time_stamps,
       stop_watches,
+      project_rates: ProjectRates::new(),
+      clock: system_clock(),
+      policy: RunningPolicy::default(),
+      events: Vec::new(),
     }
   }
 
+  /// Returns this controller which takes the current moment from the given clock instead of
+  /// the system clock.
+  pub fn with_clock(self, clock: Arc<dyn Clock>) -> Self {
+    TimeEntitiesController { clock, ..self }
+  }
+
+  /// Returns this controller which applies the policy when time stamps start or resume.
+  pub fn with_policy(self, policy: RunningPolicy) -> Self {
+    TimeEntitiesController { policy, ..self }
+  }
+
+  pub fn policy(&self) -> RunningPolicy {
+    self.policy
+  }
+
+  pub fn clock(&self) -> &dyn Clock {
+    self.clock.as_ref()
+  }
+
+  /// Returns the clock to hand it to a controller which replaces this one.
+  pub fn shared_clock(&self) -> Arc<dyn Clock> {
+    Arc::clone(&self.clock)
+  }
+
+  pub fn time_stamps(&self) -> &[TimeStamp] {
+    &self.time_stamps
+  }
+
+  pub fn stop_watches(&self) -> &[StopWatch] {
+    &self.stop_watches
+  }
+
   pub fn from_json(json: &str) -> Result<TimeEntitiesController, serde_json::Error> {
     let new = serde_json::from_str(json)?;
     Ok(new)
@@ -49,38 +181,786 @@ impl TimeEntitiesController {
     }
   }
 
-  pub fn add_new_time_stamp(&mut self, new_title: &str) -> Result<(), DuplicateTitleError> {
-    let duplicate_title_on_stamps = Self::has_duplicate_on(&self.time_stamps, new_title);
+  pub fn add_new_time_stamp(&mut self, new_title: &str) -> Result<(), EntityOperationError> {
+    let now = self.clock.now();
+    self.add_time_stamp_started_at(new_title, now)
+  }
+
+  /// Adds a time stamp which started at the given moment. Allows to track time which started
+  /// before the user created the time stamp.
+  pub fn add_time_stamp_started_at(
+    &mut self,
+    new_title: &str,
+    started: DateTime<Utc>,
+  ) -> Result<(), EntityOperationError> {
+    self.add_time_stamp(TimeStamp::with_started(new_title, started))
+  }
+
+  /// Adds a prepared time stamp, for example one with a project or tags. The running policy
+  /// applies if it runs.
+  /// # Errors
+  /// If another time stamp has the same title or the policy refuses it.
+  pub fn add_time_stamp(&mut self, new_time_stamp: TimeStamp) -> Result<(), EntityOperationError> {
+    let title = new_time_stamp.get_title();
+    let duplicate_title_on_stamps = Self::has_duplicate_on(&self.time_stamps, title);
     if duplicate_title_on_stamps {
-      return Err(DuplicateTitleError);
+      return Err(EntityOperationError::DuplicateTitle(title.to_string()));
+    }
+    if new_time_stamp.get_ended().is_none() && !new_time_stamp.is_paused() {
+      self.make_room_for(title, new_time_stamp.get_started())?;
     }
-    let new_time_stamp = TimeStamp::new(new_title);
 
+    self.push_event(
+      EntityEventKind::Created,
+      EntityKind::TimeStamp,
+      new_time_stamp.get_title(),
+    );
     self.time_stamps.push(new_time_stamp);
     Ok(())
   }
 
+  /// # Errors
+  /// If another stop watch has the same title.
+  pub fn add_stop_watch(&mut self, new_stop_watch: StopWatch) -> Result<(), DuplicateTitleError> {
+    if Self::has_duplicate_on(&self.stop_watches, new_stop_watch.get_title()) {
+      return Err(DuplicateTitleError);
+    }
+
+    self.push_event(
+      EntityEventKind::Created,
+      EntityKind::StopWatch,
+      new_stop_watch.get_title(),
+    );
+    self.stop_watches.push(new_stop_watch);
+    Ok(())
+  }
+
+  /// Returns the time stamp or the time stamp of the stop watch with the title.
+  pub fn time_stamp_of(&self, kind: EntityKind, title: &str) -> Option<&TimeStamp> {
+    match kind {
+      EntityKind::TimeStamp => self
+        .time_stamps
+        .iter()
+        .find(|time_stamp| time_stamp.get_title() == title),
+      EntityKind::StopWatch => self
+        .stop_watches
+        .iter()
+        .find(|stop_watch| stop_watch.get_title() == title)
+        .map(StopWatch::get_time_stamp),
+    }
+  }
+
+  /// Pauses the time stamp or stop watch with the title. Returns the moment of pausing.
+  /// # Errors
+  /// If there is no such entity or it is paused or finished already.
+  pub fn pause(
+    &mut self,
+    kind: EntityKind,
+    title: &str,
+  ) -> Result<DateTime<Utc>, EntityOperationError> {
+    let clock = Arc::clone(&self.clock);
+    let time_stamp = self.time_stamp_mut(kind, title)?;
+    let paused = match time_stamp.pause(clock.as_ref()) {
+      Ok(paused) => *paused,
+      Err(error) => {
+        return Err(match error.error_kind {
+          StopError::IsStoppedAlready(_) => EntityOperationError::AlreadyPaused,
+          StopError::IsFinishedAlready(_) => EntityOperationError::AlreadyFinished,
+        })
+      }
+    };
+    self.push_event(EntityEventKind::Paused, kind, title);
+    Ok(paused)
+  }
+
+  /// Resumes the time stamp or stop watch with the title. Returns the moment of resuming. The
+  /// running policy applies to paused time stamps.
+  /// # Errors
+  /// If there is no such entity, it is finished, not paused or the policy refuses it.
+  pub fn resume(
+    &mut self,
+    kind: EntityKind,
+    title: &str,
+  ) -> Result<DateTime<Utc>, EntityOperationError> {
+    let clock = Arc::clone(&self.clock);
+    if kind == EntityKind::TimeStamp {
+      let time_stamp = self.time_stamp_mut(kind, title)?;
+      if time_stamp.get_ended().is_some() {
+        return Err(EntityOperationError::AlreadyFinished);
+      }
+      if time_stamp.is_paused() {
+        self.make_room_for(title, clock.now())?;
+      }
+    }
+    let resumed = self
+      .time_stamp_mut(kind, title)?
+      .resume(clock.as_ref())
+      .map_err(|error| match error.error_kind {
+        ResumeError::IsNotPaused => EntityOperationError::NotPaused,
+        ResumeError::IsFinishedAlready | ResumeError::IsStoppedAlready => {
+          EntityOperationError::AlreadyFinished
+        }
+      })?;
+    self.push_event(EntityEventKind::Resumed, kind, title);
+    Ok(resumed)
+  }
+
+  /// Finishes the time stamp or stop watch with the title. Returns the moment of finishing.
+  /// # Errors
+  /// If there is no such entity or it is finished already.
+  pub fn finish(
+    &mut self,
+    kind: EntityKind,
+    title: &str,
+  ) -> Result<DateTime<Utc>, EntityOperationError> {
+    let clock = Arc::clone(&self.clock);
+    let finished = self
+      .time_stamp_mut(kind, title)?
+      .finish(clock.as_ref())
+      .copied()
+      .map_err(|_| EntityOperationError::AlreadyFinished)?;
+    self.push_event(EntityEventKind::Finished, kind, title);
+    Ok(finished)
+  }
+
+  /// Applies the running policy before the time stamp with the title runs from the given
+  /// moment on. Auto-pause pauses the other running time stamps at that moment or at their
+  /// last start if it is later. Refuse fails if another time stamp runs or ran since then.
+  fn make_room_for(
+    &mut self,
+    title: &str,
+    from: DateTime<Utc>,
+  ) -> Result<(), EntityOperationError> {
+    let clock = Arc::clone(&self.clock);
+    let is_running =
+      |time_stamp: &TimeStamp| time_stamp.get_ended().is_none() && !time_stamp.is_paused();
+    match self.policy {
+      RunningPolicy::Concurrent => Ok(()),
+      RunningPolicy::Refuse => {
+        let overlapping = self.time_stamps.iter().find(|time_stamp| {
+          time_stamp.get_title() != title
+            && (is_running(time_stamp)
+              || time_stamp
+                .get_active_intervals(clock.as_ref())
+                .iter()
+                .any(|(_, to)| *to > from))
+        });
+        match overlapping {
+          Some(other) => Err(EntityOperationError::OtherRunning(
+            other.get_title().to_string(),
+          )),
+          None => Ok(()),
+        }
+      }
+      RunningPolicy::AutoPause => {
+        let running: Vec<String> = self
+          .time_stamps
+          .iter()
+          .filter(|time_stamp| time_stamp.get_title() != title && is_running(time_stamp))
+          .map(|time_stamp| time_stamp.get_title().to_string())
+          .collect();
+        for other in running {
+          let time_stamp = self.time_stamp_mut(EntityKind::TimeStamp, &other)?;
+          let last_started = time_stamp
+            .get_active_intervals(clock.as_ref())
+            .last()
+            .map_or(time_stamp.get_started(), |(started, _)| *started);
+          let paused_at = FixedClock::new(from.max(last_started).min(clock.now()));
+          if time_stamp.pause(&paused_at).is_ok() {
+            self.push_event(EntityEventKind::Paused, EntityKind::TimeStamp, &other);
+          }
+        }
+        Ok(())
+      }
+    }
+  }
+
+  /// Notes an expired event for every unfinished stop watch which expired since the last call,
+  /// also if it expired in an earlier run. Pomodoros catch up on all phases which ended since
+  /// then. Returns the titles of those stop watches, each once.
+  pub fn record_expiries(&mut self) -> Vec<String> {
+    let clock = Arc::clone(&self.clock);
+    let mut all_expired: Vec<String> = Vec::new();
+    loop {
+      let expired: Vec<String> = self
+        .stop_watches
+        .iter_mut()
+        .filter_map(|stop_watch| {
+          stop_watch
+            .record_expiry(clock.as_ref())
+            .then(|| stop_watch.get_title().to_string())
+        })
+        .collect();
+      if expired.is_empty() {
+        return all_expired;
+      }
+      for title in expired {
+        self.push_event(EntityEventKind::Expired, EntityKind::StopWatch, &title);
+        self.advance_pomodoro(&title);
+        if !all_expired.contains(&title) {
+          all_expired.push(title);
+        }
+      }
+    }
+  }
+
+  /// Starts a pomodoro on the task: a stop watch with its title counting down the first work
+  /// cycle. Breaks follow as the work cycles expire, see [`Self::record_expiries`]. If the
+  /// pomodoro on the task is over, its stop watch runs again and keeps the completed cycles.
+  /// Nothing changes if it runs already.
+  /// # Errors
+  /// If a stop watch with the title is no pomodoro or one with the title of its break is no
+  /// break of it.
+  pub fn start_pomodoro(
+    &mut self,
+    task: &str,
+    settings: PomodoroSettings,
+  ) -> Result<(), EntityOperationError> {
+    self.position_of_pomodoro_break(task)?;
+    let Some(index) = Self::position_of(&self.stop_watches, task) else {
+      let work = StopWatch::new(
+        TimeStamp::with_started(task, self.clock.now()),
+        settings.work(),
+      )
+      .with_pomodoro(PomodoroPhase::Work(settings));
+      return self
+        .add_stop_watch(work)
+        .map_err(|_| EntityOperationError::DuplicateTitle(task.to_string()));
+    };
+    let clock = Arc::clone(&self.clock);
+    let work = &mut self.stop_watches[index];
+    if !matches!(work.get_pomodoro(), Some(PomodoroPhase::Work(_))) {
+      return Err(EntityOperationError::NoPomodoro(task.to_string()));
+    }
+    if work.get_time_stamp().get_ended().is_some() {
+      work.restart_pomodoro(settings, clock.as_ref());
+      self.push_event(EntityEventKind::Resumed, EntityKind::StopWatch, task);
+    }
+    Ok(())
+  }
+
+  /// Finishes the pomodoro on the task and deletes its break.
+  /// # Errors
+  /// If there is no pomodoro on the task, it is finished already or a stop watch with the title
+  /// of its break is no break of it. Nothing changes then.
+  pub fn stop_pomodoro(&mut self, task: &str) -> Result<(), EntityOperationError> {
+    if self.pomodoro_settings(task).is_none() {
+      return Err(EntityOperationError::NotFound(
+        EntityKind::StopWatch,
+        task.to_string(),
+      ));
+    }
+    self.delete_pomodoro_break(task)?;
+    self.finish(EntityKind::StopWatch, task).map(|_| ())
+  }
+
+  /// Returns the position of the break of the pomodoro on the task or None if there is none.
+  /// # Errors
+  /// If a stop watch with the title of the break is no break of the pomodoro on the task.
+  fn position_of_pomodoro_break(&self, task: &str) -> Result<Option<usize>, EntityOperationError> {
+    let break_title = pomodoro::break_title(task);
+    let Some(index) = Self::position_of(&self.stop_watches, &break_title) else {
+      return Ok(None);
+    };
+    match self.stop_watches[index].get_pomodoro() {
+      Some(PomodoroPhase::Break { task: of, .. }) if of == task => Ok(Some(index)),
+      _ => Err(EntityOperationError::NoPomodoro(break_title)),
+    }
+  }
+
+  /// Deletes the break of the pomodoro on the task if there is one.
+  /// # Errors
+  /// If a stop watch with the title of the break is no break of the pomodoro on the task. It is
+  /// kept then.
+  fn delete_pomodoro_break(&mut self, task: &str) -> Result<(), EntityOperationError> {
+    if let Some(index) = self.position_of_pomodoro_break(task)? {
+      self.stop_watches.remove(index);
+    }
+    Ok(())
+  }
+
+  /// Returns where the pomodoro on the task stands or None if there is none.
+  pub fn pomodoro_status(&self, task: &str) -> Option<PomodoroStatus> {
+    let clock = self.clock();
+    let work = self.stop_watches.iter().find(|stop_watch| {
+      stop_watch.get_title() == task
+        && matches!(stop_watch.get_pomodoro(), Some(PomodoroPhase::Work(_)))
+    })?;
+    let completed = work.get_time_stamp().get_pomodoros().len();
+    let break_title = pomodoro::break_title(task);
+    let running_break = self.stop_watches.iter().find(|stop_watch| {
+      stop_watch.get_title() == break_title && stop_watch.get_time_stamp().get_ended().is_none()
+    });
+
+    let (state, stop_watch) = match (work.get_time_stamp().get_ended(), running_break) {
+      (Some(_), _) => (PomodoroState::Over, work),
+      (None, Some(running_break)) => match running_break.get_pomodoro() {
+        Some(PomodoroPhase::Break { length, .. }) => (PomodoroState::Break(*length), running_break),
+        _ => (PomodoroState::Work, work),
+      },
+      (None, None) => (PomodoroState::Work, work),
+    };
+    let remaining = match state {
+      PomodoroState::Over => Duration::zero(),
+      _ => stop_watch
+        .get_remaining_duration(clock)
+        .unwrap_or_else(Duration::zero),
+    };
+    Some(PomodoroStatus {
+      state,
+      completed,
+      remaining,
+    })
+  }
+
+  fn pomodoro_settings(&self, task: &str) -> Option<PomodoroSettings> {
+    self
+      .stop_watches
+      .iter()
+      .find(|stop_watch| stop_watch.get_title() == task)
+      .and_then(|stop_watch| match stop_watch.get_pomodoro() {
+        Some(PomodoroPhase::Work(settings)) => Some(*settings),
+        _ => None,
+      })
+  }
+
+  /// Moves the pomodoro of the expired stop watch on at the moment it expired, which lies before
+  /// now if no invocation noticed it in time. After a work cycle the task is paused and its
+  /// break starts. After a break it is finished and the task resumes for a full work cycle.
+  /// A stop watch of the user with the title of the break is kept, the task stays paused then.
+  fn advance_pomodoro(&mut self, expired_title: &str) {
+    let Some(index) = Self::position_of(&self.stop_watches, expired_title) else {
+      return;
+    };
+    let Some(expired_at) = self.stop_watches[index].get_expired_at(self.clock.as_ref()) else {
+      return;
+    };
+    let at = FixedClock::new(expired_at);
+    match self.stop_watches[index].get_pomodoro().cloned() {
+      Some(PomodoroPhase::Work(settings)) => {
+        let time_stamp = self.stop_watches[index].get_time_stamp_mut();
+        time_stamp.complete_pomodoro(expired_at);
+        let completed = time_stamp.get_pomodoros().len();
+        if time_stamp.pause(&at).is_ok() {
+          self.push_event(
+            EntityEventKind::Paused,
+            EntityKind::StopWatch,
+            expired_title,
+          );
+        }
+
+        let (length, duration) = settings.break_after(completed);
+        if self.delete_pomodoro_break(expired_title).is_err() {
+          return;
+        }
+        let break_title = pomodoro::break_title(expired_title);
+        let break_watch =
+          StopWatch::new(TimeStamp::with_started(&break_title, expired_at), duration)
+            .with_pomodoro(PomodoroPhase::Break {
+              task: expired_title.to_string(),
+              length,
+            });
+        let _ = self.add_stop_watch(break_watch);
+      }
+      Some(PomodoroPhase::Break { task, .. }) => {
+        if self.stop_watches[index]
+          .get_time_stamp_mut()
+          .finish(&at)
+          .is_err()
+        {
+          return;
+        }
+        self.push_event(
+          EntityEventKind::Finished,
+          EntityKind::StopWatch,
+          expired_title,
+        );
+        let Some(settings) = self.pomodoro_settings(&task) else {
+          return;
+        };
+        let Some(work) = self
+          .stop_watches
+          .iter_mut()
+          .find(|stop_watch| stop_watch.get_title() == task)
+        else {
+          return;
+        };
+        if work.get_time_stamp_mut().resume(&at).is_ok() {
+          let overrun = -work
+            .get_remaining_duration(&at)
+            .unwrap_or_else(Duration::zero)
+            .min(Duration::zero());
+          work.extend_count_down(settings.work() + overrun);
+          self.push_event(EntityEventKind::Resumed, EntityKind::StopWatch, &task);
+        }
+      }
+      None => (),
+    }
+  }
+
+  /// Returns the changes since the last call.
+  pub fn take_events(&mut self) -> Vec<EntityEvent> {
+    std::mem::take(&mut self.events)
+  }
+
+  fn push_event(&mut self, event: EntityEventKind, kind: EntityKind, title: &str) {
+    self.events.push(EntityEvent {
+      event,
+      kind,
+      title: title.to_string(),
+    });
+  }
+
+  /// Pauses every other running time stamp, or finishes them with param finish_others, and
+  /// starts the time stamp with the title or resumes it if paused. Everything is checked
+  /// before the first change, so nothing changes if it fails. Stop watches are left as they are.
+  /// # Errors
+  /// If the title is blank or its time stamp is finished.
+  pub fn switch_to(
+    &mut self,
+    title: &str,
+    finish_others: bool,
+  ) -> Result<SwitchOutcome, EntityOperationError> {
+    let title = title.trim();
+    if title.is_empty() {
+      return Err(EntityOperationError::BlankTitle);
+    }
+    let target = match self
+      .time_stamps
+      .iter()
+      .find(|time_stamp| time_stamp.get_title() == title)
+    {
+      None => SwitchTarget::Started,
+      Some(time_stamp) if time_stamp.get_ended().is_some() => {
+        return Err(EntityOperationError::AlreadyFinished)
+      }
+      Some(time_stamp) if time_stamp.is_paused() => SwitchTarget::Resumed,
+      Some(_) => SwitchTarget::AlreadyRunning,
+    };
+    let stopped: Vec<String> = self
+      .time_stamps
+      .iter()
+      .filter(|time_stamp| {
+        time_stamp.get_title() != title
+          && time_stamp.get_ended().is_none()
+          && !time_stamp.is_paused()
+      })
+      .map(|time_stamp| time_stamp.get_title().to_string())
+      .collect();
+
+    for other in &stopped {
+      if finish_others {
+        self.finish(EntityKind::TimeStamp, other)?;
+      } else {
+        self.pause(EntityKind::TimeStamp, other)?;
+      }
+    }
+    match target {
+      SwitchTarget::Started => self.add_new_time_stamp(title)?,
+      SwitchTarget::Resumed => {
+        self.resume(EntityKind::TimeStamp, title)?;
+      }
+      SwitchTarget::AlreadyRunning => (),
+    }
+    Ok(SwitchOutcome {
+      title: title.to_string(),
+      target,
+      stopped,
+      finished: finish_others,
+    })
+  }
+
+  /// # Errors
+  /// If there is no such entity.
+  pub fn delete(&mut self, kind: EntityKind, title: &str) -> Result<(), EntityOperationError> {
+    let not_found = || EntityOperationError::NotFound(kind, title.to_string());
+    match kind {
+      EntityKind::TimeStamp => {
+        let index = Self::position_of(&self.time_stamps, title).ok_or_else(not_found)?;
+        self.time_stamps.remove(index);
+      }
+      EntityKind::StopWatch => {
+        let index = Self::position_of(&self.stop_watches, title).ok_or_else(not_found)?;
+        self.stop_watches.remove(index);
+      }
+    }
+    Ok(())
+  }
+
+  /// Moves the time stamp and the stop watch with the title into param target, together with
+  /// the hourly rate of their project if target has none for it. Nothing starts or stops, so
+  /// the running policy does not apply and no events are noted. Returns how many entities moved.
+  /// # Errors
+  /// If neither exists or target has one of the same kind with the title. Nothing moves then.
+  pub fn move_to(
+    &mut self,
+    title: &str,
+    target: &mut TimeEntitiesController,
+  ) -> Result<usize, EntityOperationError> {
+    let time_stamp = Self::position_of(&self.time_stamps, title);
+    let stop_watch = Self::position_of(&self.stop_watches, title);
+    if time_stamp.is_none() && stop_watch.is_none() {
+      return Err(EntityOperationError::NotFound(
+        EntityKind::TimeStamp,
+        title.to_string(),
+      ));
+    }
+    if (time_stamp.is_some() && Self::has_duplicate_on(&target.time_stamps, title))
+      || (stop_watch.is_some() && Self::has_duplicate_on(&target.stop_watches, title))
+    {
+      return Err(EntityOperationError::DuplicateTitle(title.to_string()));
+    }
+
+    let mut projects = Vec::new();
+    if let Some(index) = time_stamp {
+      let time_stamp = self.time_stamps.remove(index);
+      projects.extend(time_stamp.get_project().map(str::to_string));
+      target.time_stamps.push(time_stamp);
+    }
+    if let Some(index) = stop_watch {
+      let stop_watch = self.stop_watches.remove(index);
+      projects.extend(
+        stop_watch
+          .get_time_stamp()
+          .get_project()
+          .map(str::to_string),
+      );
+      target.stop_watches.push(stop_watch);
+    }
+    for project in projects {
+      if let Some(rate) = self.project_rates.get(&project) {
+        target
+          .project_rates
+          .entry(project)
+          .or_insert_with(|| rate.clone());
+      }
+    }
+    Ok(usize::from(time_stamp.is_some()) + usize::from(stop_watch.is_some()))
+  }
+
+  /// Gives the time stamp or stop watch with the title a new title.
+  /// # Errors
+  /// If there is no such entity or the new title is blank or taken by another one of its kind.
+  pub fn rename(
+    &mut self,
+    kind: EntityKind,
+    title: &str,
+    new_title: &str,
+  ) -> Result<(), EntityOperationError> {
+    let new_title = new_title.trim();
+    if new_title.is_empty() {
+      return Err(EntityOperationError::BlankTitle);
+    }
+    let is_taken = new_title != title
+      && match kind {
+        EntityKind::TimeStamp => Self::has_duplicate_on(&self.time_stamps, new_title),
+        EntityKind::StopWatch => Self::has_duplicate_on(&self.stop_watches, new_title),
+      };
+    if is_taken {
+      return Err(EntityOperationError::DuplicateTitle(new_title.to_string()));
+    }
+
+    self.time_stamp_mut(kind, title)?.set_title(new_title);
+    Ok(())
+  }
+
+  /// Sets the note of the time stamp or stop watch with the title. A blank note removes it.
+  /// # Errors
+  /// If there is no such entity.
+  pub fn set_note(
+    &mut self,
+    kind: EntityKind,
+    title: &str,
+    note: Option<&str>,
+  ) -> Result<(), EntityOperationError> {
+    self.time_stamp_mut(kind, title)?.set_note(note);
+    Ok(())
+  }
+
+  fn time_stamp_mut(
+    &mut self,
+    kind: EntityKind,
+    title: &str,
+  ) -> Result<&mut TimeStamp, EntityOperationError> {
+    let found = match kind {
+      EntityKind::TimeStamp => self
+        .time_stamps
+        .iter_mut()
+        .find(|time_stamp| time_stamp.get_title() == title),
+      EntityKind::StopWatch => self
+        .stop_watches
+        .iter_mut()
+        .find(|stop_watch| stop_watch.get_title() == title)
+        .map(StopWatch::get_time_stamp_mut),
+    };
+    found.ok_or_else(|| EntityOperationError::NotFound(kind, title.to_string()))
+  }
+
+  /// Returns the active time of all time stamps and stop watches within the range of days.
+  /// Days begin at midnight in the given time zone.
+  pub fn create_report(&self, range: ReportRange, zone: DisplayZone) -> Report {
+    // Breaks of pomodoros are no work.
+    let all_time_stamps = self.time_stamps.iter().chain(
+      self
+        .stop_watches
+        .iter()
+        .filter(|stop_watch| {
+          !matches!(stop_watch.get_pomodoro(), Some(PomodoroPhase::Break { .. }))
+        })
+        .map(StopWatch::get_time_stamp),
+    );
+    Report::create(all_time_stamps, range, zone, self.clock())
+      .with_project_rates(&self.project_rates)
+  }
+
+  pub fn project_rates(&self) -> &ProjectRates {
+    &self.project_rates
+  }
+
+  /// Sets the hourly rate of a project or removes it if param rate is None.
+  /// Returns the rate the project had before.
+  pub fn set_project_rate(
+    &mut self,
+    project: &str,
+    rate: Option<HourlyRate>,
+  ) -> Option<HourlyRate> {
+    let project = project.trim().to_string();
+    match rate {
+      Some(rate) => self.project_rates.insert(project, rate),
+      None => self.project_rates.remove(&project),
+    }
+  }
+
+  /// Returns all time stamps and stop watches as text tables. Moments are shown in the time zone
+  /// and format of param display.
+  pub fn to_text_table(&self, display: &DateTimeDisplay) -> String {
+    self.to_listed_text_table(display, &ListOptions::new())
+  }
+
+  /// Returns the time stamps and stop watches chosen by param options as text tables with the
+  /// chosen columns. Filters, sorting and limit apply to both tables on their own.
+  pub fn to_listed_text_table(&self, display: &DateTimeDisplay, options: &ListOptions) -> String {
+    let clock = self.clock();
+    let time_stamps = options.select(&self.time_stamps, clock);
+    let table_time_stamps =
+      options.to_text_table(&time_stamps, &Column::TIME_STAMP_DEFAULTS, display, clock);
+    let mut text = format!("Time stamps: \n{table_time_stamps}\n");
+
+    if !self.stop_watches.is_empty() {
+      let stop_watches = options.select(&self.stop_watches, clock);
+      let table_stop_watches =
+        options.to_text_table(&stop_watches, &Column::STOP_WATCH_DEFAULTS, display, clock);
+      text.push_str(&format!("Stop watches: \n{table_stop_watches}\n"));
+    }
+
+    text
+  }
+
+  /// Returns the time stamps or stop watches chosen by param options as JSON objects.
+  pub fn to_json_entities(&self, kind: EntityKind, options: &ListOptions) -> Vec<JsonEntity> {
+    let clock = self.clock();
+    match kind {
+      EntityKind::TimeStamp => options
+        .select(&self.time_stamps, clock)
+        .into_iter()
+        .map(|time_stamp| JsonEntity::of(time_stamp, kind, clock))
+        .collect(),
+      EntityKind::StopWatch => options
+        .select(&self.stop_watches, clock)
+        .into_iter()
+        .map(|stop_watch| JsonEntity::of(stop_watch, kind, clock))
+        .collect(),
+    }
+  }
+
+  /// Returns the time stamps and then the stop watches chosen by param options as a JSON array
+  /// in the schema of the HTTP API.
+  pub fn to_listed_json(&self, options: &ListOptions) -> Result<String, serde_json::Error> {
+    let mut listed = self.to_json_entities(EntityKind::TimeStamp, options);
+    listed.extend(self.to_json_entities(EntityKind::StopWatch, options));
+    serde_json::to_string_pretty(&listed)
+  }
+
   fn has_duplicate_on<T: TimeEntity>(entities: &[T], title: &str) -> bool {
     entities.iter().any(|entity| entity.get_title() == title)
   }
+
+  fn position_of<T: TimeEntity>(entities: &[T], title: &str) -> Option<usize> {
+    entities
+      .iter()
+      .position(|entity| entity.get_title() == title)
+  }
+}
+
+impl EntityEventKind {
+  pub const NAMES: [&'static str; 5] = ["create", "pause", "resume", "finish", "expired"];
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      EntityEventKind::Created => Self::NAMES[0],
+      EntityEventKind::Paused => Self::NAMES[1],
+      EntityEventKind::Resumed => Self::NAMES[2],
+      EntityEventKind::Finished => Self::NAMES[3],
+      EntityEventKind::Expired => Self::NAMES[4],
+    }
+  }
 }
 
+impl EntityKind {
+  pub fn name(&self) -> &'static str {
+    match self {
+      EntityKind::TimeStamp => "Time stamp",
+      EntityKind::StopWatch => "Stop watch",
+    }
+  }
+}
+
+impl Display for EntityOperationError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      EntityOperationError::NotFound(kind, title) => {
+        write!(f, "{} \"{title}\" does not exist", kind.name())
+      }
+      EntityOperationError::DuplicateTitle(_) => write!(f, "{}", DUPLICATE_ADDED_TIME_ERROR_MSG),
+      EntityOperationError::BlankTitle => write!(f, "Title must not be blank"),
+      EntityOperationError::AlreadyPaused => write!(f, "Is already paused"),
+      EntityOperationError::NotPaused => write!(f, "Is not paused"),
+      EntityOperationError::AlreadyFinished => write!(f, "Is already finished"),
+      EntityOperationError::OtherRunning(title) => write!(
+        f,
+        "Time stamp \"{title}\" would run at the same time, which the running policy refuses"
+      ),
+      EntityOperationError::NoPomodoro(title) => {
+        write!(f, "Stop watch \"{title}\" exists and is no pomodoro")
+      }
+    }
+  }
+}
+
+impl std::error::Error for EntityOperationError {}
+
 impl Display for TimeEntitiesController {
+  /// Shows moments in the local time zone and in the default format.
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-    let table_time_stamps = TimeStamp::create_text_table_from_time_stamps(&self.time_stamps);
-    writeln!(f, "Time stamps: \n{table_time_stamps}")
+    write!(f, "{}", self.to_text_table(&DateTimeDisplay::default()))
   }
 }
 #[cfg(test)]
 mod test {
   use super::*;
-  use crate::time_entities::time_stamp::TimeStamp;
+  use crate::{
+    chrono_utility::clock::{FixedClock, ManualClock},
+    format_utils::TableStyle,
+    time_entities::{pomodoro::BreakLength, time_stamp::TimeStamp},
+  };
+  use chrono::TimeZone;
 
   fn create_fake_timestamps() -> Vec<TimeStamp> {
+    let clock = FixedClock::new(Utc.ymd(2022, 10, 20).and_hms(7, 15, 0));
     vec![
-      TimeStamp::new("1"),
-      TimeStamp::new("2"),
-      TimeStamp::new("3"),
+      TimeStamp::new("1", &clock),
+      TimeStamp::new("2", &clock),
+      TimeStamp::new("3", &clock),
     ]
   }
 
@@ -89,7 +969,7 @@ mod test {
     let time_stamps = create_fake_timestamps();
     let mut time_container = TimeEntitiesController::new(time_stamps, Vec::new());
 
-    let result = time_container.add_new_time_stamp(&"Unique");
+    let result = time_container.add_new_time_stamp("Unique");
 
     match result {
       Ok(_) => (),
@@ -102,7 +982,7 @@ mod test {
     let time_stamps = create_fake_timestamps();
     let mut time_container = TimeEntitiesController::new(time_stamps, Vec::new());
 
-    let result = time_container.add_new_time_stamp(&"1");
+    let result = time_container.add_new_time_stamp("1");
 
     match result {
       Ok(_) => panic!("Duplicate title added did not raise an error"),
@@ -112,4 +992,443 @@ mod test {
       ),
     }
   }
+
+  #[test]
+  fn should_list_chosen_time_stamps_and_stop_watches_as_json() {
+    let started = Utc.ymd(2022, 10, 20).and_hms(9, 0, 0);
+    let controller = TimeEntitiesController::new(
+      vec![
+        TimeStamp::with_started("Coding", started),
+        TimeStamp::with_started("Meeting", started),
+      ],
+      vec![StopWatch::new(
+        TimeStamp::with_started("Tea", started),
+        Duration::minutes(4),
+      )],
+    )
+    .with_clock(Arc::new(FixedClock::new(started + Duration::minutes(1))));
+    let options = ListOptions::new().with_limit(Some(1));
+
+    let listed: serde_json::Value =
+      serde_json::from_str(&controller.to_listed_json(&options).unwrap()).unwrap();
+
+    assert_eq!(
+      serde_json::json!([
+        {
+          "kind": "time_stamp",
+          "title": "Coding",
+          "state": "running",
+          "started": "2022-10-20T09:00:00Z",
+          "ended": null,
+          "project": null,
+          "tags": [],
+          "note": null,
+          "active_seconds": 60,
+          "paused_seconds": 0
+        },
+        {
+          "kind": "stop_watch",
+          "title": "Tea",
+          "state": "running",
+          "started": "2022-10-20T09:00:00Z",
+          "ended": null,
+          "project": null,
+          "tags": [],
+          "note": null,
+          "active_seconds": 60,
+          "paused_seconds": 0,
+          "remaining_seconds": 180
+        }
+      ]),
+      listed
+    );
+  }
+
+  #[test]
+  fn should_keep_whole_titles_if_default_columns_overflow_80_columns() {
+    let started = Utc.ymd(2022, 10, 20).and_hms(9, 0, 0);
+    let controller = TimeEntitiesController::new(
+      vec![
+        TimeStamp::with_started("Title of a weekly meeting", started),
+        TimeStamp::with_started("A very long title", started),
+      ],
+      Vec::new(),
+    )
+    .with_clock(Arc::new(FixedClock::new(started + Duration::minutes(1))));
+    let display = DateTimeDisplay::new(DisplayZone::Named(chrono_tz::UTC), Default::default());
+    let options =
+      ListOptions::new().with_table_style(TableStyle::default().with_max_table_width(Some(80)));
+
+    let listed = controller.to_listed_text_table(&display, &options);
+
+    assert!(listed.contains("Title of a weekly meeting"), "{listed}");
+    assert!(listed.contains("A very long title"), "{listed}");
+  }
+
+  #[test]
+  fn should_add_time_stamp_with_given_start() {
+    let mut time_container = TimeEntitiesController::empty();
+    let started = Utc.ymd(2022, 10, 20).and_hms(7, 15, 0);
+
+    time_container
+      .add_time_stamp_started_at("Backdated", started)
+      .unwrap();
+
+    assert_eq!(
+      vec![TimeStamp::with_started("Backdated", started)],
+      time_container.time_stamps
+    );
+  }
+
+  #[test]
+  fn should_pause_resume_and_finish_by_title() {
+    let started = Utc.ymd(2022, 10, 20).and_hms(9, 0, 0);
+    let mut time_container =
+      TimeEntitiesController::new(vec![TimeStamp::with_started("Coding", started)], Vec::new())
+        .with_clock(Arc::new(FixedClock::new(
+          started + chrono::Duration::hours(1),
+        )));
+    let kind = EntityKind::TimeStamp;
+
+    time_container.pause(kind, "Coding").unwrap();
+    assert_eq!(
+      Err(EntityOperationError::AlreadyPaused),
+      time_container.pause(kind, "Coding")
+    );
+    time_container.resume(kind, "Coding").unwrap();
+    assert_eq!(
+      Err(EntityOperationError::NotPaused),
+      time_container.resume(kind, "Coding")
+    );
+    time_container.finish(kind, "Coding").unwrap();
+
+    assert_eq!(
+      Err(EntityOperationError::AlreadyFinished),
+      time_container.resume(kind, "Coding")
+    );
+    assert_eq!(
+      Err(EntityOperationError::NotFound(
+        EntityKind::StopWatch,
+        "Coding".to_string()
+      )),
+      time_container.pause(EntityKind::StopWatch, "Coding")
+    );
+  }
+
+  #[test]
+  fn should_record_each_expiry_once_as_event() {
+    let started = Utc.ymd(2022, 10, 20).and_hms(9, 0, 0);
+    let clock = Arc::new(ManualClock::new(started));
+    let mut time_container = TimeEntitiesController::new(
+      Vec::new(),
+      vec![StopWatch::new(
+        TimeStamp::with_started("Tea", started),
+        chrono::Duration::minutes(4),
+      )],
+    )
+    .with_clock(clock.clone());
+
+    time_container.pause(EntityKind::StopWatch, "Tea").unwrap();
+    time_container.resume(EntityKind::StopWatch, "Tea").unwrap();
+    assert!(time_container.record_expiries().is_empty());
+    clock.advance(chrono::Duration::minutes(5));
+    assert_eq!(vec!["Tea"], time_container.record_expiries());
+    assert!(time_container.record_expiries().is_empty());
+
+    let events: Vec<EntityEventKind> = time_container
+      .take_events()
+      .iter()
+      .map(|event| event.event)
+      .collect();
+    assert_eq!(
+      vec![
+        EntityEventKind::Paused,
+        EntityEventKind::Resumed,
+        EntityEventKind::Expired
+      ],
+      events
+    );
+    assert!(time_container.take_events().is_empty());
+  }
+
+  #[test]
+  fn should_chain_work_and_breaks_of_pomodoro() {
+    let started = Utc.ymd(2022, 10, 20).and_hms(9, 0, 0);
+    let clock = Arc::new(ManualClock::new(started));
+    let mut time_container = TimeEntitiesController::empty().with_clock(clock.clone());
+    let settings = PomodoroSettings::new(
+      chrono::Duration::minutes(25),
+      chrono::Duration::minutes(5),
+      chrono::Duration::minutes(15),
+      2,
+    );
+    time_container.start_pomodoro("Essay", settings).unwrap();
+
+    clock.advance(chrono::Duration::minutes(25));
+    assert_eq!(vec!["Essay"], time_container.record_expiries());
+    let status = time_container.pomodoro_status("Essay").unwrap();
+    assert_eq!(PomodoroState::Break(BreakLength::Short), status.state);
+    assert_eq!(1, status.completed);
+    assert_eq!(chrono::Duration::minutes(5), status.remaining);
+
+    clock.advance(chrono::Duration::minutes(6));
+    assert_eq!(vec!["Essay break"], time_container.record_expiries());
+    let status = time_container.pomodoro_status("Essay").unwrap();
+    assert_eq!(PomodoroState::Work, status.state);
+    // Work resumed when the break was over a minute ago.
+    assert_eq!(chrono::Duration::minutes(24), status.remaining);
+
+    clock.advance(chrono::Duration::minutes(25));
+    time_container.record_expiries();
+    assert_eq!(
+      PomodoroState::Break(BreakLength::Long),
+      time_container.pomodoro_status("Essay").unwrap().state
+    );
+
+    time_container.stop_pomodoro("Essay").unwrap();
+    assert_eq!(
+      PomodoroState::Over,
+      time_container.pomodoro_status("Essay").unwrap().state
+    );
+    assert_eq!(1, time_container.stop_watches().len());
+    let report = time_container.create_report(
+      ReportRange::single_day(started.date_naive()),
+      DisplayZone::Named(chrono_tz::UTC),
+    );
+    assert_eq!(2, report.total_pomodoros());
+  }
+
+  #[test]
+  fn should_catch_up_on_pomodoro_phases_at_their_ends() {
+    let started = Utc.ymd(2022, 10, 20).and_hms(9, 0, 0);
+    let clock = Arc::new(ManualClock::new(started));
+    let mut time_container = TimeEntitiesController::empty().with_clock(clock.clone());
+    let settings = PomodoroSettings::new(
+      chrono::Duration::minutes(25),
+      chrono::Duration::minutes(5),
+      chrono::Duration::minutes(15),
+      2,
+    );
+    time_container.start_pomodoro("Essay", settings).unwrap();
+
+    // Work ends at 9:25, 9:55 and 10:35, breaks at 9:30 and 10:10.
+    clock.advance(chrono::Duration::minutes(98));
+    assert_eq!(
+      vec!["Essay", "Essay break"],
+      time_container.record_expiries()
+    );
+
+    let status = time_container.pomodoro_status("Essay").unwrap();
+    assert_eq!(PomodoroState::Break(BreakLength::Short), status.state);
+    assert_eq!(3, status.completed);
+    assert_eq!(chrono::Duration::minutes(2), status.remaining);
+    let work = time_container
+      .time_stamp_of(EntityKind::StopWatch, "Essay")
+      .unwrap();
+    assert_eq!(
+      vec![
+        started + chrono::Duration::minutes(25),
+        started + chrono::Duration::minutes(55),
+        started + chrono::Duration::minutes(95),
+      ],
+      work.get_pomodoros()
+    );
+    assert_eq!(
+      chrono::Duration::minutes(75),
+      work.get_active_duration(clock.as_ref())
+    );
+  }
+
+  #[test]
+  fn should_restart_pomodoro_which_is_over() {
+    let started = Utc.ymd(2022, 10, 20).and_hms(9, 0, 0);
+    let clock = Arc::new(ManualClock::new(started));
+    let mut time_container = TimeEntitiesController::empty().with_clock(clock.clone());
+    let settings = PomodoroSettings::default();
+    time_container.start_pomodoro("Task", settings).unwrap();
+    clock.advance(chrono::Duration::minutes(25));
+    time_container.record_expiries();
+    time_container.stop_pomodoro("Task").unwrap();
+
+    clock.advance(chrono::Duration::hours(1));
+    time_container.start_pomodoro("Task", settings).unwrap();
+
+    let status = time_container.pomodoro_status("Task").unwrap();
+    assert_eq!(PomodoroState::Work, status.state);
+    assert_eq!(1, status.completed);
+    assert_eq!(chrono::Duration::minutes(25), status.remaining);
+    time_container
+      .add_stop_watch(StopWatch::new(
+        TimeStamp::with_started("Tea", started),
+        chrono::Duration::minutes(4),
+      ))
+      .unwrap();
+    assert_eq!(
+      Err(EntityOperationError::NoPomodoro("Tea".to_string())),
+      time_container.start_pomodoro("Tea", settings)
+    );
+  }
+
+  #[test]
+  fn should_keep_stop_watch_titled_like_break_of_pomodoro() {
+    let started = Utc.ymd(2022, 10, 20).and_hms(9, 0, 0);
+    let clock = Arc::new(ManualClock::new(started));
+    let mut time_container = TimeEntitiesController::empty().with_clock(clock.clone());
+    time_container
+      .start_pomodoro("Essay", PomodoroSettings::default())
+      .unwrap();
+    let own = StopWatch::new(
+      TimeStamp::with_started("Essay break", started),
+      chrono::Duration::hours(1),
+    );
+    time_container.add_stop_watch(own.clone()).unwrap();
+
+    clock.advance(chrono::Duration::minutes(25));
+    time_container.record_expiries();
+
+    let not_break = Err(EntityOperationError::NoPomodoro("Essay break".to_string()));
+    assert_eq!(not_break, time_container.stop_pomodoro("Essay"));
+    assert_eq!(
+      not_break,
+      time_container.start_pomodoro("Essay", PomodoroSettings::default())
+    );
+    assert_eq!(
+      Some(own.get_time_stamp()),
+      time_container.time_stamp_of(EntityKind::StopWatch, "Essay break")
+    );
+  }
+
+  #[test]
+  fn should_switch_by_pausing_others_and_resuming_target() {
+    let started = Utc.ymd(2022, 10, 20).and_hms(9, 0, 0);
+    let clock = Arc::new(ManualClock::new(started));
+    let mut time_container = TimeEntitiesController::new(
+      vec![
+        TimeStamp::with_started("Coding", started),
+        TimeStamp::with_started("Mails", started),
+      ],
+      Vec::new(),
+    )
+    .with_clock(clock.clone());
+
+    let outcome = time_container.switch_to("Review", false).unwrap();
+    assert_eq!(SwitchTarget::Started, outcome.target);
+    assert_eq!(vec!["Coding", "Mails"], outcome.stopped);
+
+    clock.advance(chrono::Duration::minutes(10));
+    let outcome = time_container.switch_to(" Coding ", true).unwrap();
+    assert_eq!(SwitchTarget::Resumed, outcome.target);
+    assert_eq!(vec!["Review"], outcome.stopped);
+    assert!(time_container.time_stamps()[2].get_ended().is_some());
+
+    assert_eq!(
+      Err(EntityOperationError::AlreadyFinished),
+      time_container.switch_to("Review", false)
+    );
+    assert!(!time_container.time_stamps()[0].is_paused());
+  }
+
+  #[test]
+  fn should_pause_others_or_refuse_by_running_policy() {
+    let started = Utc.ymd(2022, 10, 20).and_hms(9, 0, 0);
+    let clock = Arc::new(ManualClock::new(started + chrono::Duration::hours(1)));
+    let time_stamps = vec![TimeStamp::with_started("Coding", started)];
+
+    let mut auto_pause = TimeEntitiesController::new(time_stamps.clone(), Vec::new())
+      .with_clock(clock.clone())
+      .with_policy(RunningPolicy::AutoPause);
+    auto_pause
+      .add_time_stamp_started_at("Call", started + chrono::Duration::minutes(40))
+      .unwrap();
+    let coding = &auto_pause.time_stamps()[0];
+    assert!(coding.is_paused());
+    assert_eq!(
+      chrono::Duration::minutes(40),
+      coding.get_active_duration(clock.as_ref())
+    );
+
+    let mut refuse = TimeEntitiesController::new(time_stamps, Vec::new())
+      .with_clock(clock.clone())
+      .with_policy(RunningPolicy::Refuse);
+    assert_eq!(
+      Err(EntityOperationError::OtherRunning("Coding".to_string())),
+      refuse.add_new_time_stamp("Call")
+    );
+    refuse.pause(EntityKind::TimeStamp, "Coding").unwrap();
+    assert_eq!(
+      Err(EntityOperationError::OtherRunning("Coding".to_string())),
+      refuse.add_time_stamp_started_at("Call", started + chrono::Duration::minutes(40))
+    );
+    refuse.add_new_time_stamp("Call").unwrap();
+    assert_eq!(
+      Err(EntityOperationError::OtherRunning("Call".to_string())),
+      refuse.resume(EntityKind::TimeStamp, "Coding")
+    );
+  }
+
+  #[test]
+  fn should_rename_only_to_free_title_and_delete() {
+    let mut time_container = TimeEntitiesController::new(create_fake_timestamps(), Vec::new());
+    let kind = EntityKind::TimeStamp;
+
+    assert_eq!(
+      Err(EntityOperationError::DuplicateTitle("2".to_string())),
+      time_container.rename(kind, "1", " 2 ")
+    );
+    time_container.rename(kind, "1", "One").unwrap();
+    time_container.delete(kind, "2").unwrap();
+
+    let titles: Vec<&str> = time_container
+      .time_stamps()
+      .iter()
+      .map(TimeStamp::get_title)
+      .collect();
+    assert_eq!(vec!["One", "3"], titles);
+  }
+
+  #[test]
+  fn should_start_new_time_stamp_at_now_of_given_clock() {
+    let now = Utc.ymd(2022, 10, 20).and_hms(9, 0, 0);
+    let mut time_container =
+      TimeEntitiesController::empty().with_clock(Arc::new(FixedClock::new(now)));
+
+    time_container.add_new_time_stamp("Now").unwrap();
+
+    assert_eq!(
+      vec![TimeStamp::with_started("Now", now)],
+      time_container.time_stamps
+    );
+  }
+
+  #[test]
+  fn should_move_entities_with_title_and_rate_of_their_project() {
+    let started = Utc.ymd(2022, 10, 20).and_hms(9, 0, 0);
+    let mut source = TimeEntitiesController::new(
+      vec![
+        TimeStamp::with_started("Audit", started).with_project(Some("Client X")),
+        TimeStamp::with_started("Lunch", started),
+      ],
+      vec![StopWatch::new(
+        TimeStamp::with_started("Audit", started),
+        Duration::minutes(30),
+      )],
+    );
+    let rate = HourlyRate::parse("90 EUR").unwrap();
+    source.set_project_rate("Client X", Some(rate.clone()));
+    let mut target =
+      TimeEntitiesController::new(vec![TimeStamp::with_started("Lunch", started)], Vec::new());
+
+    assert_eq!(Ok(2), source.move_to("Audit", &mut target));
+    assert_eq!(
+      Err(EntityOperationError::DuplicateTitle("Lunch".to_string())),
+      source.move_to("Lunch", &mut target)
+    );
+
+    assert_eq!(1, source.time_stamps().len());
+    assert!(source.stop_watches().is_empty());
+    assert_eq!(2, target.time_stamps().len());
+    assert_eq!(1, target.stop_watches().len());
+    assert_eq!(Some(&rate), target.project_rates().get("Client X"));
+    assert!(target.take_events().is_empty());
+  }
 }
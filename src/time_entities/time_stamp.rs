@@ -1,18 +1,24 @@
-use std::fmt::Display;
-
-use chrono::prelude::*;
+use chrono::{prelude::*, Duration};
 use serde::{Deserialize, Serialize};
 pub mod time_stamp_errors;
 #[cfg(test)]
 mod time_stamp_tests;
 
-use crate::{chrono_utility::DateDifference, format_utils};
+use crate::{
+  billing::HourlyRate,
+  chrono_utility::{clock::Clock, date_time_display::DateTimeDisplay, DateDifference},
+  format_utils,
+};
 
 use self::time_stamp_errors::{ResumeError, StampOperationError, StopError};
 
-use super::TimeEntity;
+use super::{
+  listing::{self, Column},
+  TimeEntity,
+};
 const ERROR_MSG_ALREADY_PAUSED: &str = "Is already stopped";
 const ERROR_MSG_ALREADY_FINISHED: &str = "Is already finished";
+const ERROR_MSG_NOT_PAUSED: &str = "Is not paused";
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct TimeStamp {
@@ -22,53 +28,190 @@ pub struct TimeStamp {
   is_paused: bool,
   last_paused: Option<DateTime<Utc>>,
   passed_paused_time: u64,
-  #[cfg(test)]
-  #[serde(skip)]
-  /// Used in tests for functions which work with current moment in time. Example Pause, Finish etc
-  /// This allow manipulation of the current moment in time manually
-  current_fake_now_moment: DateTime<Utc>,
+  /// Project the tracked time belongs to, like a customer or a product.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  project: Option<String>,
+  /// Labels to group time stamps in reports.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  tags: Vec<String>,
+  /// Rate of this time stamp which is used instead of the rate of its project.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  rate: Option<HourlyRate>,
+  /// Non-billable time is reported, but no money is billed for it.
+  #[serde(default = "billable_by_default", skip_serializing_if = "is_billable")]
+  billable: bool,
+  /// Pauses which were resumed or closed by finishing. Time stamps saved by older versions only
+  /// know the total of their paused time, so the list can be shorter than
+  /// passed_paused_time suggests.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pauses: Vec<Pause>,
+  /// Free text about the tracked work.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  note: Option<String>,
+  /// Moments at which work cycles of a pomodoro were completed.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pomodoros: Vec<DateTime<Utc>>,
+}
+
+fn billable_by_default() -> bool {
+  true
+}
+
+fn is_billable(billable: &bool) -> bool {
+  *billable
+}
+
+/// Returns the trimmed text or None if it is blank.
+fn trimmed_or_none(text: Option<&str>) -> Option<String> {
+  text
+    .map(str::trim)
+    .filter(|text| !text.is_empty())
+    .map(str::to_string)
+}
+
+/// Span of time in which a time stamp was paused.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Pause {
+  started: DateTime<Utc>,
+  ended: DateTime<Utc>,
 }
 
 impl TimeStamp {
   /// Used to show to user that a moment in time is not known yet.
   /// # Example
   /// If a time stamp is not finished yet then ended time is shown as NOT_AVAILABLE to the user.
-  const NOT_AVAILABLE: &'static str = "N/A";
-  pub fn new(title: &str) -> TimeStamp {
-    TimeStamp::with_started(title, Utc::now())
+  pub(crate) const NOT_AVAILABLE: &'static str = "N/A";
+  /// Creates a time stamp which starts at the current moment of the clock.
+  pub fn new(title: &str, clock: &dyn Clock) -> TimeStamp {
+    TimeStamp::with_started(title, clock.now())
   }
 
   pub fn with_started(title: &str, started: DateTime<Utc>) -> TimeStamp {
     TimeStamp {
-      #[cfg(test)]
-      current_fake_now_moment: started,
       title: title.trim().to_string(),
       started,
       ended: None,
       is_paused: false,
       last_paused: None,
       passed_paused_time: 0u64,
+      project: None,
+      tags: Vec::new(),
+      rate: None,
+      billable: true,
+      pauses: Vec::new(),
+      note: None,
+      pomodoros: Vec::new(),
     }
   }
 
-  fn get_text_headers() -> Vec<String> {
-    vec![
-      "Title",
-      "Started at",
-      "Ended at",
-      "Is paused",
-      "Last time paused",
-    ]
-    .iter()
-    .map(|to_str| to_str.to_string())
-    .collect()
+  /// Returns this time stamp with its own hourly rate instead of the one of its project.
+  pub fn with_rate(self, rate: Option<HourlyRate>) -> TimeStamp {
+    TimeStamp { rate, ..self }
+  }
+
+  /// Returns this time stamp marked as billable or non-billable.
+  pub fn with_billable(self, billable: bool) -> TimeStamp {
+    TimeStamp { billable, ..self }
+  }
+
+  pub fn get_rate(&self) -> Option<&HourlyRate> {
+    self.rate.as_ref()
+  }
+
+  pub fn is_billable(&self) -> bool {
+    self.billable
+  }
+
+  /// Returns this time stamp assigned to the given project. Blank names mean no project.
+  pub fn with_project(self, project: Option<&str>) -> TimeStamp {
+    TimeStamp {
+      project: trimmed_or_none(project),
+      ..self
+    }
+  }
+
+  /// Returns this time stamp with the given tags. Blank and repeated tags are left out.
+  pub fn with_tags<S: AsRef<str>>(self, tags: &[S]) -> TimeStamp {
+    let mut unique_tags: Vec<String> = Vec::new();
+    for tag in tags.iter().map(|tag| tag.as_ref().trim()) {
+      if !tag.is_empty() && !unique_tags.iter().any(|added| added == tag) {
+        unique_tags.push(tag.to_string());
+      }
+    }
+    TimeStamp {
+      tags: unique_tags,
+      ..self
+    }
+  }
+
+  pub fn get_project(&self) -> Option<&str> {
+    self.project.as_deref()
+  }
+
+  pub fn get_tags(&self) -> &[String] {
+    &self.tags
+  }
+
+  /// Returns this time stamp with a note. Blank notes mean no note.
+  pub fn with_note(self, note: Option<&str>) -> TimeStamp {
+    TimeStamp {
+      note: trimmed_or_none(note),
+      ..self
+    }
+  }
+
+  pub fn get_note(&self) -> Option<&str> {
+    self.note.as_deref()
+  }
+
+  /// Changes the title. Uniqueness of titles is checked by the owner of the time stamp.
+  pub(crate) fn set_title(&mut self, title: &str) {
+    self.title = title.trim().to_string();
+  }
+
+  pub(crate) fn set_note(&mut self, note: Option<&str>) {
+    self.note = trimmed_or_none(note);
+  }
+
+  pub fn get_pomodoros(&self) -> &[DateTime<Utc>] {
+    &self.pomodoros
+  }
+
+  pub(crate) fn complete_pomodoro(&mut self, completed: DateTime<Utc>) {
+    self.pomodoros.push(completed);
+  }
+
+  pub fn get_started(&self) -> DateTime<Utc> {
+    self.started
+  }
+
+  pub fn get_ended(&self) -> Option<DateTime<Utc>> {
+    self.ended
+  }
+
+  pub fn is_paused(&self) -> bool {
+    self.is_paused
+  }
+
+  pub fn get_last_paused(&self) -> Option<DateTime<Utc>> {
+    self.last_paused
+  }
+
+  pub(crate) fn get_text_headers() -> Vec<String> {
+    Column::TIME_STAMP_DEFAULTS
+      .iter()
+      .map(|column| column.header().to_string())
+      .collect()
   }
 
   /// Paused a time stamp. This stops the counting of the passed time on this stamp. Instead it will
   /// count the paused time until resumed, ending the pause.
   /// # Errors
   /// If stamp is already paused or finished.
-  pub fn pause(&mut self) -> Result<&DateTime<Utc>, StampOperationError<StopError>> {
+  pub fn pause(
+    &mut self,
+    clock: &dyn Clock,
+  ) -> Result<&DateTime<Utc>, StampOperationError<StopError<'_>>> {
     if self.is_paused {
       return Err(StampOperationError::new(
         ERROR_MSG_ALREADY_PAUSED,
@@ -83,20 +226,34 @@ impl TimeStamp {
       )),
       None => {
         self.is_paused = true;
-        let new_last_paused = self.get_now();
+        let new_last_paused = clock.now();
         self.last_paused = Some(new_last_paused);
         Ok(self.last_paused.as_ref().unwrap())
       }
     }
   }
 
-  pub fn resume(&mut self) -> Result<DateTime<Utc>, StampOperationError<ResumeError>> {
-    self.is_paused = false;
-
-    let now = self.get_now();
-    let difference_bet_paused_now =
-      now - self.last_paused.expect("moment of paused should be there");
-    self.passed_paused_time += difference_bet_paused_now.num_seconds() as u64;
+  /// Ends the pause of a time stamp, so its passed time is counted again.
+  /// # Errors
+  /// If the time stamp is finished or not paused.
+  pub fn resume(
+    &mut self,
+    clock: &dyn Clock,
+  ) -> Result<DateTime<Utc>, StampOperationError<ResumeError>> {
+    if self.ended.is_some() {
+      return Err(StampOperationError::new(
+        ERROR_MSG_ALREADY_FINISHED,
+        ResumeError::IsFinishedAlready,
+      ));
+    }
+    if !self.is_paused {
+      return Err(StampOperationError::new(
+        ERROR_MSG_NOT_PAUSED,
+        ResumeError::IsNotPaused,
+      ));
+    }
+    let now = clock.now();
+    self.end_pause(now);
 
     Ok(now)
   }
@@ -107,32 +264,121 @@ impl TimeStamp {
   /// This moment is always returned even if an error is returned.
   /// # Errors
   /// Calling this method a second time. Because a finished time stamp can not be finished again.
-  pub fn finish(&mut self) -> Result<&DateTime<Utc>, &DateTime<Utc>> {
-    self.is_paused = false;
+  pub fn finish(&mut self, clock: &dyn Clock) -> Result<&DateTime<Utc>, &DateTime<Utc>> {
     match self.ended {
       Some(ref ended_time) => Err(ended_time),
       None => {
-        self.ended = Some(self.get_now());
+        let now = clock.now();
+        self.end_pause(now);
+        self.ended = Some(now);
         Ok(self.ended.as_ref().unwrap())
       }
     }
   }
-  #[allow(dead_code)]
-  /// TODO: remove dead_code
-  fn get_unpaused_passed_time(&self) -> DateDifference {
-    let difference = if self.is_paused {
-      self.last_paused.unwrap() - self.started
-    } else {
-      self.get_now() - self.started
-    };
 
-    let difference_minus_paused_time = (difference.num_seconds() as u64) - self.passed_paused_time;
+  /// Takes back finishing, so the time since the moment of finishing counts as paused until
+  /// resumed. Does nothing if the time stamp is not finished.
+  pub(crate) fn reopen(&mut self) {
+    if let Some(ended) = self.ended.take() {
+      self.is_paused = true;
+      self.last_paused = Some(ended);
+    }
+  }
+
+  /// Adds the time since the last pause to the passed paused time if paused.
+  fn end_pause(&mut self, now: DateTime<Utc>) {
+    if let (true, Some(last_paused)) = (self.is_paused, self.last_paused) {
+      let difference_bet_paused_now = now - last_paused;
+      self.passed_paused_time += difference_bet_paused_now.num_seconds().max(0) as u64;
+      self.pauses.push(Pause {
+        started: last_paused,
+        ended: now.max(last_paused),
+      });
+    }
+    self.is_paused = false;
+  }
+
+  /// Moment up to which time is counted. It is the moment of finishing or now if not finished.
+  fn end_of_counting(&self, clock: &dyn Clock) -> DateTime<Utc> {
+    self.ended.unwrap_or_else(|| clock.now())
+  }
+
+  /// Time between start and now or the moment of finishing. Includes paused time.
+  pub fn get_elapsed_duration(&self, clock: &dyn Clock) -> Duration {
+    self.end_of_counting(clock) - self.started
+  }
+
+  /// Time spent paused including a pause which is still going on.
+  pub fn get_paused_duration(&self, clock: &dyn Clock) -> Duration {
+    let passed = Duration::seconds(self.passed_paused_time as i64);
+    match (self.is_paused, self.last_paused) {
+      (true, Some(last_paused)) => passed + (self.end_of_counting(clock) - last_paused),
+      _ => passed,
+    }
+  }
+
+  /// Time spent not paused between start and now or the moment of finishing.
+  pub fn get_active_duration(&self, clock: &dyn Clock) -> Duration {
+    self.get_elapsed_duration(clock) - self.get_paused_duration(clock)
+  }
+
+  /// Returns the spans of time in which this time stamp was not paused, in order.
+  /// Paused time of older versions without a known span is taken from the end of counting.
+  pub fn get_active_intervals(&self, clock: &dyn Clock) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let end = self.end_of_counting(clock);
+    let mut pauses: Vec<Pause> = self.pauses.clone();
+    if let (true, Some(last_paused)) = (self.is_paused, self.last_paused) {
+      pauses.push(Pause {
+        started: last_paused,
+        ended: end.max(last_paused),
+      });
+    }
+    pauses.sort_by_key(|pause| pause.started);
+
+    let mut intervals = Vec::new();
+    let mut cursor = self.started;
+    for pause in pauses {
+      if pause.started > cursor {
+        intervals.push((cursor, pause.started.min(end)));
+      }
+      cursor = cursor.max(pause.ended);
+    }
+    if end > cursor {
+      intervals.push((cursor, end));
+    }
 
-    DateDifference::new(difference_minus_paused_time)
+    let known_paused: i64 = self
+      .pauses
+      .iter()
+      .map(|pause| (pause.ended - pause.started).num_seconds())
+      .sum();
+    let unknown_paused = self.passed_paused_time as i64 - known_paused;
+    let mut to_cut = Duration::seconds(unknown_paused.max(0));
+    while to_cut > Duration::zero() {
+      match intervals.pop() {
+        Some((from, to)) if to - from > to_cut => {
+          intervals.push((from, to - to_cut));
+          to_cut = Duration::zero();
+        }
+        Some((from, to)) => to_cut = to_cut - (to - from),
+        None => break,
+      }
+    }
+
+    intervals.retain(|(from, to)| to > from);
+    intervals
   }
 
-  pub fn create_text_table_from_time_stamps(data: &Vec<TimeStamp>) -> String {
-    let mut text_data = TimeStamp::many_to_text(data);
+  pub fn get_unpaused_passed_time(&self, clock: &dyn Clock) -> DateDifference {
+    DateDifference::from(self.get_active_duration(clock))
+  }
+
+  pub fn create_text_table_from_time_stamps(
+    data: &[TimeStamp],
+    display: &DateTimeDisplay,
+    clock: &dyn Clock,
+  ) -> String {
+    let mut text_data = TimeStamp::many_to_text(data, display, clock);
     let headers = TimeStamp::get_text_headers();
 
     text_data.insert(0, headers);
@@ -141,10 +387,14 @@ impl TimeStamp {
 
   /// Creates a list of text columns from time stamps. Useful for preparing time stamps for
   /// other functions to print tables.
-  fn many_to_text(to_convert: &[TimeStamp]) -> Vec<Vec<String>> {
+  fn many_to_text(
+    to_convert: &[TimeStamp],
+    display: &DateTimeDisplay,
+    clock: &dyn Clock,
+  ) -> Vec<Vec<String>> {
     let mut to_return: Vec<Vec<String>> = Vec::new();
     for time_stamp in to_convert {
-      let columns = time_stamp.to_str_vec();
+      let columns = time_stamp.to_str_vec(display, clock);
       to_return.push(columns);
     }
     to_return
@@ -154,64 +404,16 @@ impl TimeStamp {
   /// - Title: Label of timestamp/stopwatch.
   /// - Started: Time at which it was started.
   /// - Ended: Time at wich the stopwatch has ended or the timestamp was finished.
-  /// N/A if it was not ended yet
+  ///   N/A if it was not ended yet
   /// - Is paused: yes for stopped. no if not stopped.
-  /// - Time left: Seconds left until stopwatch is done. N/A if not stopwatch
-  fn to_str_vec(&self) -> Vec<String> {
-    let mut output: Vec<String> = Vec::new();
-    output.push(self.title.clone());
-    output.push(TimeStamp::time_to_str(self.started));
-
-    TimeStamp::push_text_date_time(&mut output, self.ended);
-
-    output.push(if self.is_paused {
-      "yes".to_string()
-    } else {
-      "no".to_string()
-    });
-
-    TimeStamp::push_text_date_time(&mut output, self.last_paused);
-
-    output
-  }
-
-  fn push_text_date_time<T: Display>(to_push_on: &mut Vec<String>, date_time: Option<T>) {
-    if let Some(left_time) = date_time {
-      to_push_on.push(left_time.to_string())
-    } else {
-      to_push_on.push(TimeStamp::NOT_AVAILABLE.to_string())
-    }
-  }
-
-  /// Returns an easy to read text presentation of date time.
-  /// The date is shown as date, (month, day, year) and
-  /// then time (hour minutes seconds) from left to right.
-  fn time_to_str(date: DateTime<Utc>) -> String {
-    let seconds = format_utils::with_at_least_2_digits(date.second() as u64);
-    let minutes = format_utils::with_at_least_2_digits(date.minute() as u64);
-    let hours = format_utils::with_at_least_2_digits(date.hour() as u64);
-    let days = format_utils::with_at_least_2_digits(date.day() as u64);
-    let months = format_utils::with_at_least_2_digits(date.month() as u64);
-
-    format!(
-      "On {}.{}.{} at {}:{}:{}",
-      months,
-      days,
-      date.year(),
-      hours,
-      minutes,
-      seconds
-    )
-  }
-
-  #[cfg(not(test))]
-  fn get_now(&self) -> DateTime<Utc> {
-    Utc::now()
-  }
-
-  #[cfg(test)]
-  fn get_now(&self) -> DateTime<Utc> {
-    self.current_fake_now_moment
+  /// - Elapsed: Time since started up to now or until ended including paused time.
+  /// - Paused total: Time spent paused.
+  /// - Active: Elapsed time without paused time.
+  ///
+  /// Every moment is shown in the time zone and format of param display. Durations are counted
+  /// up to the current moment of param clock.
+  pub(crate) fn to_str_vec(&self, display: &DateTimeDisplay, clock: &dyn Clock) -> Vec<String> {
+    listing::cells(self, &Column::TIME_STAMP_DEFAULTS, display, clock)
   }
 }
 
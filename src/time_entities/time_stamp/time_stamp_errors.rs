@@ -9,7 +9,7 @@ pub struct StampOperationError<T> {
 
 impl<T> StampOperationError<T> {
   pub fn get_error_msg(&self) -> &str {
-    &self.error_message
+    self.error_message
   }
 
   pub fn new(error_message: &'static str, error_kind: T) -> Self {
@@ -31,6 +31,7 @@ pub enum StopError<'a> {
 pub enum ResumeError {
   IsFinishedAlready,
   IsStoppedAlready,
+  IsNotPaused,
 }
 
 impl<T> Display for StampOperationError<T> {
@@ -2,25 +2,20 @@ use std::ops::Add;
 
 use chrono::Duration;
 
-use crate::chrono_utility;
+use crate::chrono_utility::{self, clock::ManualClock, date_time_display::DisplayZone};
 
 use super::*;
 
-impl TimeStamp {
-  fn set_new(&mut self, date: DateTime<Utc>) {
-    self.current_fake_now_moment = date;
-  }
-
-  fn add_duration(&mut self, to_add: chrono::Duration) {
-    self.current_fake_now_moment += to_add;
-  }
+/// Shows moments in UTC so tests do not depend on the local time zone of the system.
+fn utc_display() -> DateTimeDisplay {
+  DateTimeDisplay::new(DisplayZone::Named(chrono_tz::UTC), Default::default())
 }
 
 #[test]
 fn should_finish_on_2_hours_later() {
-  let (mut actual_data, expected_ended) = setup_finish();
+  let (mut actual_data, expected_ended, clock) = setup_finish();
 
-  match actual_data.finish() {
+  match actual_data.finish(&clock) {
     Ok(ended_time) => assert_eq!(&expected_ended, ended_time),
     Err(_) => panic!("Failure on finished time stamp"),
   };
@@ -29,13 +24,13 @@ fn should_finish_on_2_hours_later() {
 #[test]
 fn should_return_error_on_already_finished() {
   // Set up
-  let (mut actual_data, expected_ended) = setup_finish();
-  actual_data.finish().unwrap();
+  let (mut actual_data, expected_ended, clock) = setup_finish();
+  actual_data.finish(&clock).unwrap();
   // Change current time and see if ended time remains the same
-  actual_data.set_new(expected_ended.add(Duration::hours(2)));
+  clock.set(expected_ended.add(Duration::hours(2)));
 
   // Act
-  match actual_data.finish() {
+  match actual_data.finish(&clock) {
     // Assert
     Ok(_) => panic!("Should return error on already ended time stamp"),
     Err(actual_time) => assert_eq!(
@@ -45,13 +40,13 @@ fn should_return_error_on_already_finished() {
   }
 }
 
-fn setup_finish() -> (TimeStamp, DateTime<Utc>) {
+fn setup_finish() -> (TimeStamp, DateTime<Utc>, ManualClock) {
   let started = Utc.ymd(2012, 8, 8).and_hms(2, 2, 2);
-  let mut actual_data = TimeStamp::with_started("2 Hours later ...", started.clone());
+  let actual_data = TimeStamp::with_started("2 Hours later ...", started);
   let expected_ended = started.add(Duration::hours(2));
-  actual_data.set_new(expected_ended);
+  let clock = ManualClock::new(expected_ended);
 
-  (actual_data, expected_ended)
+  (actual_data, expected_ended, clock)
 }
 
 #[test]
@@ -60,7 +55,7 @@ fn should_convert_to_str_vec() {
   let started = Utc.ymd(2014, 8, 24).and_hms(18, 8, 24);
   let actual_data = TimeStamp::with_started(title, started);
 
-  let actual_vec = actual_data.to_str_vec();
+  let actual_vec = actual_data.to_str_vec(&utc_display(), &ManualClock::new(started));
 
   assert_eq!(
     actual_vec,
@@ -70,18 +65,12 @@ fn should_convert_to_str_vec() {
       TimeStamp::NOT_AVAILABLE.to_string(),
       "no".to_string(),
       TimeStamp::NOT_AVAILABLE.to_string(),
+      "00:00:00".to_string(),
+      "00:00:00".to_string(),
+      "00:00:00".to_string(),
     ]
   );
 }
-#[test]
-fn should_convert_to_right_format_from_data() {
-  let time = Utc.ymd(2014, 7, 8).and_hms(22, 45, 21);
-
-  let actual_text_format = TimeStamp::time_to_str(time);
-  const EXPECTED: &str = "On 07.08.2014 at 22:45:21";
-  assert_eq!(EXPECTED, actual_text_format);
-}
-
 #[test]
 fn should_return_table_for_time_stamps() {
   let input = vec![
@@ -95,11 +84,15 @@ fn should_return_table_for_time_stamps() {
     ),
   ];
 
-  let actual_table = TimeStamp::create_text_table_from_time_stamps(&input);
+  let actual_table = TimeStamp::create_text_table_from_time_stamps(
+    &input,
+    &utc_display(),
+    &ManualClock::new(Utc.ymd(2022, 2, 1).and_hms(12, 32, 34)),
+  );
   let expected =
-    "Title                      Started at                 Ended at  Is paused  Last time paused  
-1. Line with more content  On 02.01.2018 at 14:12:24  N/A       no         N/A               
-2. Line with more content  On 02.01.2022 at 12:32:34  N/A       no         N/A               
+    "Title                      Started at                 Ended at  Is paused  Last time paused  Elapsed      Paused total  Active
+1. Line with more content  On 02.01.2018 at 14:12:24  N/A       no         N/A               35062:20:10  00:00:00      35062:20:10
+2. Line with more content  On 02.01.2022 at 12:32:34  N/A       no         N/A               00:00:00     00:00:00      00:00:00
 "
     .to_string();
   for (expected_side, actual_side) in expected.lines().zip(actual_table.lines()) {
@@ -110,10 +103,11 @@ fn should_return_table_for_time_stamps() {
 #[test]
 fn should_pause_time_stamp() {
   let start_moment = Utc.ymd(2000, 2, 1).and_hms(2, 1, 1);
-  let mut to_stop = TimeStamp::with_started("To stop.", start_moment.clone());
+  let mut to_stop = TimeStamp::with_started("To stop.", start_moment);
+  let clock = ManualClock::new(start_moment);
   let expected_paused_time = start_moment.add(Duration::hours(2));
-  to_stop.set_new(expected_paused_time);
-  let result = to_stop.pause();
+  clock.set(expected_paused_time);
+  let result = to_stop.pause(&clock);
 
   match result {
     Ok(paused) => assert_eq!(
@@ -127,12 +121,13 @@ fn should_pause_time_stamp() {
 #[test]
 fn should_return_error_pausing_on_already_paused() {
   let start_moment = Utc.ymd(2000, 2, 1).and_hms(2, 1, 1);
-  let mut to_stop = TimeStamp::with_started("To stop.", start_moment.clone());
+  let mut to_stop = TimeStamp::with_started("To stop.", start_moment);
+  let clock = ManualClock::new(start_moment);
   let after_paused_time = start_moment.add(Duration::hours(2));
-  _ = to_stop.pause();
+  _ = to_stop.pause(&clock);
 
-  to_stop.set_new(after_paused_time);
-  let result = to_stop.pause();
+  clock.set(after_paused_time);
+  let result = to_stop.pause(&clock);
   match result {
     Ok(_) => panic!("Should return an error for pausing an already paused one."),
     Err(paused) => {
@@ -150,11 +145,12 @@ fn should_return_error_pausing_on_already_paused() {
 #[test]
 fn should_return_error_pausing_on_already_finished() {
   let start_moment = Utc.ymd(2000, 2, 1).and_hms(2, 1, 1);
-  let mut to_stop = TimeStamp::with_started("To stop.", start_moment.clone());
+  let mut to_stop = TimeStamp::with_started("To stop.", start_moment);
+  let clock = ManualClock::new(start_moment);
 
-  to_stop.finish().unwrap();
+  to_stop.finish(&clock).unwrap();
 
-  let result = to_stop.pause();
+  let result = to_stop.pause(&clock);
   match result {
     Ok(_) => panic!("Should return an error for pausing an already finished one."),
     Err(paused) => {
@@ -169,32 +165,56 @@ fn should_return_error_pausing_on_already_finished() {
   }
 }
 
+#[test]
+fn should_return_error_resuming_not_paused_or_finished() {
+  let start_moment = Utc.ymd(2000, 2, 1).and_hms(2, 1, 1);
+  let mut to_resume = TimeStamp::with_started("To resume.", start_moment);
+  let clock = ManualClock::new(start_moment);
+
+  match to_resume.resume(&clock) {
+    Ok(_) => panic!("Should return an error for resuming a running one."),
+    Err(error) => {
+      assert!(matches!(error.error_kind, ResumeError::IsNotPaused));
+      assert_eq!(ERROR_MSG_NOT_PAUSED, error.get_error_msg());
+    }
+  }
+
+  to_resume.pause(&clock).unwrap();
+  to_resume.finish(&clock).unwrap();
+
+  match to_resume.resume(&clock) {
+    Ok(_) => panic!("Should return an error for resuming a finished one."),
+    Err(error) => assert!(matches!(error.error_kind, ResumeError::IsFinishedAlready)),
+  }
+}
+
 #[test]
 fn should_return_difference_between_started_and_now() {
   let started = Utc.ymd(2000, 1, 1).and_hms(0, 0, 0);
-  let mut time_stamp = TimeStamp::with_started("1", started);
+  let time_stamp = TimeStamp::with_started("1", started);
+  let clock = ManualClock::new(started);
   let now_total_secs = chrono_utility::duration_with_hms(4, 2, 20);
-  let now_after_init = started.add(now_total_secs.clone());
+  let now_after_init = started.add(now_total_secs);
 
-  time_stamp.set_new(now_after_init);
+  clock.set(now_after_init);
 
-  let actual = time_stamp.get_unpaused_passed_time();
+  let actual = time_stamp.get_unpaused_passed_time(&clock);
 
-  let expected = DateDifference::new(now_total_secs.num_seconds() as u64);
+  let expected = DateDifference::new(now_total_secs.num_seconds());
   assert_eq!(expected, actual);
 }
 
 #[test]
 fn should_return_difference_between_started_and_paused() {
   let duration_up_to_pause = chrono_utility::duration_with_hms(4, 2, 20);
-  let time_stamp = setup_actual_for_time_difference_with_pause(
+  let (time_stamp, clock) = setup_actual_for_time_difference_with_pause(
     Utc.ymd(2000, 1, 1).and_hms(0, 0, 0),
     duration_up_to_pause,
     chrono_utility::duration_with_hms(2, 2, 2),
   );
-  let actual = time_stamp.get_unpaused_passed_time();
+  let actual = time_stamp.get_unpaused_passed_time(&clock);
 
-  let expected = DateDifference::new(duration_up_to_pause.num_seconds() as u64);
+  let expected = DateDifference::new(duration_up_to_pause.num_seconds());
   assert_eq!(
     expected, actual,
     "Should only return time difference between started and paused moment"
@@ -204,19 +224,19 @@ fn should_return_difference_between_started_and_paused() {
 #[test]
 fn should_return_difference_between_started_paused_unpaused() {
   let duration_before_pause = chrono_utility::duration_with_hms(1, 20, 33);
-  let mut time_stamp = setup_actual_for_time_difference_with_pause(
+  let (mut time_stamp, clock) = setup_actual_for_time_difference_with_pause(
     Utc.ymd(2000, 2, 2).and_hms(1, 1, 1),
     duration_before_pause,
     chrono_utility::duration_with_hms(8, 4, 0),
   );
 
-  time_stamp.resume().unwrap();
+  time_stamp.resume(&clock).unwrap();
   let duration_after_resume = chrono_utility::duration_with_hms(3, 3, 3);
-  time_stamp.add_duration(duration_after_resume);
-  let actual = time_stamp.get_unpaused_passed_time();
+  clock.advance(duration_after_resume);
+  let actual = time_stamp.get_unpaused_passed_time(&clock);
 
   let expected_duration = duration_before_pause.add(duration_after_resume);
-  let expected = DateDifference::new(expected_duration.num_seconds() as u64);
+  let expected = DateDifference::new(expected_duration.num_seconds());
 
   assert_eq!(
     expected, actual,
@@ -228,14 +248,110 @@ fn setup_actual_for_time_difference_with_pause(
   started: DateTime<Utc>,
   duration_up_to_pause: Duration,
   duration_after_paused: Duration,
-) -> TimeStamp {
+) -> (TimeStamp, ManualClock) {
   let mut time_stamp = TimeStamp::with_started("1", started);
+  let clock = ManualClock::new(started);
 
-  time_stamp.set_new(started.add(duration_up_to_pause));
-  time_stamp.pause().unwrap();
+  clock.set(started.add(duration_up_to_pause));
+  time_stamp.pause(&clock).unwrap();
 
   // See if passing time does not alter returned time difference after pause.
-  time_stamp.set_new(time_stamp.get_now().add(duration_after_paused));
+  clock.advance(duration_after_paused);
+
+  (time_stamp, clock)
+}
+
+#[test]
+fn should_count_pause_going_on_while_finished() {
+  let started = Utc.ymd(2000, 2, 2).and_hms(1, 0, 0);
+  let mut time_stamp = TimeStamp::with_started("1", started);
+  let clock = ManualClock::new(started);
+  clock.set(started.add(Duration::hours(1)));
+  time_stamp.pause(&clock).unwrap();
+  clock.set(started.add(Duration::hours(3)));
+  time_stamp.finish(&clock).unwrap();
+
+  // Time passing after finishing should not change any duration.
+  clock.advance(Duration::hours(5));
+
+  assert_eq!(Duration::hours(3), time_stamp.get_elapsed_duration(&clock));
+  assert_eq!(Duration::hours(2), time_stamp.get_paused_duration(&clock));
+  assert_eq!(Duration::hours(1), time_stamp.get_active_duration(&clock));
+}
+
+#[test]
+fn should_count_ongoing_pause_in_paused_total() {
+  let started = Utc.ymd(2000, 2, 2).and_hms(1, 0, 0);
+  let (mut time_stamp, clock) = setup_actual_for_time_difference_with_pause(
+    started,
+    Duration::minutes(30),
+    Duration::minutes(45),
+  );
+
+  assert_eq!(
+    Duration::minutes(75),
+    time_stamp.get_elapsed_duration(&clock)
+  );
+  assert_eq!(
+    Duration::minutes(45),
+    time_stamp.get_paused_duration(&clock)
+  );
+
+  time_stamp.resume(&clock).unwrap();
+  clock.advance(Duration::minutes(10));
+
+  assert_eq!(
+    Duration::minutes(85),
+    time_stamp.get_elapsed_duration(&clock)
+  );
+  assert_eq!(
+    Duration::minutes(45),
+    time_stamp.get_paused_duration(&clock)
+  );
+  assert_eq!(
+    Duration::minutes(40),
+    time_stamp.get_active_duration(&clock)
+  );
+}
+
+#[test]
+fn should_return_active_intervals_around_pauses() {
+  let started = Utc.ymd(2000, 2, 2).and_hms(1, 0, 0);
+  let (mut time_stamp, clock) =
+    setup_actual_for_time_difference_with_pause(started, Duration::hours(1), Duration::hours(2));
+  time_stamp.resume(&clock).unwrap();
+  clock.advance(Duration::hours(1));
 
-  time_stamp
+  assert_eq!(
+    vec![
+      (started, started.add(Duration::hours(1))),
+      (
+        started.add(Duration::hours(3)),
+        started.add(Duration::hours(4))
+      ),
+    ],
+    time_stamp.get_active_intervals(&clock)
+  );
+}
+
+#[test]
+fn should_take_paused_time_without_known_pauses_from_end() {
+  let saved_by_older_version = r#"{
+    "title": "Old",
+    "started": "2000-02-02T10:00:00Z",
+    "ended": "2000-02-02T14:00:00Z",
+    "is_paused": false,
+    "last_paused": "2000-02-02T11:00:00Z",
+    "passed_paused_time": 3600
+  }"#;
+  let time_stamp: TimeStamp = serde_json::from_str(saved_by_older_version).unwrap();
+  let clock = ManualClock::new(Utc.ymd(2000, 2, 3).and_hms(0, 0, 0));
+
+  assert_eq!(
+    vec![(
+      Utc.ymd(2000, 2, 2).and_hms(10, 0, 0),
+      Utc.ymd(2000, 2, 2).and_hms(13, 0, 0)
+    )],
+    time_stamp.get_active_intervals(&clock)
+  );
 }